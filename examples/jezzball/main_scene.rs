@@ -360,7 +360,7 @@ pub fn init_balls(world: &mut ScionWorld, assets: &JezzBallAssets) {
                 ColliderMask::Custom("BORDER_LEFT".to_string()),
                 ColliderMask::Custom("BORDER_RIGHT".to_string()),
             ],
-            ColliderType::SquareCollider(38),
+            ColliderType::CircleCollider(19),
         )
         .with_offset(Vector::new(-5., -5.)),
         ball_animations(),
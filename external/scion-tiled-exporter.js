@@ -75,6 +75,7 @@ function buildScionMap(map) {
         height: map.height,
         tile_width: map.tileWidth,
         tile_height: map.tileHeight,
+        tilemap_type: buildTilemapType(map),
         properties: map.properties(),
         layers: buildLayers(map, tilesets),
         objects: buildObjects(map),
@@ -82,6 +83,31 @@ function buildScionMap(map) {
     };
 }
 
+function buildTilemapType(map) {
+    const stagger = {
+        stagger_axis: map.staggerAxis === TileMap.StaggerX ? "X" : "Y",
+        stagger_index: map.staggerIndex === TileMap.StaggerEven ? "Even" : "Odd"
+    };
+    switch (map.orientation) {
+        case TileMap.Isometric:
+            // Same diamond placement as Tiled, see the importer of Scion
+            return {
+                Isometric: {
+                    offset_x: {x_multiplier: map.tileWidth / 2, y_multiplier: -map.tileWidth / 2, z_multiplier: 0},
+                    offset_y: {x_multiplier: -map.tileHeight / 2, y_multiplier: map.tileWidth - map.tileHeight / 2, z_multiplier: 0},
+                    offset_z: {x_multiplier: 0, y_multiplier: 0, z_multiplier: 0}
+                }
+            };
+        case TileMap.Hexagonal:
+            stagger.side_length = map.hexSideLength;
+            return {Hexagonal: stagger};
+        case TileMap.Staggered:
+            return {Staggered: stagger};
+        default:
+            return "Standard";
+    }
+}
+
 function buildLayers(map, tilesets) {
     let layers = [];
     let flatLayers = flattenLayers(map.layers);
@@ -127,10 +153,33 @@ function buildSingleLayer(layer, tilesets) {
     return {
         name: layer.name,
         tiles_encoded: Base64.encode(JSON.stringify(layerTiles)),
-        properties: layer.properties(),
+        properties: buildLayerProperties(layer),
     };
 }
 
+// Rendering attributes of the layer, stored as the properties read by Scion's LayerSettings
+function buildLayerProperties(layer) {
+    let properties = layer.properties();
+    if (!layer.visible) {
+        properties.visible = "false";
+    }
+    if (layer.opacity < 1) {
+        properties.opacity = String(layer.opacity);
+    }
+    if (layer.tintColor && layer.tintColor.valid && String(layer.tintColor).toLowerCase() !== "#ffffff") {
+        // Tiled writes the alpha of the colors first, Scion last
+        const tint = String(layer.tintColor);
+        properties.tint = tint.length === 9 ? "#" + tint.substring(3) + tint.substring(1, 3) : tint;
+    }
+    if (layer.parallaxFactor && layer.parallaxFactor.x !== 1) {
+        properties.parallax_x = String(layer.parallaxFactor.x);
+    }
+    if (layer.parallaxFactor && layer.parallaxFactor.y !== 1) {
+        properties.parallax_y = String(layer.parallaxFactor.y);
+    }
+    return properties;
+}
+
 function findMatchingTileset(tile, tilesets) {
     return tilesets.find((tileset) => tileset.name === tile.tileset.name);
 }
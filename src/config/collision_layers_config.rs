@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Collision layers configuration used by Scion.
+///
+/// `layers` lists the custom layer names to register at startup, and `matrix` lists, for a layer name,
+/// the names of the layers it collides with. Built-in masks can be referenced by their names
+/// (`Character`, `Bullet`, `Death`, `Landscape`, `Item`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CollisionLayersConfig {
+    pub layers: Vec<String>,
+    pub matrix: HashMap<String, Vec<String>>,
+}
@@ -6,7 +6,7 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::config::{logger_config::LoggerConfig, window_config::WindowConfig};
+use crate::config::{collision_layers_config::CollisionLayersConfig, logger_config::LoggerConfig, window_config::WindowConfig};
 
 /// Main configuration used by `crate::Scion` to configure the game.
 /// Please use [`ScionConfigBuilder`] if you want to build if from code.
@@ -18,6 +18,8 @@ pub struct ScionConfig {
     pub(crate) logger_config: Option<LoggerConfig>,
     /// Window configuration to use.
     pub(crate) window_config: Option<WindowConfig>,
+    /// Collision layers and collision matrix to register at startup.
+    pub(crate) collision_layers: Option<CollisionLayersConfig>,
 }
 
 impl Default for ScionConfig {
@@ -26,6 +28,7 @@ impl Default for ScionConfig {
             app_name: "Scion game".to_string(),
             logger_config: Some(Default::default()),
             window_config: Some(Default::default()),
+            collision_layers: None,
         }
     }
 }
@@ -65,6 +68,12 @@ impl ScionConfigBuilder {
         self
     }
 
+    /// Sets the collision layers and collision matrix registered at startup
+    pub fn with_collision_layers_config(mut self, collision_layers: CollisionLayersConfig) -> Self {
+        self.config.collision_layers = Some(collision_layers);
+        self
+    }
+
     /// Retrieves the configuration built
     pub fn get(self) -> ScionConfig {
         self.config
@@ -1,5 +1,6 @@
 use crate::config::scion_config::ScionConfig;
 use crate::core::package::Package;
+use crate::core::resources::collision_layers::CollisionLayers;
 use crate::core::scene::{Scene, SceneMachine};
 use crate::core::scheduler::Scheduler;
 use crate::core::state::GameState;
@@ -25,7 +26,11 @@ impl ScionBuilder {
             scene: Default::default(),
             world: Default::default(),
         };
-        builder.with_package(InternalPackage)
+        let mut builder = builder.with_package(InternalPackage);
+        if let Some(collision_layers) = builder.config.collision_layers.as_ref() {
+            builder.world.insert_resource(CollisionLayers::from_config(collision_layers));
+        }
+        builder
     }
 
     /// Specify a system to add to the scheduler.
@@ -40,6 +45,13 @@ impl ScionBuilder {
         self
     }
 
+    /// Specify a system to add to the scheduler, executed at each fixed update (60 times per second),
+    /// after the scene's `on_fixed_update`.
+    pub fn with_fixed_system(mut self, system: fn(&mut GameData)) -> Self {
+        self.scheduler.add_fixed_system(system);
+        self
+    }
+
     /// Set the scene to the given one. Only one scene can be executed at a time
     pub fn with_scene<T: Scene + Default + Send + 'static>(mut self) -> Self {
         self.scene = Some(Box::<T>::default());
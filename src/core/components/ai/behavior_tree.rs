@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use hecs::Entity;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::components::ai::BehaviorError;
+use crate::core::resources::behavior_registry::BehaviorRegistry;
+use crate::core::world::GameData;
+
+/// Leaf of a behavior tree doing something, possibly over several ticks
+pub type BehaviorAction = Arc<dyn Fn(Entity, &mut GameData, &mut Blackboard) -> BehaviorStatus + Send + Sync>;
+/// Leaf of a behavior tree checking something
+pub type BehaviorCondition = Arc<dyn Fn(Entity, &GameData, &Blackboard) -> bool + Send + Sync>;
+
+/// Result of the tick of a behavior tree node
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BehaviorStatus {
+    Success,
+    Failure,
+    /// The node needs more ticks to finish, it will be resumed at the next tick
+    Running,
+}
+
+/// `Blackboard` is the memory of a behavior tree, shared by all its nodes. Values are stored as JSON values,
+/// so that any serializable value can be stored, and blackboards can be loaded from JSON.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Blackboard {
+    values: HashMap<String, Value>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_value<T: Serialize>(mut self, key: &str, value: T) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    pub fn set<T: Serialize>(&mut self, key: &str, value: T) {
+        let value = serde_json::to_value(value).expect("Blackboard values must be serializable");
+        self.values.insert(key.to_string(), value);
+    }
+
+    /// Value of `key`, `None` if it is missing or of another type
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.values.get(key).and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+}
+
+enum NodeKind {
+    Sequence(Vec<BehaviorNode>),
+    Selector(Vec<BehaviorNode>),
+    Inverter(Box<BehaviorNode>),
+    AlwaysSucceed(Box<BehaviorNode>),
+    AlwaysFail(Box<BehaviorNode>),
+    Repeat(Option<usize>, Box<BehaviorNode>),
+    Action(BehaviorAction),
+    Condition(BehaviorCondition),
+}
+
+/// Node of a behavior tree.
+///
+/// Sequences and selectors remember their running child, and resume from it at the next tick.
+pub struct BehaviorNode {
+    kind: NodeKind,
+    /// Running child of a sequence or a selector, done iterations of a repeat
+    progress: usize,
+}
+
+impl BehaviorNode {
+    fn from_kind(kind: NodeKind) -> Self {
+        Self { kind, progress: 0 }
+    }
+
+    /// Ticks the children in order, until one of them fails. Succeeds if all of them succeed.
+    pub fn sequence(children: Vec<BehaviorNode>) -> Self {
+        Self::from_kind(NodeKind::Sequence(children))
+    }
+
+    /// Ticks the children in order, until one of them succeeds. Fails if all of them fail.
+    pub fn selector(children: Vec<BehaviorNode>) -> Self {
+        Self::from_kind(NodeKind::Selector(children))
+    }
+
+    /// Swaps the success and the failure of `child`
+    pub fn inverter(child: BehaviorNode) -> Self {
+        Self::from_kind(NodeKind::Inverter(Box::new(child)))
+    }
+
+    /// Succeeds when `child` is done, whatever its result
+    pub fn always_succeed(child: BehaviorNode) -> Self {
+        Self::from_kind(NodeKind::AlwaysSucceed(Box::new(child)))
+    }
+
+    /// Fails when `child` is done, whatever its result
+    pub fn always_fail(child: BehaviorNode) -> Self {
+        Self::from_kind(NodeKind::AlwaysFail(Box::new(child)))
+    }
+
+    /// Runs `child` until it succeeded `count` times, one run per tick at most. Fails as soon as `child` fails.
+    pub fn repeat(count: usize, child: BehaviorNode) -> Self {
+        Self::from_kind(NodeKind::Repeat(Some(count), Box::new(child)))
+    }
+
+    /// Runs `child` again and again, until it fails
+    pub fn repeat_until_failure(child: BehaviorNode) -> Self {
+        Self::from_kind(NodeKind::Repeat(None, Box::new(child)))
+    }
+
+    pub fn action(action: impl Fn(Entity, &mut GameData, &mut Blackboard) -> BehaviorStatus + Send + Sync + 'static) -> Self {
+        Self::from_kind(NodeKind::Action(Arc::new(action)))
+    }
+
+    pub fn condition(condition: impl Fn(Entity, &GameData, &Blackboard) -> bool + Send + Sync + 'static) -> Self {
+        Self::from_kind(NodeKind::Condition(Arc::new(condition)))
+    }
+
+    pub(crate) fn tick(&mut self, entity: Entity, data: &mut GameData, blackboard: &mut Blackboard) -> BehaviorStatus {
+        match &mut self.kind {
+            NodeKind::Sequence(children) => {
+                tick_children(children, &mut self.progress, BehaviorStatus::Success, entity, data, blackboard)
+            }
+            NodeKind::Selector(children) => {
+                tick_children(children, &mut self.progress, BehaviorStatus::Failure, entity, data, blackboard)
+            }
+            NodeKind::Inverter(child) => match child.tick(entity, data, blackboard) {
+                BehaviorStatus::Success => BehaviorStatus::Failure,
+                BehaviorStatus::Failure => BehaviorStatus::Success,
+                BehaviorStatus::Running => BehaviorStatus::Running,
+            },
+            NodeKind::AlwaysSucceed(child) => match child.tick(entity, data, blackboard) {
+                BehaviorStatus::Running => BehaviorStatus::Running,
+                _ => BehaviorStatus::Success,
+            },
+            NodeKind::AlwaysFail(child) => match child.tick(entity, data, blackboard) {
+                BehaviorStatus::Running => BehaviorStatus::Running,
+                _ => BehaviorStatus::Failure,
+            },
+            NodeKind::Repeat(count, child) => match child.tick(entity, data, blackboard) {
+                BehaviorStatus::Running => BehaviorStatus::Running,
+                BehaviorStatus::Failure => {
+                    self.progress = 0;
+                    BehaviorStatus::Failure
+                }
+                BehaviorStatus::Success => {
+                    self.progress += 1;
+                    if count.is_some_and(|count| self.progress >= count) {
+                        self.progress = 0;
+                        BehaviorStatus::Success
+                    } else {
+                        BehaviorStatus::Running
+                    }
+                }
+            },
+            NodeKind::Action(action) => action(entity, data, blackboard),
+            NodeKind::Condition(condition) => match condition(entity, data, blackboard) {
+                true => BehaviorStatus::Success,
+                false => BehaviorStatus::Failure,
+            },
+        }
+    }
+}
+
+/// `BehaviorTree` is a behavior tree component, ticked each frame by `Scion`, from its root.
+///
+/// While the tree is ticked, its root and its blackboard are detached from the component : the actions
+/// and conditions receive the blackboard as a parameter instead.
+pub struct BehaviorTree {
+    root: Option<BehaviorNode>,
+    blackboard: Blackboard,
+    status: Option<BehaviorStatus>,
+}
+
+impl BehaviorTree {
+    pub fn new(root: BehaviorNode) -> Self {
+        Self { root: Some(root), blackboard: Blackboard::default(), status: None }
+    }
+
+    /// Loads a behavior tree from JSON, using the actions and conditions registered by name in `registry`.
+    ///
+    /// ```json
+    /// {
+    ///   "blackboard": { "speed": 3 },
+    ///   "root": { "type": "Selector", "children": [
+    ///     { "type": "Sequence", "children": [
+    ///       { "type": "Condition", "name": "player_near" },
+    ///       { "type": "Action", "name": "attack" }
+    ///     ]},
+    ///     { "type": "Repeat", "count": 3, "child": { "type": "Action", "name": "wander" } }
+    ///   ]}
+    /// }
+    /// ```
+    pub fn from_json(json: &str, registry: &BehaviorRegistry) -> Result<Self, BehaviorError> {
+        let data: BehaviorTreeData = serde_json::from_str(json).map_err(|e| BehaviorError::Parsing(e.to_string()))?;
+        Ok(Self::new(data.root.into_node(registry)?).with_blackboard(data.blackboard))
+    }
+
+    pub fn with_blackboard(mut self, blackboard: Blackboard) -> Self {
+        self.blackboard = blackboard;
+        self
+    }
+
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.blackboard
+    }
+
+    /// Status returned by the root at the last tick, `None` before the first tick
+    pub fn last_status(&self) -> Option<BehaviorStatus> {
+        self.status
+    }
+
+    pub(crate) fn detach(&mut self) -> Option<(BehaviorNode, Blackboard)> {
+        self.root.take().map(|root| (root, std::mem::take(&mut self.blackboard)))
+    }
+
+    pub(crate) fn attach(&mut self, root: BehaviorNode, blackboard: Blackboard, status: BehaviorStatus) {
+        self.root = Some(root);
+        self.blackboard = blackboard;
+        self.status = Some(status);
+    }
+}
+
+/// Ticks `children` from the running one, while they return `continue_on`, which is also the result when all of them did
+fn tick_children(
+    children: &mut [BehaviorNode],
+    progress: &mut usize,
+    continue_on: BehaviorStatus,
+    entity: Entity,
+    data: &mut GameData,
+    blackboard: &mut Blackboard,
+) -> BehaviorStatus {
+    while *progress < children.len() {
+        let status = children[*progress].tick(entity, data, blackboard);
+        if status == BehaviorStatus::Running {
+            return status;
+        }
+        if status != continue_on {
+            *progress = 0;
+            return status;
+        }
+        *progress += 1;
+    }
+    *progress = 0;
+    continue_on
+}
+
+#[derive(Deserialize)]
+struct BehaviorTreeData {
+    #[serde(default)]
+    blackboard: Blackboard,
+    root: NodeData,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum NodeData {
+    Sequence { children: Vec<NodeData> },
+    Selector { children: Vec<NodeData> },
+    Inverter { child: Box<NodeData> },
+    AlwaysSucceed { child: Box<NodeData> },
+    AlwaysFail { child: Box<NodeData> },
+    Repeat { count: Option<usize>, child: Box<NodeData> },
+    Action { name: String },
+    Condition { name: String },
+}
+
+impl NodeData {
+    fn into_node(self, registry: &BehaviorRegistry) -> Result<BehaviorNode, BehaviorError> {
+        let children = |children: Vec<NodeData>| children.into_iter().map(|c| c.into_node(registry)).collect::<Result<Vec<_>, _>>();
+        let kind = match self {
+            NodeData::Sequence { children: c } => NodeKind::Sequence(children(c)?),
+            NodeData::Selector { children: c } => NodeKind::Selector(children(c)?),
+            NodeData::Inverter { child } => NodeKind::Inverter(Box::new(child.into_node(registry)?)),
+            NodeData::AlwaysSucceed { child } => NodeKind::AlwaysSucceed(Box::new(child.into_node(registry)?)),
+            NodeData::AlwaysFail { child } => NodeKind::AlwaysFail(Box::new(child.into_node(registry)?)),
+            NodeData::Repeat { count, child } => NodeKind::Repeat(count, Box::new(child.into_node(registry)?)),
+            NodeData::Action { name } => NodeKind::Action(registry.action(&name)?),
+            NodeData::Condition { name } => NodeKind::Condition(registry.condition(&name)?),
+        };
+        Ok(BehaviorNode::from_kind(kind))
+    }
+}
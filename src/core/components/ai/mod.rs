@@ -0,0 +1,15 @@
+//! Reusable building blocks for the logic of non player characters : a finite state machine and a behavior tree.
+//! Both can be written in code or loaded from JSON, using callbacks registered by name in the
+//! [`crate::core::resources::behavior_registry::BehaviorRegistry`]. Behavior trees are ticked each frame by `Scion`,
+//! state machines by the [`state_machine::StateMachines`] package added for their type of states.
+pub mod behavior_tree;
+pub mod state_machine;
+
+/// `BehaviorError` represents the errors that can happen while loading a state machine or a behavior tree from JSON
+#[derive(Debug)]
+pub enum BehaviorError {
+    /// The JSON doesn't describe a valid state machine or behavior tree
+    Parsing(String),
+    /// A callback, guard, action or condition is not registered in the `BehaviorRegistry`
+    UnknownCallback(String),
+}
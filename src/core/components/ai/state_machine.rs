@@ -0,0 +1,269 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use hecs::Entity;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::core::components::ai::BehaviorError;
+use crate::core::package::Package;
+use crate::core::resources::behavior_registry::BehaviorRegistry;
+use crate::core::systems::ai_systems::state_machine_system;
+use crate::core::world::GameData;
+use crate::ScionBuilder;
+
+/// Callback called when a state machine enters, updates or exits a state
+pub type StateCallback = Arc<dyn Fn(Entity, &mut GameData) + Send + Sync>;
+/// Condition allowing a state machine transition
+pub type TransitionGuard = Arc<dyn Fn(Entity, &GameData) -> bool + Send + Sync>;
+
+#[derive(Default, Clone)]
+struct StateCallbacks {
+    on_enter: Option<StateCallback>,
+    on_update: Option<StateCallback>,
+    on_exit: Option<StateCallback>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Transition {
+    /// `None` for the transitions allowed from any state
+    from: Option<usize>,
+    to: usize,
+    guard: TransitionGuard,
+}
+
+/// `StateMachine` is a finite state machine component, ticked each frame by the `StateMachines<S>` package
+/// registered for its type of states `S`, usually an enum.
+///
+/// At each tick, the first transition of the current state whose guard is true is taken, calling the `on_exit`
+/// callback of the current state then the `on_enter` callback of the new one. The `on_update` callback of the
+/// current state is then called. Transitions declared with `with_any_transition` are checked from every state,
+/// after the transitions of the current state.
+///
+/// While the guards are evaluated, the transitions are detached from the component, so guards can read
+/// the state machine of their entity.
+pub struct StateMachine<S> {
+    /// States, indexed like the callbacks
+    states: Vec<S>,
+    callbacks: Vec<StateCallbacks>,
+    transitions: Vec<Transition>,
+    current: usize,
+    entered: bool,
+    requested: Option<usize>,
+    time_in_state: f32,
+}
+
+impl<S: PartialEq + Send + Sync + 'static> StateMachine<S> {
+    /// Creates a state machine starting in the `initial` state
+    pub fn new(initial: S) -> Self {
+        Self {
+            states: vec![initial],
+            callbacks: vec![StateCallbacks::default()],
+            transitions: vec![],
+            current: 0,
+            entered: false,
+            requested: None,
+            time_in_state: 0.,
+        }
+    }
+
+    /// Loads a state machine from JSON, using the callbacks and guards registered by name in `registry`.
+    ///
+    /// ```json
+    /// {
+    ///   "initial": "Idle",
+    ///   "states": [{ "state": "Chase", "on_enter": "start_running", "on_update": "chase_player" }],
+    ///   "transitions": [
+    ///     { "from": "Idle", "to": "Chase", "guard": "player_visible" },
+    ///     { "to": "Dead", "guard": "no_health" }
+    ///   ]
+    /// }
+    /// ```
+    pub fn from_json(json: &str, registry: &BehaviorRegistry) -> Result<Self, BehaviorError>
+    where
+        S: DeserializeOwned,
+    {
+        let data: StateMachineData<S> = serde_json::from_str(json).map_err(|e| BehaviorError::Parsing(e.to_string()))?;
+        let mut machine = StateMachine::new(data.initial);
+        for state in data.states {
+            let index = machine.index_of(state.state);
+            let callback = |name: Option<String>| name.map(|n| registry.callback(&n)).transpose();
+            machine.callbacks[index] = StateCallbacks {
+                on_enter: callback(state.on_enter)?,
+                on_update: callback(state.on_update)?,
+                on_exit: callback(state.on_exit)?,
+            };
+        }
+        for transition in data.transitions {
+            let from = transition.from.map(|from| machine.index_of(from));
+            let to = machine.index_of(transition.to);
+            machine.transitions.push(Transition { from, to, guard: registry.guard(&transition.guard)? });
+        }
+        Ok(machine)
+    }
+
+    /// Sets the callback called when the machine enters `state`
+    pub fn with_on_enter(
+        mut self,
+        state: S,
+        callback: impl Fn(Entity, &mut GameData) + Send + Sync + 'static,
+    ) -> Self {
+        let index = self.index_of(state);
+        self.callbacks[index].on_enter = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the callback called at each tick while the machine is in `state`
+    pub fn with_on_update(
+        mut self,
+        state: S,
+        callback: impl Fn(Entity, &mut GameData) + Send + Sync + 'static,
+    ) -> Self {
+        let index = self.index_of(state);
+        self.callbacks[index].on_update = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the callback called when the machine leaves `state`
+    pub fn with_on_exit(
+        mut self,
+        state: S,
+        callback: impl Fn(Entity, &mut GameData) + Send + Sync + 'static,
+    ) -> Self {
+        let index = self.index_of(state);
+        self.callbacks[index].on_exit = Some(Arc::new(callback));
+        self
+    }
+
+    /// Adds a transition from `from` to `to`, taken when `guard` is true
+    pub fn with_transition(
+        mut self,
+        from: S,
+        to: S,
+        guard: impl Fn(Entity, &GameData) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let (from, to) = (self.index_of(from), self.index_of(to));
+        self.transitions.push(Transition { from: Some(from), to, guard: Arc::new(guard) });
+        self
+    }
+
+    /// Adds a transition from any state to `to`, taken when `guard` is true
+    pub fn with_any_transition(
+        mut self,
+        to: S,
+        guard: impl Fn(Entity, &GameData) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let to = self.index_of(to);
+        self.transitions.push(Transition { from: None, to, guard: Arc::new(guard) });
+        self
+    }
+
+    /// Current state of the machine
+    pub fn current_state(&self) -> &S {
+        &self.states[self.current]
+    }
+
+    /// Whether the machine is currently in `state`
+    pub fn is_in(&self, state: &S) -> bool {
+        self.current_state() == state
+    }
+
+    /// Seconds spent in the current state
+    pub fn time_in_state(&self) -> f32 {
+        self.time_in_state
+    }
+
+    /// Forces a transition to `state` at the next tick, whatever the guards
+    pub fn request_state(&mut self, state: S) {
+        self.requested = Some(self.index_of(state));
+    }
+
+    /// Index of `state`, declaring it if needed
+    fn index_of(&mut self, state: S) -> usize {
+        match self.states.iter().position(|s| *s == state) {
+            Some(index) => index,
+            None => {
+                self.states.push(state);
+                self.callbacks.push(StateCallbacks::default());
+                self.states.len() - 1
+            }
+        }
+    }
+
+    /// Takes the transitions out of the machine, with its current state and the requested one, to evaluate the guards
+    pub(crate) fn detach(&mut self) -> (usize, Option<usize>, Vec<Transition>) {
+        (self.current, self.requested.take(), std::mem::take(&mut self.transitions))
+    }
+
+    /// Puts back the detached transitions, moves to `next` if any, and returns the callbacks to call for this tick,
+    /// in order. The machine is already in its new state when the callbacks are called.
+    pub(crate) fn attach(&mut self, transitions: Vec<Transition>, next: Option<usize>, delta: f32) -> Vec<StateCallback> {
+        self.transitions = transitions;
+        let mut callbacks = Vec::new();
+        if !self.entered {
+            self.entered = true;
+            callbacks.extend(self.callbacks[self.current].on_enter.clone());
+        }
+        match next {
+            Some(next) => {
+                callbacks.extend(self.callbacks[self.current].on_exit.clone());
+                callbacks.extend(self.callbacks[next].on_enter.clone());
+                self.current = next;
+                self.time_in_state = 0.;
+            }
+            None => self.time_in_state += delta,
+        }
+        callbacks.extend(self.callbacks[self.current].on_update.clone());
+        callbacks
+    }
+}
+
+/// First transition from `current` whose guard is true, the transitions from any state being checked last
+pub(crate) fn next_state(transitions: &[Transition], current: usize, entity: Entity, data: &GameData) -> Option<usize> {
+    transitions
+        .iter()
+        .filter(|t| t.from == Some(current))
+        .chain(transitions.iter().filter(|t| t.from.is_none() && t.to != current))
+        .find(|t| (t.guard)(entity, data))
+        .map(|t| t.to)
+}
+
+/// This package adds the system ticking the `StateMachine<S>` components. It must be added once for each type of states.
+pub struct StateMachines<S>(PhantomData<fn() -> S>);
+
+impl<S> Default for StateMachines<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: PartialEq + Send + Sync + 'static> Package for StateMachines<S> {
+    fn load(&self, builder: ScionBuilder) -> ScionBuilder {
+        builder.with_system(state_machine_system::<S>)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "S: Deserialize<'de>"))]
+struct StateMachineData<S> {
+    initial: S,
+    #[serde(default)]
+    states: Vec<StateData<S>>,
+    #[serde(default)]
+    transitions: Vec<TransitionData<S>>,
+}
+
+#[derive(Deserialize)]
+struct StateData<S> {
+    state: S,
+    on_enter: Option<String>,
+    on_update: Option<String>,
+    on_exit: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TransitionData<S> {
+    from: Option<S>,
+    to: S,
+    guard: String,
+}
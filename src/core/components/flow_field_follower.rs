@@ -0,0 +1,34 @@
+/// `FlowFieldFollower` makes an entity move, cell after cell, along a flow field of the `FlowFields` resource.
+///
+/// The entity samples the field at its global translation each frame and moves toward the center of the next cell,
+/// so a single field can steer any number of entities toward its goals.
+#[derive(Debug, Clone)]
+pub struct FlowFieldFollower {
+    pub(crate) field: String,
+    pub(crate) speed: f32,
+    pub(crate) arrived: bool,
+}
+
+impl FlowFieldFollower {
+    /// Creates a follower of the field registered as `field`, moving at `speed` world units per second
+    pub fn new(field: &str, speed: f32) -> Self {
+        Self { field: field.to_string(), speed, arrived: false }
+    }
+
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Whether the entity is in one of the goal cells of its field
+    pub fn has_arrived(&self) -> bool {
+        self.arrived
+    }
+}
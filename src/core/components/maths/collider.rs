@@ -1,12 +1,17 @@
 use geo_clipper::Clipper;
 use geo_types::{Coord, LineString};
 use hecs::Entity;
+use serde::{Deserialize, Serialize};
 
 use crate::core::components::maths::{coordinates::Coordinates, transform::Transform, Pivot};
-use crate::utils::maths::{centroid_polygon, rotate_point_around_pivot, Vector};
+use crate::core::resources::collision_layers::CollisionLayers;
+use crate::utils::maths::{centroid_points, centroid_polygon, rotate_point_around_pivot, Vector};
 
-/// `ColliderMask` will serve as a 'mask' to allow filter while collisions happen
-#[derive(PartialEq, Clone, Eq, Hash, Debug)]
+/// `ColliderMask` will serve as a 'mask' to allow filter while collisions happen.
+///
+/// Each mask is a collision layer, see [`crate::core::resources::collision_layers::CollisionLayers`].
+/// The named variants are kept for compatibility and use the layers 0 to 5.
+#[derive(PartialEq, Clone, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum ColliderMask {
     None,
     Character,
@@ -14,15 +19,41 @@ pub enum ColliderMask {
     Death,
     Landscape,
     Item,
+    /// Named layer, given a layer number when registered in `CollisionLayers`
     Custom(String),
+    /// Numeric layer, from 6 to 63 as the layers 0 to 5 are used by the built-in variants.
+    /// It can't be a layer already given to a `Custom` name, see `CollisionLayers`
+    Layer(u8),
+}
+
+impl ColliderMask {
+    /// Retrieves the mask using this name : a built-in variant name, or a custom one
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "None" => ColliderMask::None,
+            "Character" => ColliderMask::Character,
+            "Bullet" => ColliderMask::Bullet,
+            "Death" => ColliderMask::Death,
+            "Landscape" => ColliderMask::Landscape,
+            "Item" => ColliderMask::Item,
+            _ => ColliderMask::Custom(name.to_string()),
+        }
+    }
 }
 
 /// `ColliderType` will determine the shape of the collider.
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ColliderType {
     SquareCollider(usize),
     RectangleCollider(usize, usize),
-    PolygonCollider(Vec<Coordinates>)
+    PolygonCollider(Vec<Coordinates>),
+    /// Circle using its radius. Like other colliders, its origin is the top left of its bounding box
+    CircleCollider(usize),
+    /// Capsule using its width and height. Round ends are on the smallest side
+    CapsuleCollider(usize, usize),
+    /// Collider to add on a `Tilemap` entity. Each cell whose pathing value is contained in `solid_pathings`
+    /// is solid, with a size of `tile_width` x `tile_height`. Rotation of the tilemap is not supported.
+    TilemapGridCollider { tile_width: usize, tile_height: usize, solid_pathings: Vec<String> },
 }
 
 /// The main collider representation to add to an entity, using the new function
@@ -32,10 +63,28 @@ pub struct Collider {
     collider_type: ColliderType,
     collision_filter: Vec<ColliderMask>,
     collisions: Vec<Collision>,
+    /// Entities and masks this collider was colliding with during the previous frame
+    previous_collisions: Vec<(Entity, ColliderMask)>,
+    ended_collisions: Vec<(Entity, ColliderMask)>,
     offset: Vector,
     debug_lines: bool,
     local_pivot: Option<Pivot>,
     parent_pivot: Option<Pivot>,
+    static_collider: bool,
+    /// Solid cells of a `TilemapGridCollider`, merged as rows of (x, y, length)
+    grid_cells: Vec<(usize, usize, usize)>,
+    /// Size in tiles of a `TilemapGridCollider`
+    grid_dimensions: (usize, usize),
+    grid_dirty: bool,
+    /// Bit of the collider mask layer, and bits of its filters layers. Resolved using `CollisionLayers`
+    layer: Option<u8>,
+    filter_bits: u64,
+    layers_resolved: bool,
+    continuous: bool,
+    clamp_on_impact: bool,
+    ongoing_events: bool,
+    /// Global translation of the collider during the previous collision computation, used by continuous detection
+    previous_translation: Option<Coordinates>,
 }
 
 impl Collider {
@@ -51,10 +100,23 @@ impl Collider {
             collider_type,
             collision_filter,
             collisions: vec![],
+            previous_collisions: vec![],
+            ended_collisions: vec![],
             offset: Vector::default(),
             debug_lines: false,
             local_pivot: None,
             parent_pivot: None,
+            static_collider: false,
+            grid_cells: vec![],
+            grid_dimensions: (0, 0),
+            grid_dirty: true,
+            layer: None,
+            filter_bits: 0,
+            layers_resolved: false,
+            continuous: false,
+            clamp_on_impact: false,
+            ongoing_events: false,
+            previous_translation: None,
         }
     }
 
@@ -73,6 +135,118 @@ impl Collider {
         self
     }
 
+    /// Flags this collider as static, meaning that it is not expected to move (walls, tilemap colliders..).
+    /// Static colliders shapes are cached across frames, and only computed again when their transform changes.
+    pub fn with_static(mut self) -> Self {
+        self.static_collider = true;
+        self
+    }
+
+    /// Asks a `TilemapGridCollider` to read again the pathing values of its tilemap.
+    /// This is done automatically when using `Tilemap::modify_sprite_tile`.
+    pub fn refresh_grid(&mut self) {
+        self.grid_dirty = true;
+    }
+
+    pub(crate) fn is_grid_dirty(&self) -> bool {
+        self.grid_dirty
+    }
+
+    /// Sets the solid cells of a `TilemapGridCollider`, merging contiguous cells of a same row
+    pub(crate) fn set_grid_cells(&mut self, width: usize, height: usize, solid: &[(usize, usize)]) {
+        let mut solid = solid.to_vec();
+        solid.sort_by_key(|(x, y)| (*y, *x));
+        solid.dedup();
+        let mut rows: Vec<(usize, usize, usize)> = Vec::new();
+        for (x, y) in solid {
+            match rows.last_mut() {
+                Some((row_x, row_y, length)) if *row_y == y && *row_x + *length == x => *length += 1,
+                _ => rows.push((x, y, 1)),
+            }
+        }
+        self.grid_cells = rows;
+        self.grid_dimensions = (width, height);
+        self.grid_dirty = false;
+    }
+
+    /// Enables continuous collision detection : the whole movement since the previous frame is checked, so that
+    /// fast colliders can't go through thin ones. Such collisions have a `time_of_impact`.
+    pub fn with_continuous_detection(mut self) -> Self {
+        self.continuous = true;
+        self
+    }
+
+    /// Enables continuous collision detection, and moves the entity back to the first contact point when an impact
+    /// is found along its movement. For rigid bodies, the physics also clamps each step to the contact point.
+    pub fn with_continuous_clamping(mut self) -> Self {
+        self.continuous = true;
+        self.clamp_on_impact = true;
+        self
+    }
+
+    /// Publishes an `Ongoing` event on the [`COLLISIONS_TOPIC`] for each collision of this collider lasting since
+    /// the previous frame. By default only the `Started` and `Ended` events are published, as resting contacts would
+    /// otherwise fill the topic every frame
+    pub fn with_ongoing_events(mut self) -> Self {
+        self.ongoing_events = true;
+        self
+    }
+
+    pub fn publishes_ongoing_events(&self) -> bool {
+        self.ongoing_events
+    }
+
+    /// Whether or not continuous collision detection is enabled for this collider
+    pub fn is_continuous(&self) -> bool {
+        self.continuous
+    }
+
+    pub fn clamps_on_impact(&self) -> bool {
+        self.clamp_on_impact
+    }
+
+    /// Forgets the previous position of a continuous collider, for example after a teleportation,
+    /// so that the movement isn't checked until the next frame
+    pub fn reset_continuous_detection(&mut self) {
+        self.previous_translation = None;
+    }
+
+    pub(crate) fn previous_translation(&self) -> Option<Coordinates> {
+        self.previous_translation
+    }
+
+    pub(crate) fn set_previous_translation(&mut self, translation: Coordinates) {
+        self.previous_translation = Some(translation);
+    }
+
+    pub(crate) fn layers_resolved(&self) -> bool {
+        self.layers_resolved
+    }
+
+    /// Computes the layer and the filter bits of this collider
+    pub(crate) fn resolve_layers(&mut self, layers: &mut CollisionLayers) {
+        self.layer = layers.layer_or_register(&self.collider_mask);
+        self.filter_bits = layers.bits(&self.collision_filter);
+        self.layers_resolved = true;
+    }
+
+    pub(crate) fn layer(&self) -> Option<u8> {
+        self.layer
+    }
+
+    pub(crate) fn layer_bits(&self) -> u64 {
+        self.layer.map_or(0, |layer| 1 << layer)
+    }
+
+    pub(crate) fn filter_bits(&self) -> u64 {
+        self.filter_bits
+    }
+
+    /// Whether or not this collider has been flagged as static
+    pub fn is_static(&self) -> bool {
+        self.static_collider
+    }
+
     /// Return whether or not this collider colliding to any other collider ?
     pub fn is_colliding(&self) -> bool {
         !self.collisions.is_empty()
@@ -83,6 +257,25 @@ impl Collider {
         &self.collisions
     }
 
+    /// Returns the collisions that started this frame
+    pub fn collision_started(&self) -> Vec<&Collision> {
+        self.collisions.iter().filter(|c| !self.was_colliding_with(&c.other_entity)).collect()
+    }
+
+    /// Returns the collisions that already existed during the previous frame
+    pub fn collision_ongoing(&self) -> Vec<&Collision> {
+        self.collisions.iter().filter(|c| self.was_colliding_with(&c.other_entity)).collect()
+    }
+
+    /// Returns the entities, and their masks, this collider stopped colliding with this frame
+    pub fn collision_ended(&self) -> &Vec<(Entity, ColliderMask)> {
+        &self.ended_collisions
+    }
+
+    fn was_colliding_with(&self, entity: &Entity) -> bool {
+        self.previous_collisions.iter().any(|(e, _)| e == entity)
+    }
+
     /// The mask of this collider
     pub fn mask(&self) -> &ColliderMask {
         &self.collider_mask
@@ -113,7 +306,19 @@ impl Collider {
     }
 
     pub(crate) fn clear_collisions(&mut self) {
-        self.collisions.clear();
+        self.previous_collisions = self.collisions.drain(..).map(|c| (c.other_entity, c.mask)).collect();
+        self.ended_collisions.clear();
+    }
+
+    /// Computes the collisions that ended, once every collision of the frame has been added
+    pub(crate) fn compute_ended_collisions(&mut self) {
+        let collisions = &self.collisions;
+        self.ended_collisions = self
+            .previous_collisions
+            .iter()
+            .filter(|(e, _)| !collisions.iter().any(|c| c.other_entity == *e))
+            .cloned()
+            .collect();
     }
     pub(crate) fn set_parent_pivot(&mut self, parent_pivot: Pivot) {
         self.parent_pivot = Some(parent_pivot);
@@ -129,15 +334,19 @@ impl Collider {
         }
     }
 
+    fn pivot_point(&self, base_x: f32, base_y: f32) -> Coordinates {
+        match self.get_pivot() {
+            Pivot::TopLeft => { Coordinates::new(base_x, base_y) }
+            Pivot::Center => { centroid_polygon(&self.collider_coordinates(base_x, base_y)) }
+            Pivot::Custom(x,y) => {Coordinates::new(base_x + x,base_y + y)}
+        }
+    }
+
     pub(crate) fn collider_polygon(&self, transform: &Transform) -> geo_types::Polygon::<f32> {
         let base_x = transform.global_translation.x + self.offset.x;
         let base_y = transform.global_translation.y + self.offset.y;
         let vec = self.collider_coordinates(base_x, base_y);
-        let pivot_point = match self.get_pivot() {
-            Pivot::TopLeft => { Coordinates::new(base_x, base_y) }
-            Pivot::Center => { centroid_polygon(&vec) }
-            Pivot::Custom(x,y) => {Coordinates::new(base_x + x,base_y + y)}
-        };
+        let pivot_point = self.pivot_point(base_x, base_y);
 
         let coords: Vec<Coord<f32>> = vec.iter().map(|c| rotate_point_around_pivot(c, &pivot_point, transform.global_angle))
             .map(|c| {
@@ -149,6 +358,60 @@ impl Collider {
         geo_types::Polygon::<f32>::new(LineString::<f32>(coords), vec![])
     }
 
+    /// Computes the shape used by the narrowphase, in world coordinates
+    pub(crate) fn collider_shape(&self, transform: &Transform) -> ColliderShape {
+        let base_x = transform.global_translation.x + self.offset.x;
+        let base_y = transform.global_translation.y + self.offset.y;
+        match self.collider_type() {
+            ColliderType::CircleCollider(_) | ColliderType::CapsuleCollider(_, _) => {
+                let (start, end, radius) = self.round_segment(base_x, base_y);
+                let pivot_point = self.pivot_point(base_x, base_y);
+                ColliderShape::Round {
+                    start: rotate_point_around_pivot(&start, &pivot_point, transform.global_angle),
+                    end: rotate_point_around_pivot(&end, &pivot_point, transform.global_angle),
+                    radius,
+                }
+            }
+            ColliderType::TilemapGridCollider { .. } => {
+                ColliderShape::Grid(self.grid_rectangles(base_x, base_y).iter().map(|r| to_geo_polygon(r)).collect())
+            }
+            _ => ColliderShape::Polygon(self.collider_polygon(transform)),
+        }
+    }
+
+    /// Segment and radius describing a round collider : every point closer than radius from the segment is inside
+    fn round_segment(&self, base_x: f32, base_y: f32) -> (Coordinates, Coordinates, f32) {
+        let (width, height) = match self.collider_type() {
+            ColliderType::CircleCollider(radius) => (*radius as f32 * 2., *radius as f32 * 2.),
+            ColliderType::CapsuleCollider(width, height) => (*width as f32, *height as f32),
+            _ => (0., 0.),
+        };
+        let radius = width.min(height) / 2.;
+        let start = Coordinates::new(base_x + radius, base_y + radius);
+        let end = if height >= width {
+            Coordinates::new(base_x + radius, base_y + height - radius)
+        } else {
+            Coordinates::new(base_x + width - radius, base_y + radius)
+        };
+        (start, end, radius)
+    }
+
+    /// Rectangles covering the solid cells of a `TilemapGridCollider`
+    pub(crate) fn grid_rectangles(&self, base_x: f32, base_y: f32) -> Vec<Vec<Coordinates>> {
+        if let ColliderType::TilemapGridCollider { tile_width, tile_height, .. } = self.collider_type() {
+            let (w, h) = (*tile_width as f32, *tile_height as f32);
+            self.grid_cells
+                .iter()
+                .map(|(x, y, length)| {
+                    let (x, y) = (base_x + *x as f32 * w, base_y + *y as f32 * h);
+                    rectangle_coordinates(x, y, *length as f32 * w, h)
+                })
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+
     pub(crate) fn collider_coordinates(&self, base_x: f32, base_y: f32) -> Vec<Coordinates> {
         match self.collider_type() {
             ColliderType::SquareCollider(size) => {
@@ -170,14 +433,24 @@ impl Collider {
             ColliderType::PolygonCollider(coordinates) => {
                 coordinates.iter().map(|c|  Coordinates::new(base_x + c.x, base_y + c.y)).collect()
             }
+            ColliderType::CircleCollider(_) | ColliderType::CapsuleCollider(_, _) => {
+                let (start, end, radius) = self.round_segment(base_x, base_y);
+                round_outline(&start, &end, radius)
+            }
+            ColliderType::TilemapGridCollider { tile_width, tile_height, .. } => {
+                let (width, height) = self.grid_dimensions;
+                rectangle_coordinates(base_x, base_y, (width * tile_width) as f32, (height * tile_height) as f32)
+            }
         }
     }
 
 
+    #[cfg(test)]
     pub(crate) fn can_collide_with(&self, other: &Collider) -> bool {
         self.collision_filter.is_empty() || self.collision_filter.contains(&other.collider_mask)
     }
 
+    #[cfg(test)]
     pub(crate) fn collides_with(
         &self,
         self_transform: &Transform,
@@ -188,39 +461,597 @@ impl Collider {
             return None;
         }
 
-        let self_polygon = self.collider_polygon(self_transform);
-        let target_polygon = target_collider.collider_polygon(target_transform);
-        let result = self_polygon.intersection(&target_polygon, 1.0);
+        self.collider_shape(self_transform).intersection(&target_collider.collider_shape(target_transform))
+    }
+
+    pub(crate) fn add_collisions(&mut self, collisions: &mut Vec<Collision>) {
+        self.collisions.append(collisions);
+    }
+}
+
+/// Narrowphase of the collision detection : computes the intersection area of two collider polygons
+pub(crate) fn polygons_intersection(polygon: &geo_types::Polygon<f32>, target: &geo_types::Polygon<f32>) -> Option<CollisionArea> {
+    let result = polygon.intersection(target, 1.0);
+    result.0.first().map(|collision| CollisionArea {
+        coordinates: collision.exterior().0.iter().map(|c| Coordinates::new(c.x, c.y)).collect()
+    })
+}
+
+/// Number of segments used to approximate a half circle
+const HALF_CIRCLE_SEGMENTS: usize = 12;
+
+fn rectangle_coordinates(x: f32, y: f32, width: f32, height: f32) -> Vec<Coordinates> {
+    vec![
+        Coordinates::new(x, y),
+        Coordinates::new(x + width, y),
+        Coordinates::new(x + width, y + height),
+        Coordinates::new(x, y + height),
+    ]
+}
 
-        if !result.0.is_empty() {
-            let collision = result.0.first().unwrap();
-            let coordinates: Vec<Coordinates> = collision.exterior().0.iter().map(|c| Coordinates::new(c.x, c.y)).collect();
-            Some(CollisionArea{
-                coordinates
+fn to_geo_polygon(coordinates: &[Coordinates]) -> geo_types::Polygon<f32> {
+    geo_types::Polygon::<f32>::new(
+        LineString::<f32>(coordinates.iter().map(|c| Coord { x: c.x, y: c.y }).collect()),
+        vec![],
+    )
+}
+
+/// Approximated outline of a round shape, used for debug lines and collision areas
+fn round_outline(start: &Coordinates, end: &Coordinates, radius: f32) -> Vec<Coordinates> {
+    let base_angle = (end.y - start.y).atan2(end.x - start.x) + std::f32::consts::FRAC_PI_2;
+    let half_circle = |center: Coordinates, from: f32| {
+        (0..=HALF_CIRCLE_SEGMENTS).map(move |i| {
+            let angle = from + std::f32::consts::PI * i as f32 / HALF_CIRCLE_SEGMENTS as f32;
+            Coordinates::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+        })
+    };
+    half_circle(*start, base_angle).chain(half_circle(*end, base_angle + std::f32::consts::PI)).collect()
+}
+
+fn distance(a: &Coordinates, b: &Coordinates) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+/// Closest point from `point` on the segment [a, b]
+fn closest_point_on_segment(point: &Coordinates, a: &Coordinates, b: &Coordinates) -> Coordinates {
+    let (abx, aby) = (b.x - a.x, b.y - a.y);
+    let length = abx * abx + aby * aby;
+    if length == 0. {
+        return *a;
+    }
+    let t = (((point.x - a.x) * abx + (point.y - a.y) * aby) / length).clamp(0., 1.);
+    Coordinates::new(a.x + t * abx, a.y + t * aby)
+}
+
+fn segments_intersect(a: &Coordinates, b: &Coordinates, c: &Coordinates, d: &Coordinates) -> bool {
+    let cross = |o: &Coordinates, p: &Coordinates, q: &Coordinates| (p.x - o.x) * (q.y - o.y) - (p.y - o.y) * (q.x - o.x);
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    ((d1 > 0. && d2 < 0.) || (d1 < 0. && d2 > 0.)) && ((d3 > 0. && d4 < 0.) || (d3 < 0. && d4 > 0.))
+}
+
+/// Closest points between the segments [a, b] and [c, d]
+fn closest_points_between_segments(
+    a: &Coordinates,
+    b: &Coordinates,
+    c: &Coordinates,
+    d: &Coordinates,
+) -> (Coordinates, Coordinates) {
+    if segments_intersect(a, b, c, d) {
+        let point = closest_point_on_segment(a, c, d);
+        return (point, point);
+    }
+    [
+        (*a, closest_point_on_segment(a, c, d)),
+        (*b, closest_point_on_segment(b, c, d)),
+        (closest_point_on_segment(c, a, b), *c),
+        (closest_point_on_segment(d, a, b), *d),
+    ]
+    .iter()
+    .min_by(|(p1, q1), (p2, q2)| distance(p1, q1).total_cmp(&distance(p2, q2)))
+    .copied()
+    .expect("Closest points candidates can't be empty")
+}
+
+fn polygon_contains(polygon: &geo_types::Polygon<f32>, point: &Coordinates) -> bool {
+    let points = &polygon.exterior().0;
+    let mut inside = false;
+    for i in 0..points.len() {
+        let (p, q) = (points[i], points[(i + points.len() - 1) % points.len()]);
+        if (p.y > point.y) != (q.y > point.y) && point.x < (q.x - p.x) * (point.y - p.y) / (q.y - p.y) + p.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Shape of a collider in world coordinates, used by the narrowphase of the collision detection
+#[derive(Clone, Debug)]
+pub(crate) enum ColliderShape {
+    Polygon(geo_types::Polygon<f32>),
+    /// Every point closer than `radius` from the segment [start, end]. A circle has `start` == `end`
+    Round { start: Coordinates, end: Coordinates, radius: f32 },
+    Grid(Vec<geo_types::Polygon<f32>>),
+}
+
+impl ColliderShape {
+    pub(crate) fn aabb(&self) -> Aabb {
+        match self {
+            ColliderShape::Polygon(polygon) => Aabb::from_polygon(polygon),
+            ColliderShape::Round { start, end, radius } => Aabb::new(
+                start.x.min(end.x) - radius,
+                start.y.min(end.y) - radius,
+                start.x.max(end.x) + radius,
+                start.y.max(end.y) + radius,
+            ),
+            ColliderShape::Grid(cells) => cells.iter().map(Aabb::from_polygon).reduce(|a, b| {
+                Aabb::new(a.min_x.min(b.min_x), a.min_y.min(b.min_y), a.max_x.max(b.max_x), a.max_y.max(b.max_y))
+            }).unwrap_or_else(|| Aabb::new(f32::MAX, f32::MAX, f32::MIN, f32::MIN)),
+        }
+    }
+
+    /// Moves the shape by the given offsets
+    pub(crate) fn translate(&mut self, dx: f32, dy: f32) {
+        let translate_polygon = |polygon: &mut geo_types::Polygon<f32>| {
+            polygon.exterior_mut(|exterior| exterior.0.iter_mut().for_each(|c| {
+                c.x += dx;
+                c.y += dy;
+            }));
+        };
+        match self {
+            ColliderShape::Polygon(polygon) => translate_polygon(polygon),
+            ColliderShape::Round { start, end, .. } => {
+                *start = Coordinates::new(start.x + dx, start.y + dy);
+                *end = Coordinates::new(end.x + dx, end.y + dy);
+            }
+            ColliderShape::Grid(cells) => cells.iter_mut().for_each(translate_polygon),
+        }
+    }
+
+    /// Whether or not the point is inside this shape
+    pub(crate) fn contains_point(&self, point: &Coordinates) -> bool {
+        match self {
+            ColliderShape::Polygon(polygon) => polygon_contains(polygon, point),
+            ColliderShape::Round { start, end, radius } => {
+                distance(point, &closest_point_on_segment(point, start, end)) <= *radius
+            }
+            ColliderShape::Grid(cells) => cells.iter().any(|cell| polygon_contains(cell, point)),
+        }
+    }
+
+    /// Casts a ray against this shape. `direction` must be normalized.
+    /// Returns the distance of the first hit and the normal of the shape at this point.
+    /// A ray starting inside the shape hits it at distance 0.
+    pub(crate) fn ray_intersection(
+        &self,
+        origin: &Coordinates,
+        direction: &Vector,
+        max_distance: f32,
+    ) -> Option<(f32, Vector)> {
+        if self.contains_point(origin) {
+            return Some((0., Vector::new(-direction.x, -direction.y)));
+        }
+        let closest = |hits: Vec<(f32, Vector)>| {
+            hits.into_iter().filter(|(t, _)| *t <= max_distance).min_by(|(a, _), (b, _)| a.total_cmp(b))
+        };
+        match self {
+            ColliderShape::Polygon(polygon) => closest(ray_polygon_hits(origin, direction, polygon)),
+            ColliderShape::Grid(cells) => {
+                closest(cells.iter().flat_map(|cell| ray_polygon_hits(origin, direction, cell)).collect())
+            }
+            ColliderShape::Round { start, end, radius } => {
+                let mut hits: Vec<(f32, Vector)> =
+                    [start, end].iter().filter_map(|center| ray_circle_hit(origin, direction, center, *radius)).collect();
+                let length = distance(start, end);
+                if length > f32::EPSILON {
+                    let (nx, ny) = (-(end.y - start.y) / length, (end.x - start.x) / length);
+                    for sign in [1., -1.] {
+                        let a = Coordinates::new(start.x + sign * nx * radius, start.y + sign * ny * radius);
+                        let b = Coordinates::new(end.x + sign * nx * radius, end.y + sign * ny * radius);
+                        if let Some(t) = ray_segment_hit(origin, direction, &a, &b) {
+                            hits.push((t, Vector::new(sign * nx, sign * ny)));
+                        }
+                    }
+                }
+                closest(hits)
+            }
+        }
+    }
+
+    /// Minimum translation vector to apply to this shape to stop overlapping `other`.
+    /// Shapes are split into convex pieces, concave polygons being cut into triangles by ear clipping, and the deepest
+    /// overlap between two pieces is found with the separating axis theorem.
+    pub(crate) fn minimum_translation_vector(&self, other: &ColliderShape) -> Option<Vector> {
+        if let (
+            ColliderShape::Round { start, end, radius },
+            ColliderShape::Round { start: other_start, end: other_end, radius: other_radius },
+        ) = (self, other)
+        {
+            let (p, q) = closest_points_between_segments(start, end, other_start, other_end);
+            let d = distance(&p, &q);
+            let depth = radius + other_radius - d;
+            if depth <= 0. {
+                return None;
+            }
+            let (nx, ny) = if d > f32::EPSILON { ((p.x - q.x) / d, (p.y - q.y) / d) } else { (0., -1.) };
+            return Some(Vector::new(nx * depth, ny * depth));
+        }
+
+        let pieces = self.convex_pieces();
+        let other_pieces = other.convex_pieces();
+        pieces
+            .iter()
+            .flat_map(|piece| other_pieces.iter().filter_map(move |other_piece| separating_axis_mtv(piece, other_piece)))
+            .max_by(|a, b| (a.x * a.x + a.y * a.y).total_cmp(&(b.x * b.x + b.y * b.y)))
+    }
+
+    fn convex_pieces(&self) -> Vec<Vec<Coordinates>> {
+        let to_coordinates = |polygon: &geo_types::Polygon<f32>| {
+            let mut points: Vec<Coordinates> = polygon.exterior().0.iter().map(|c| Coordinates::new(c.x, c.y)).collect();
+            // geo closes its rings, repeating the first point
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            points
+        };
+        match self {
+            ColliderShape::Polygon(polygon) => convex_decomposition(to_coordinates(polygon)),
+            ColliderShape::Round { start, end, radius } => vec![round_outline(start, end, *radius)],
+            ColliderShape::Grid(cells) => cells.iter().map(to_coordinates).collect(),
+        }
+    }
+
+    /// Computes the intersection area of the two shapes, if they collide
+    pub(crate) fn intersection(&self, other: &ColliderShape) -> Option<CollisionArea> {
+        match (self, other) {
+            (ColliderShape::Polygon(polygon), _) => polygon_shape_intersection(polygon, other),
+            (_, ColliderShape::Polygon(polygon)) => polygon_shape_intersection(polygon, self),
+            (ColliderShape::Grid(cells), _) => grid_shape_intersection(cells, other),
+            (_, ColliderShape::Grid(cells)) => grid_shape_intersection(cells, self),
+            (
+                ColliderShape::Round { start, end, radius },
+                ColliderShape::Round { start: other_start, end: other_end, radius: other_radius },
+            ) => {
+                let (p, q) = closest_points_between_segments(start, end, other_start, other_end);
+                if distance(&p, &q) > radius + other_radius {
+                    return None;
+                }
+                let outline = to_geo_polygon(&round_outline(start, end, *radius));
+                let other_outline = to_geo_polygon(&round_outline(other_start, other_end, *other_radius));
+                Some(polygons_intersection(&outline, &other_outline).unwrap_or_else(|| {
+                    CollisionArea { coordinates: vec![Coordinates::new((p.x + q.x) / 2., (p.y + q.y) / 2.)] }
+                }))
+            }
+        }
+    }
+}
+
+fn cross(o: &Coordinates, a: &Coordinates, b: &Coordinates) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Splits a polygon into convex pieces : convex polygons are kept whole, concave ones are cut into triangles
+fn convex_decomposition(points: Vec<Coordinates>) -> Vec<Vec<Coordinates>> {
+    let len = points.len();
+    if len < 4 {
+        return vec![points];
+    }
+    let orientation: f32 = (0..len).map(|i| cross(&Coordinates::new(0., 0.), &points[i], &points[(i + 1) % len])).sum();
+    let turns: Vec<f32> = (0..len).map(|i| cross(&points[(i + len - 1) % len], &points[i], &points[(i + 1) % len])).collect();
+    if turns.iter().all(|turn| turn * orientation >= 0.) {
+        return vec![points];
+    }
+
+    // Ear clipping
+    let mut remaining = points;
+    let mut pieces = Vec::new();
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let ear = (0..len).find(|i| {
+            let (a, b, c) = (&remaining[(i + len - 1) % len], &remaining[*i], &remaining[(i + 1) % len]);
+            cross(a, b, c) * orientation > 0.
+                && remaining.iter().filter(|p| ![a, b, c].contains(p)).all(|p| {
+                    let (d1, d2, d3) = (cross(a, b, p), cross(b, c, p), cross(c, a, p));
+                    !((d1 * orientation >= 0.) && (d2 * orientation >= 0.) && (d3 * orientation >= 0.))
+                })
+        });
+        match ear {
+            Some(i) => {
+                pieces.push(vec![remaining[(i + len - 1) % len], remaining[i], remaining[(i + 1) % len]]);
+                remaining.remove(i);
+            }
+            // Degenerate polygon, kept as it is
+            None => break,
+        }
+    }
+    pieces.push(remaining);
+    pieces
+}
+
+/// Distance along the ray where it crosses the segment [a, b], if it does
+fn ray_segment_hit(origin: &Coordinates, direction: &Vector, a: &Coordinates, b: &Coordinates) -> Option<f32> {
+    let (ex, ey) = (b.x - a.x, b.y - a.y);
+    let denominator = direction.x * ey - direction.y * ex;
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+    let (ox, oy) = (a.x - origin.x, a.y - origin.y);
+    let t = (ox * ey - oy * ex) / denominator;
+    let u = (ox * direction.y - oy * direction.x) / denominator;
+    if t >= 0. && (0. ..=1.).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+fn ray_polygon_hits(origin: &Coordinates, direction: &Vector, polygon: &geo_types::Polygon<f32>) -> Vec<(f32, Vector)> {
+    let points = &polygon.exterior().0;
+    (0..points.len())
+        .filter_map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % points.len()]);
+            let (a, b) = (Coordinates::new(p.x, p.y), Coordinates::new(q.x, q.y));
+            let length = distance(&a, &b);
+            if length <= f32::EPSILON {
+                return None;
+            }
+            ray_segment_hit(origin, direction, &a, &b).map(|t| {
+                let (mut nx, mut ny) = (-(b.y - a.y) / length, (b.x - a.x) / length);
+                // The normal faces the ray origin
+                if nx * direction.x + ny * direction.y > 0. {
+                    nx = -nx;
+                    ny = -ny;
+                }
+                (t, Vector::new(nx, ny))
             })
+        })
+        .collect()
+}
+
+fn ray_circle_hit(origin: &Coordinates, direction: &Vector, center: &Coordinates, radius: f32) -> Option<(f32, Vector)> {
+    let (ox, oy) = (origin.x - center.x, origin.y - center.y);
+    let b = ox * direction.x + oy * direction.y;
+    let c = ox * ox + oy * oy - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0. {
+        return None;
+    }
+    let t = -b - discriminant.sqrt();
+    if t < 0. {
+        return None;
+    }
+    let (hx, hy) = (origin.x + direction.x * t - center.x, origin.y + direction.y * t - center.y);
+    Some((t, Vector::new(hx / radius, hy / radius)))
+}
+
+/// Precision, in pixels, of the contact distances found by sweeps
+const SWEEP_PRECISION: f32 = 0.05;
+
+/// Depth, in pixels, a swept shape is moved into the shape it touches, so that both overlap despite the precision of
+/// the sweep. Used to compute the area of continuous collisions, and to let the physics resolve continuous contacts
+pub(crate) const CONTACT_PENETRATION: f32 = 2. * SWEEP_PRECISION;
+
+/// Moves `shape` along the normalized `direction`, and returns the distance it can travel before touching `target`,
+/// with the contact normal pointing toward `shape`. Targets already overlapping at the start position are ignored.
+pub(crate) fn sweep_shape(
+    shape: &ColliderShape,
+    direction: &Vector,
+    max_distance: f32,
+    target: &ColliderShape,
+) -> Option<(f32, Vector)> {
+    let start_aabb = shape.aabb();
+    // Steps small enough to not go through the swept shape
+    let step = ((start_aabb.max_x - start_aabb.min_x).min(start_aabb.max_y - start_aabb.min_y) / 2.).max(SWEEP_PRECISION);
+    let overlaps = |distance: f32| {
+        let mut moved = shape.clone();
+        moved.translate(direction.x * distance, direction.y * distance);
+        moved.minimum_translation_vector(target)
+    };
+    if overlaps(0.).is_some() {
+        return None;
+    }
+
+    let mut previous = 0.;
+    let mut distance = step.min(max_distance);
+    loop {
+        if overlaps(distance).is_some() {
+            break;
+        }
+        if distance >= max_distance {
+            return None;
+        }
+        previous = distance;
+        distance = (distance + step).min(max_distance);
+    }
+    let (mut free, mut blocked) = (previous, distance);
+    while blocked - free > SWEEP_PRECISION {
+        let middle = (free + blocked) / 2.;
+        if overlaps(middle).is_some() {
+            blocked = middle;
         } else {
-            None
+            free = middle;
         }
     }
+    let mtv = overlaps(blocked).unwrap_or(Vector::new(-direction.x, -direction.y));
+    let mtv_length = (mtv.x * mtv.x + mtv.y * mtv.y).sqrt().max(f32::EPSILON);
+    Some((free, Vector::new(mtv.x / mtv_length, mtv.y / mtv_length)))
+}
 
-    pub(crate) fn add_collisions(&mut self, collisions: &mut Vec<Collision>) {
-        self.collisions.append(collisions);
+/// Separating axis test between two convex polygons. Returns the vector to apply to `polygon` to separate them
+fn separating_axis_mtv(polygon: &[Coordinates], other: &[Coordinates]) -> Option<Vector> {
+    let project = |points: &[Coordinates], axis: (f32, f32)| {
+        points.iter().map(|p| p.x * axis.0 + p.y * axis.1).fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v), max.max(v)))
+    };
+    let mut best: Option<(f32, (f32, f32))> = None;
+    for points in [polygon, other] {
+        for i in 0..points.len() {
+            let (p, q) = (points[i], points[(i + 1) % points.len()]);
+            let length = distance(&p, &q);
+            if length <= f32::EPSILON {
+                continue;
+            }
+            let axis = (-(q.y - p.y) / length, (q.x - p.x) / length);
+            let ((min_a, max_a), (min_b, max_b)) = (project(polygon, axis), project(other, axis));
+            let overlap = max_a.min(max_b) - min_a.max(min_b);
+            if overlap <= 0. {
+                return None;
+            }
+            if best.is_none_or(|(depth, _)| overlap < depth) {
+                best = Some((overlap, axis));
+            }
+        }
+    }
+
+    let (depth, (mut ax, mut ay)) = best?;
+    let (center, other_center) = (centroid_points(&polygon.to_vec()), centroid_points(&other.to_vec()));
+    if (center.x - other_center.x) * ax + (center.y - other_center.y) * ay < 0. {
+        ax = -ax;
+        ay = -ay;
     }
+    Some(Vector::new(ax * depth, ay * depth))
+}
+
+fn grid_shape_intersection(cells: &[geo_types::Polygon<f32>], shape: &ColliderShape) -> Option<CollisionArea> {
+    let aabb = shape.aabb();
+    cells
+        .iter()
+        .filter(|cell| Aabb::from_polygon(cell).intersects(&aabb))
+        .find_map(|cell| polygon_shape_intersection(cell, shape))
 }
 
-/// Representation of a collision
+fn polygon_shape_intersection(polygon: &geo_types::Polygon<f32>, shape: &ColliderShape) -> Option<CollisionArea> {
+    match shape {
+        ColliderShape::Polygon(other) => polygons_intersection(polygon, other),
+        ColliderShape::Grid(cells) => grid_shape_intersection(cells, &ColliderShape::Polygon(polygon.clone())),
+        ColliderShape::Round { start, end, radius } => {
+            let points = &polygon.exterior().0;
+            let closest = (0..points.len())
+                .map(|i| {
+                    let a = Coordinates::new(points[i].x, points[i].y);
+                    let next = points[(i + 1) % points.len()];
+                    closest_points_between_segments(start, end, &a, &Coordinates::new(next.x, next.y))
+                })
+                .min_by(|(p1, q1), (p2, q2)| distance(p1, q1).total_cmp(&distance(p2, q2)));
+            let touching = closest.is_some_and(|(p, q)| distance(&p, &q) <= *radius);
+            if !touching && !polygon_contains(polygon, start) {
+                return None;
+            }
+            let outline = to_geo_polygon(&round_outline(start, end, *radius));
+            Some(polygons_intersection(polygon, &outline).unwrap_or_else(|| CollisionArea {
+                coordinates: vec![closest.map(|(_, q)| q).unwrap_or(*start)],
+            }))
+        }
+    }
+}
+
+/// Axis aligned bounding box, used to quickly discard colliders that can't intersect
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl Aabb {
+    pub fn new(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Self {
+        Self { min_x, min_y, max_x, max_y }
+    }
+
+    /// Computes the bounding box of a polygon
+    pub fn from_polygon(polygon: &geo_types::Polygon<f32>) -> Self {
+        polygon.exterior().0.iter().fold(
+            Aabb::new(f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |acc, c| Aabb::new(acc.min_x.min(c.x), acc.min_y.min(c.y), acc.max_x.max(c.x), acc.max_y.max(c.y)),
+        )
+    }
+
+    /// Bounding box covering this one moved by up to `distance` along the normalized `direction`
+    pub fn swept(&self, direction: &Vector, distance: f32) -> Aabb {
+        let (dx, dy) = (direction.x * distance, direction.y * distance);
+        Aabb::new(self.min_x + dx.min(0.), self.min_y + dy.min(0.), self.max_x + dx.max(0.), self.max_y + dy.max(0.))
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min_x <= other.max_x && self.max_x >= other.min_x && self.min_y <= other.max_y && self.max_y >= other.min_y
+    }
+}
+
+/// Name of the `Events` topic where collisions starts and ends are published, as well as the ongoing collisions
+/// of the colliders created `with_ongoing_events`
+pub const COLLISIONS_TOPIC: &str = "Collisions";
+
+/// The different steps of a collision between two colliders
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollisionEventType {
+    Started,
+    Ongoing,
+    Ended,
+}
+
+/// Event published on the [`COLLISIONS_TOPIC`] topic, from the point of view of `entity`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollisionEvent {
+    event_type: CollisionEventType,
+    entity: u64,
+    mask: ColliderMask,
+    other_entity: u64,
+    other_mask: ColliderMask,
+}
+
+impl CollisionEvent {
+    pub(crate) fn new(
+        event_type: CollisionEventType,
+        entity: Entity,
+        mask: ColliderMask,
+        other_entity: Entity,
+        other_mask: ColliderMask,
+    ) -> Self {
+        Self { event_type, entity: entity.to_bits().get(), mask, other_entity: other_entity.to_bits().get(), other_mask }
+    }
+
+    pub fn event_type(&self) -> CollisionEventType {
+        self.event_type
+    }
+
+    pub fn entity(&self) -> Entity {
+        Entity::from_bits(self.entity).expect("Invalid entity in collision event")
+    }
+
+    pub fn mask(&self) -> &ColliderMask {
+        &self.mask
+    }
+
+    pub fn other_entity(&self) -> Entity {
+        Entity::from_bits(self.other_entity).expect("Invalid entity in collision event")
+    }
+
+    pub fn other_mask(&self) -> &ColliderMask {
+        &self.other_mask
+    }
+}
+
+/// Representation of a collision, from the point of view of the collider holding it : `other_entity`, `mask`
+/// and `coordinates` describe the other collider
 #[derive(Clone, Debug)]
 pub struct Collision {
     pub(crate) mask: ColliderMask,
     pub(crate) entity: Entity,
+    pub(crate) other_entity: Entity,
     pub(crate) coordinates: Coordinates,
     pub(crate) collision_area: CollisionArea,
+    pub(crate) time_of_impact: Option<f32>,
 }
 
 impl Collision {
+    /// Entity holding the collider
     pub fn entity(&self) -> &Entity {
         &self.entity
     }
+    /// Entity collided with
+    pub fn other_entity(&self) -> &Entity {
+        &self.other_entity
+    }
     pub fn mask(&self) -> &ColliderMask {
         &self.mask
     }
@@ -230,6 +1061,11 @@ impl Collision {
     pub fn area(&self) -> &CollisionArea {
         &self.collision_area
     }
+    /// For collisions found by continuous detection, the fraction of the movement since the previous frame,
+    /// from 0 to 1, where the contact happened
+    pub fn time_of_impact(&self) -> Option<f32> {
+        self.time_of_impact
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -299,6 +1135,101 @@ mod tests {
         assert!(bullet.collides_with(&ship_transform_out, &bullet, &bullet_transform).is_none());
     }
 
+    #[test]
+    fn test_circle_collisions() {
+        let ball = Collider::new(ColliderMask::Bullet, vec![], ColliderType::CircleCollider(10));
+        let square = Collider::new(ColliderMask::Landscape, vec![], ColliderType::SquareCollider(10));
+
+        // Bounding boxes overlap on the corner, but the circle doesn't reach the square
+        assert!(ball.collides_with(&Transform::from_xy(0., 0.), &square, &Transform::from_xy(18., 18.)).is_none());
+        assert!(ball.collides_with(&Transform::from_xy(0., 0.), &square, &Transform::from_xy(15., 15.)).is_some());
+        assert!(ball.collides_with(&Transform::from_xy(0., 0.), &square, &Transform::from_xy(19., 5.)).is_some());
+
+        assert!(ball.collides_with(&Transform::from_xy(0., 0.), &ball, &Transform::from_xy(19., 0.)).is_some());
+        assert!(ball.collides_with(&Transform::from_xy(0., 0.), &ball, &Transform::from_xy(15., 15.)).is_none());
+    }
+
+    #[test]
+    fn test_capsule_collisions() {
+        let capsule = Collider::new(ColliderMask::Character, vec![], ColliderType::CapsuleCollider(10, 40));
+        let ball = Collider::new(ColliderMask::Bullet, vec![], ColliderType::CircleCollider(5));
+
+        assert!(capsule.collides_with(&Transform::from_xy(0., 0.), &ball, &Transform::from_xy(9., 20.)).is_some());
+        assert!(capsule.collides_with(&Transform::from_xy(0., 0.), &ball, &Transform::from_xy(9., -9.)).is_none());
+        assert!(capsule.collides_with(&Transform::from_xy(0., 0.), &capsule, &Transform::from_xy(5., 35.)).is_some());
+    }
+
+    #[test]
+    fn test_grid_collisions() {
+        let mut grid = Collider::new(
+            ColliderMask::Landscape,
+            vec![],
+            ColliderType::TilemapGridCollider { tile_width: 10, tile_height: 10, solid_pathings: vec!["wall".to_string()] },
+        );
+        grid.set_grid_cells(4, 4, &[(0, 0), (1, 0), (2, 0), (3, 3)]);
+        assert_eq!(vec![(0, 0, 3), (3, 3, 1)], grid.grid_cells);
+
+        let square = Collider::new(ColliderMask::Character, vec![], ColliderType::SquareCollider(5));
+        assert!(square.collides_with(&Transform::from_xy(22., 2.), &grid, &Transform::default()).is_some());
+        assert!(square.collides_with(&Transform::from_xy(15., 15.), &grid, &Transform::default()).is_none());
+        assert!(square.collides_with(&Transform::from_xy(33., 33.), &grid, &Transform::default()).is_some());
+    }
+
+    #[test]
+    fn test_minimum_translation_vector() {
+        let square = Collider::new(ColliderMask::None, vec![], ColliderType::SquareCollider(10));
+        let floor = Collider::new(ColliderMask::None, vec![], ColliderType::RectangleCollider(100, 10));
+        let mtv = square
+            .collider_shape(&Transform::from_xy(20., -8.))
+            .minimum_translation_vector(&floor.collider_shape(&Transform::default()))
+            .unwrap();
+        assert!((mtv.x()).abs() < 0.001);
+        assert!((mtv.y() + 2.).abs() < 0.001);
+
+        let ball = Collider::new(ColliderMask::None, vec![], ColliderType::CircleCollider(5));
+        let mtv = ball
+            .collider_shape(&Transform::from_xy(8., 0.))
+            .minimum_translation_vector(&ball.collider_shape(&Transform::default()))
+            .unwrap();
+        assert!((mtv.x() - 2.).abs() < 0.001);
+        assert!(ball
+            .collider_shape(&Transform::from_xy(20., 0.))
+            .minimum_translation_vector(&ball.collider_shape(&Transform::default()))
+            .is_none());
+    }
+
+    #[test]
+    fn test_concave_polygon_decomposition() {
+        let concave = vec![
+            Coordinates::new(0., 0.),
+            Coordinates::new(10., 0.),
+            Coordinates::new(10., 10.),
+            Coordinates::new(5., 5.),
+            Coordinates::new(0., 10.),
+        ];
+        let pieces = convex_decomposition(concave.clone());
+        assert_eq!(3, pieces.len());
+        assert!(pieces.iter().all(|p| p.len() == 3));
+        assert_eq!(1, convex_decomposition(concave[0..3].to_vec()).len());
+
+        // A square in the notch of the polygon doesn't touch it
+        let shape = ColliderShape::Polygon(to_geo_polygon(&concave));
+        let square = ColliderShape::Polygon(to_geo_polygon(&rectangle_coordinates(4., 8., 2., 2.)));
+        assert!(shape.minimum_translation_vector(&square).is_none());
+    }
+
+    #[test]
+    fn test_aabb_intersects() {
+        let a = Aabb::new(0., 0., 10., 10.);
+        assert!(a.intersects(&Aabb::new(10., 10., 20., 20.)));
+        assert!(a.intersects(&Aabb::new(2., 2., 4., 4.)));
+        assert!(!a.intersects(&Aabb::new(11., 0., 20., 10.)));
+
+        let collider = Collider::new(ColliderMask::None, vec![], ColliderType::RectangleCollider(4, 2));
+        let aabb = Aabb::from_polygon(&collider.collider_polygon(&Transform::from_xy(1., 1.)));
+        assert_eq!(Aabb::new(1., 1., 5., 3.), aabb);
+    }
+
     #[test]
     fn test_does_notcollides_with_square_if_offsets_too_far() {
         let mut bullet = Collider::new(
@@ -4,10 +4,13 @@ pub mod coordinates;
 pub mod hierarchy;
 pub mod transform;
 pub mod padding;
+pub mod rigid_body;
 mod color_picking;
 
+use serde::{Deserialize, Serialize};
+
 /// `Pivot` tells where the pivot point of a component is
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Pivot {
     /// Pivot is on the top left corner of the shape
     TopLeft,
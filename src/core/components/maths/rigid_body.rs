@@ -0,0 +1,125 @@
+use crate::utils::maths::Vector;
+
+/// `BodyType` tells how a [`RigidBody`] is moved by the physics
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BodyType {
+    /// Affected by gravity, velocity and collisions
+    Dynamic,
+    /// Moved only by its velocity. It pushes dynamic bodies, but is never pushed back
+    Kinematic,
+    /// Never moves. Colliders without a `RigidBody` behave like static bodies
+    Static,
+}
+
+/// Component adding an entity to the physics simulation. The entity also needs a `Transform`, and a `Collider`
+/// to be able to collide with other bodies.
+#[derive(Debug, Clone)]
+pub struct RigidBody {
+    body_type: BodyType,
+    mass: f32,
+    restitution: f32,
+    friction: f32,
+    gravity_scale: f32,
+}
+
+impl RigidBody {
+    pub fn new(body_type: BodyType) -> Self {
+        Self { body_type, mass: 1., restitution: 0., friction: 0.2, gravity_scale: 1. }
+    }
+
+    pub fn dynamic() -> Self {
+        Self::new(BodyType::Dynamic)
+    }
+
+    pub fn kinematic() -> Self {
+        Self::new(BodyType::Kinematic)
+    }
+
+    pub fn fixed() -> Self {
+        Self::new(BodyType::Static)
+    }
+
+    /// Mass of the body, used to share the separation and the impulses between two dynamic bodies
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        assert!(mass > 0., "A rigid body mass must be positive");
+        self.mass = mass;
+        self
+    }
+
+    /// Bounciness of the body, from 0 (no bounce) to 1 (keeps all its speed)
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    /// Friction applied when sliding against another body
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Multiplier applied to the `Physics` gravity for this body
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn body_type(&self) -> BodyType {
+        self.body_type
+    }
+
+    pub fn mass(&self) -> f32 {
+        self.mass
+    }
+
+    pub fn restitution(&self) -> f32 {
+        self.restitution
+    }
+
+    pub fn friction(&self) -> f32 {
+        self.friction
+    }
+
+    pub fn gravity_scale(&self) -> f32 {
+        self.gravity_scale
+    }
+
+    pub(crate) fn inverse_mass(&self) -> f32 {
+        match self.body_type {
+            BodyType::Dynamic => 1. / self.mass,
+            BodyType::Kinematic | BodyType::Static => 0.,
+        }
+    }
+}
+
+/// Linear velocity of a [`RigidBody`], in pixels per second
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Velocity {
+    pub(crate) linear: Vector,
+}
+
+impl Velocity {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { linear: Vector::new(x, y) }
+    }
+
+    pub fn x(&self) -> f32 {
+        self.linear.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.linear.y
+    }
+
+    pub fn set_x(&mut self, x: f32) {
+        self.linear.x = x;
+    }
+
+    pub fn set_y(&mut self, y: f32) {
+        self.linear.y = y;
+    }
+
+    pub fn linear(&self) -> &Vector {
+        &self.linear
+    }
+}
@@ -1,3 +1,9 @@
 pub mod maths;
+pub mod flow_field_follower;
+pub mod ai;
 
-pub(crate) struct Dirty;
\ No newline at end of file
+pub(crate) struct Dirty;
+
+/// Marker component telling `Scion` to record the state of this entity at each fixed update,
+/// so that it can be restored later using `GameData::rewind`. See [`crate::core::resources::rewind::Rewind`]
+pub struct Rewindable;
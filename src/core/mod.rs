@@ -10,3 +10,4 @@ pub mod application_builder;
 pub mod scion_runner;
 pub mod components;
 mod command_buffer;
+pub mod prefab;
@@ -0,0 +1,435 @@
+//! Prefabs are data-driven entity templates. A prefab describes a bundle of components, with
+//! optional nested children, and references assets by the name they were registered with in the
+//! `AssetManager`. They are stored using the same JSON `.scion` format as tilemaps and tilesets.
+pub mod importer {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use hecs::{Entity, EntityBuilder};
+    use log::debug;
+
+    use crate::core::components::maths::collider::Collider;
+    use crate::core::components::maths::coordinates::Coordinates;
+    use crate::core::components::maths::hierarchy::Parent;
+    use crate::core::components::maths::transform::{Transform, TransformBuilder};
+    use crate::core::components::maths::Pivot;
+    use crate::core::prefab::data::{PrefabAtlas, PrefabComponent, PrefabOverrides};
+    use crate::core::resources::asset_manager::AssetType;
+    use crate::core::world::{GameData, Resources, World};
+    use crate::graphics::components::animations::Animations;
+    use crate::graphics::components::material::Material;
+    use crate::graphics::components::shapes::polygon::Polygon;
+    use crate::graphics::components::shapes::rectangle::Rectangle;
+    use crate::graphics::components::tiles::atlas::importer::load_tileset;
+    use crate::graphics::components::tiles::sprite::Sprite;
+    use crate::graphics::components::ui::ui_text::UiText;
+    use crate::graphics::components::{Hide, Square};
+    use crate::utils::maths::Vector;
+
+    /// Import a prefab from a .scion format located at `path`, into a PrefabAtlas
+    pub fn import_prefab(path: &str) -> PrefabAtlas {
+        match crate::utils::file::read_file(Path::new(path)) {
+            Ok(file) => {
+                let prefab: PrefabAtlas = serde_json::from_slice(file.as_slice()).unwrap_or_else(|e| panic!("Error while reading prefab at path {:?} : {:?}", path, e));
+                debug!("Prefab at path {} has been loaded", path);
+                prefab
+            }
+            Err(e) => panic!("{:?}", e)
+        }
+    }
+
+    /// Spawn the prefab `name` into the world, using `transform` as the root entity's transform.
+    /// The prefab is either retrieved from the asset manager, or loaded from the path registered for
+    /// `AssetType::Prefab(name)` (see `AssetManager`).
+    pub fn spawn_prefab(data: &mut GameData, name: &str, transform: Transform) -> Entity {
+        spawn_prefab_with_overrides(data, name, transform, PrefabOverrides::default())
+    }
+
+    /// Spawn the prefab `name` into the world, replacing the components declared in the prefab by the
+    /// ones provided in `overrides`.
+    pub fn spawn_prefab_with_overrides(data: &mut GameData,
+                                       name: &str,
+                                       transform: Transform,
+                                       overrides: PrefabOverrides) -> Entity {
+        let prefab = retrieve_prefab(&mut data.resources, name);
+        let mut ancestors = vec![name.to_string()];
+        let (components, children) = resolve_node(&mut data.resources, &prefab, &mut ancestors);
+        spawn_node(data, &prefab, components, children, Some(transform), None, &overrides, &mut ancestors)
+    }
+
+    fn retrieve_prefab(resources: &mut Resources, name: &str) -> PrefabAtlas {
+        let existing = resources.assets().retrieve_prefab(name).cloned();
+        match existing {
+            Some(prefab) => prefab,
+            None => {
+                let path = resources.assets().get_atlas_path_for_asset_type(AssetType::Prefab(name.to_string()));
+                let prefab = import_prefab(&path);
+                resources.assets_mut().register_prefab(prefab.clone());
+                prefab
+            }
+        }
+    }
+
+    /// Merges a node with the prefab it references, if any. Components declared on the node replace the
+    /// ones of the referenced prefab, and children are appended to the referenced prefab's ones.
+    /// The names of the referenced prefabs are added to `visited`, which holds the prefabs of the node's ancestors.
+    fn resolve_node(resources: &mut Resources,
+                    node: &PrefabAtlas,
+                    visited: &mut Vec<String>) -> (Vec<PrefabComponent>, Vec<PrefabAtlas>) {
+        match &node.prefab {
+            None => (node.components.clone(), node.children.clone()),
+            Some(base_name) => {
+                if visited.contains(base_name) {
+                    panic!("Prefab '{}' is referencing itself through its children", base_name);
+                }
+                visited.push(base_name.to_string());
+                let base = retrieve_prefab(resources, base_name);
+                let (mut components, mut children) = resolve_node(resources, &base, visited);
+                node.components.iter().for_each(|c| PrefabComponent::upsert(&mut components, c.clone()));
+                children.append(&mut node.children.clone());
+                (components, children)
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_node(data: &mut GameData,
+                  node: &PrefabAtlas,
+                  mut components: Vec<PrefabComponent>,
+                  children: Vec<PrefabAtlas>,
+                  transform: Option<Transform>,
+                  parent: Option<Entity>,
+                  overrides: &PrefabOverrides,
+                  ancestors: &mut Vec<String>) -> Entity {
+        overrides.apply(parent.map(|_| node.name.as_str()), &mut components);
+
+        let mut builder = EntityBuilder::new();
+        let mut local_transform = None;
+        {
+            let resources = &mut data.resources;
+            for component in components.drain(0..) {
+                if let PrefabComponent::Transform { x, y, z, scale, angle } = component {
+                    local_transform = Some(TransformBuilder::new()
+                        .with_translation(x, y, z)
+                        .with_scale(scale)
+                        .with_angle(angle)
+                        .build());
+                } else {
+                    add_component(resources, &mut builder, component);
+                }
+            }
+        }
+        builder.add(transform.or(local_transform).unwrap_or_default());
+        if let Some(parent) = parent {
+            builder.add(Parent::new(parent));
+        }
+        let entity = data.push(builder.build());
+
+        for child in children.iter() {
+            let depth = ancestors.len();
+            let (child_components, child_children) = resolve_node(&mut data.resources, child, ancestors);
+            spawn_node(data, child, child_components, child_children, None, Some(entity), overrides, ancestors);
+            ancestors.truncate(depth);
+        }
+        entity
+    }
+
+    fn add_component(resources: &mut Resources, builder: &mut EntityBuilder, component: PrefabComponent) {
+        match component {
+            PrefabComponent::Transform { .. } => {}
+            PrefabComponent::Sprite { tile_number, pivot } => {
+                builder.add(Sprite::new(tile_number).pivot(pivot.unwrap_or(Pivot::TopLeft)));
+            }
+            PrefabComponent::Square { length, pivot } => {
+                builder.add(Square::new(length, None).pivot(pivot.unwrap_or(Pivot::TopLeft)));
+            }
+            PrefabComponent::Rectangle { width, height, pivot } => {
+                builder.add(Rectangle::new(width, height, None).pivot(pivot.unwrap_or(Pivot::TopLeft)));
+            }
+            PrefabComponent::Polygon { vertices, pivot } => {
+                builder.add(Polygon::new(vertices.iter().map(|c| Coordinates::new(c.x, c.y)).collect())
+                    .pivot(pivot.unwrap_or(Pivot::TopLeft)));
+            }
+            PrefabComponent::Color(color) => {
+                builder.add(Material::Diffuse(color));
+            }
+            PrefabComponent::Material(name) => {
+                let asset_ref = resources.assets().retrieve_material_ref(&name)
+                    .unwrap_or_else(|| panic!("Prefab is referencing the unknown material '{}'", name));
+                builder.add(asset_ref);
+            }
+            PrefabComponent::Tileset(name) => {
+                builder.add(load_tileset(resources, &name));
+            }
+            PrefabComponent::UiText { text, font, font_size, font_color } => {
+                let font_ref = resources.assets().retrieve_font_ref(&font)
+                    .unwrap_or_else(|| panic!("Prefab is referencing the unknown font '{}'", font));
+                let mut ui_text = UiText::new(text, font_ref);
+                if let Some(size) = font_size {
+                    ui_text = ui_text.with_font_size(size);
+                }
+                if let Some(color) = font_color {
+                    ui_text = ui_text.with_font_color(color);
+                }
+                builder.add(ui_text);
+            }
+            PrefabComponent::Animations(names) => {
+                let assets = resources.assets();
+                let animations: HashMap<String, _> = names.iter().map(|name| {
+                    (name.to_string(), assets.build_animation(name)
+                        .unwrap_or_else(|| panic!("Prefab is referencing the unknown animation '{}'", name)))
+                }).collect();
+                builder.add(Animations::new(animations));
+            }
+            PrefabComponent::Collider { mask, filters, shape, offset, debug_lines } => {
+                let mut collider = Collider::new(mask, filters, shape);
+                if let Some(offset) = offset {
+                    collider = collider.with_offset(Vector::new(offset.x, offset.y));
+                }
+                if debug_lines {
+                    collider = collider.with_debug_lines();
+                }
+                builder.add(collider);
+            }
+            PrefabComponent::Hide => {
+                builder.add(Hide);
+            }
+        }
+    }
+}
+
+pub mod data {
+    use std::collections::HashMap;
+    use std::mem::discriminant;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::core::components::maths::collider::{ColliderMask, ColliderType};
+    use crate::core::components::maths::coordinates::Coordinates;
+    use crate::core::components::maths::Pivot;
+    use crate::graphics::components::color::Color;
+
+    /// Data representation of a prefab, or of one of its children.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct PrefabAtlas {
+        /// Name of the prefab. For children, used to target them with `PrefabOverrides`
+        #[serde(default)]
+        pub(crate) name: String,
+        /// Optional name of another prefab this one is based on
+        #[serde(default)]
+        pub(crate) prefab: Option<String>,
+        #[serde(default)]
+        pub(crate) components: Vec<PrefabComponent>,
+        #[serde(default)]
+        pub(crate) children: Vec<PrefabAtlas>,
+    }
+
+    impl PrefabAtlas {
+        pub fn new(name: &str, components: Vec<PrefabComponent>) -> Self {
+            Self { name: name.to_string(), prefab: None, components, children: vec![] }
+        }
+
+        pub fn with_child(mut self, child: PrefabAtlas) -> Self {
+            self.children.push(child);
+            self
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn components(&self) -> &Vec<PrefabComponent> {
+            &self.components
+        }
+
+        pub fn children(&self) -> &Vec<PrefabAtlas> {
+            &self.children
+        }
+    }
+
+    /// The components that can be described in a prefab. Assets are referenced by the name
+    /// they have been registered with in the `AssetManager`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(tag = "tag", content = "content")]
+    pub enum PrefabComponent {
+        /// Local transform of the entity. Ignored on the root entity, which uses the spawn transform
+        Transform {
+            #[serde(default)]
+            x: f32,
+            #[serde(default)]
+            y: f32,
+            #[serde(default)]
+            z: usize,
+            #[serde(default = "default_scale")]
+            scale: f32,
+            #[serde(default)]
+            angle: f32,
+        },
+        Sprite { tile_number: usize, pivot: Option<Pivot> },
+        Square { length: f32, pivot: Option<Pivot> },
+        Rectangle { width: f32, height: f32, pivot: Option<Pivot> },
+        Polygon { vertices: Vec<Coordinates>, pivot: Option<Pivot> },
+        /// Diffuse material using the given color
+        Color(Color),
+        /// Material registered using `AssetManager::register_named_material`
+        Material(String),
+        /// Tileset, loaded from its registered atlas if not already in the asset manager
+        Tileset(String),
+        /// Text using a font registered using `AssetManager::register_named_font`
+        UiText { text: String, font: String, font_size: Option<usize>, font_color: Option<Color> },
+        /// Animations registered using `AssetManager::register_animation`
+        Animations(Vec<String>),
+        Collider {
+            mask: ColliderMask,
+            #[serde(default)]
+            filters: Vec<ColliderMask>,
+            shape: ColliderType,
+            offset: Option<Coordinates>,
+            #[serde(default)]
+            debug_lines: bool,
+        },
+        Hide,
+    }
+
+    fn default_scale() -> f32 {
+        1.
+    }
+
+    impl PrefabComponent {
+        /// Replaces the component of the same kind in `components`, or adds it if missing
+        pub(crate) fn upsert(components: &mut Vec<PrefabComponent>, component: PrefabComponent) {
+            match components.iter_mut().find(|c| discriminant(*c) == discriminant(&component)) {
+                Some(existing) => *existing = component,
+                None => components.push(component),
+            }
+        }
+    }
+
+    /// Per instance modifications applied to a prefab when spawning it.
+    #[derive(Default, Clone)]
+    pub struct PrefabOverrides {
+        root: Vec<PrefabComponent>,
+        children: HashMap<String, Vec<PrefabComponent>>,
+    }
+
+    impl PrefabOverrides {
+        /// Replaces a component of the root entity
+        pub fn with_component(mut self, component: PrefabComponent) -> Self {
+            self.root.push(component);
+            self
+        }
+
+        /// Replaces a component of every child named `child_name`
+        pub fn with_child_component(mut self, child_name: &str, component: PrefabComponent) -> Self {
+            self.children.entry(child_name.to_string()).or_default().push(component);
+            self
+        }
+
+        /// `child_name` is None for the root entity
+        pub(crate) fn apply(&self, child_name: Option<&str>, components: &mut Vec<PrefabComponent>) {
+            let overrides = match child_name {
+                None => Some(&self.root),
+                Some(name) => self.children.get(name),
+            };
+            if let Some(overrides) = overrides {
+                overrides.iter().for_each(|c| PrefabComponent::upsert(components, c.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::Entity;
+
+    use crate::core::components::maths::hierarchy::{Children, Parent};
+    use crate::core::components::maths::transform::Transform;
+    use crate::core::prefab::data::{PrefabAtlas, PrefabComponent, PrefabOverrides};
+    use crate::core::prefab::importer::spawn_prefab_with_overrides;
+    use crate::core::resources::asset_manager::{AssetManager, AssetRef};
+    use crate::core::world::{GameData, World};
+    use crate::graphics::components::color::Color;
+    use crate::graphics::components::material::Material;
+    use crate::graphics::components::tiles::sprite::Sprite;
+
+    const ENEMY: &str = r#"{
+        "name": "enemy",
+        "components": [
+            {"tag": "Sprite", "content": {"tile_number": 3, "pivot": "Center"}},
+            {"tag": "Material", "content": "red"}
+        ],
+        "children": [
+            {"name": "shadow", "components": [
+                {"tag": "Transform", "content": {"x": 2.0, "y": 4.0}},
+                {"tag": "Square", "content": {"length": 8.0, "pivot": null}},
+                {"tag": "Color", "content": {"r": 0, "g": 0, "b": 0, "a": 0.5}}
+            ]}
+        ]
+    }"#;
+
+    fn test_data() -> GameData {
+        let mut data = GameData::default();
+        let mut assets = AssetManager::default();
+        assets.register_named_material("red", Material::Diffuse(Color::new_rgb(255, 0, 0)));
+        assets.register_prefab(serde_json::from_str(ENEMY).unwrap());
+        data.insert_resource(assets);
+        data
+    }
+
+    #[test]
+    fn spawn_prefab_test() {
+        let mut data = test_data();
+        let root = data.spawn_prefab("enemy", Transform::from_xy(10., 20.));
+
+        assert_eq!(3, data.entry::<&Sprite>(root).unwrap().get().unwrap().get_tile_nb());
+        assert!(data.entry::<&AssetRef<Material>>(root).unwrap().get().is_some());
+        assert_eq!(10., data.entry::<&Transform>(root).unwrap().get().unwrap().translation().x());
+
+        let children: Vec<Entity> = data.entry::<&Children>(root).unwrap().get().unwrap().0.clone();
+        assert_eq!(1, children.len());
+        assert_eq!(root, data.entry::<&Parent>(children[0]).unwrap().get().unwrap().entity());
+        assert_eq!(4., data.entry::<&Transform>(children[0]).unwrap().get().unwrap().translation().y());
+    }
+
+    #[test]
+    fn spawn_prefab_with_overrides_test() {
+        let mut data = test_data();
+        let overrides = PrefabOverrides::default()
+            .with_component(PrefabComponent::Sprite { tile_number: 7, pivot: None })
+            .with_child_component("shadow", PrefabComponent::Hide);
+        let root = spawn_prefab_with_overrides(&mut data, "enemy", Transform::default(), overrides);
+
+        assert_eq!(7, data.entry::<&Sprite>(root).unwrap().get().unwrap().get_tile_nb());
+        let child = data.entry::<&Children>(root).unwrap().get().unwrap().0[0];
+        assert!(data.entry::<&crate::graphics::components::Hide>(child).unwrap().get().is_some());
+    }
+
+    #[test]
+    fn spawn_prefab_based_on_another_test() {
+        let mut data = test_data();
+        data.assets_mut().register_prefab(PrefabAtlas::new("boss", vec![]).with_child(PrefabAtlas {
+            name: "minion".to_string(),
+            prefab: Some("enemy".to_string()),
+            components: vec![PrefabComponent::Sprite { tile_number: 1, pivot: None }],
+            children: vec![],
+        }));
+        let root = data.spawn_prefab("boss", Transform::default());
+
+        let minion = data.entry::<&Children>(root).unwrap().get().unwrap().0[0];
+        assert_eq!(1, data.entry::<&Sprite>(minion).unwrap().get().unwrap().get_tile_nb());
+        assert_eq!(1, data.entry::<&Children>(minion).unwrap().get().unwrap().0.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "is referencing itself through its children")]
+    fn spawn_prefab_with_a_child_referencing_it_test() {
+        let mut data = test_data();
+        data.assets_mut().register_prefab(PrefabAtlas::new("nest", vec![]).with_child(PrefabAtlas {
+            name: "egg".to_string(),
+            prefab: Some("nest".to_string()),
+            components: vec![],
+            children: vec![],
+        }));
+        data.spawn_prefab("nest", Transform::default());
+    }
+}
@@ -2,6 +2,8 @@ use std::{collections::HashMap, marker::PhantomData};
 
 use log::debug;
 
+use crate::core::prefab::data::PrefabAtlas;
+use crate::graphics::components::animations::Animation;
 use crate::graphics::components::ui::font::Font;
 use crate::graphics::components::{material::Material, tiles::tileset::Tileset};
 
@@ -16,6 +18,10 @@ pub struct AssetManager {
     asset_ref_registry: HashMap<AssetType, usize>,
     materials: HashMap<usize, Material>,
     fonts: HashMap<usize, Font>,
+    /// Animations factories, identified by name, used to build animations on demand
+    animations: HashMap<String, fn() -> Animation>,
+    /// Prefabs already loaded in the engine. Key is the prefab's name
+    prefabs: HashMap<String, PrefabAtlas>,
 }
 
 impl AssetManager {
@@ -108,6 +114,50 @@ impl AssetManager {
         next_ref
     }
 
+    /// Register a material and makes it available by `name`, for example for prefabs.
+    /// If a material already exists for this name, it is replaced in the registry
+    pub fn register_named_material(&mut self, name: &str, material: Material) -> AssetRef<Material> {
+        let asset_ref = self.register_material(material);
+        self.asset_ref_registry.insert(AssetType::Material(name.to_string()), asset_ref.0);
+        asset_ref
+    }
+
+    /// Register a font and makes it available by `name`, for example for prefabs.
+    /// If a font already exists for this name, it is replaced in the registry
+    pub fn register_named_font(&mut self, name: &str, font: Font) -> AssetRef<Font> {
+        let asset_ref = self.register_font(font);
+        self.asset_ref_registry.insert(AssetType::Font(name.to_string()), asset_ref.0);
+        asset_ref
+    }
+
+    /// Register a function building the animation `name`. Animations are stateful, so each
+    /// entity using it will receive a freshly built one.
+    pub fn register_animation(&mut self, name: &str, animation_factory: fn() -> Animation) {
+        self.animations.insert(name.to_string(), animation_factory);
+    }
+
+    /// Register an already loaded prefab. If a prefab already exists for this name, it is replaced
+    pub fn register_prefab(&mut self, prefab: PrefabAtlas) {
+        self.prefabs.insert(prefab.name.to_string(), prefab);
+    }
+
+    pub fn retrieve_material_ref(&self, name: &str) -> Option<AssetRef<Material>> {
+        self.asset_ref_registry.get(&AssetType::Material(name.to_string())).map(|id| AssetRef(*id, PhantomData))
+    }
+
+    pub fn retrieve_font_ref(&self, name: &str) -> Option<AssetRef<Font>> {
+        self.asset_ref_registry.get(&AssetType::Font(name.to_string())).map(|id| AssetRef(*id, PhantomData))
+    }
+
+    /// Builds a new instance of the animation registered as `name`
+    pub fn build_animation(&self, name: &str) -> Option<Animation> {
+        self.animations.get(name).map(|factory| factory())
+    }
+
+    pub fn retrieve_prefab(&self, name: &str) -> Option<&PrefabAtlas> {
+        self.prefabs.get(name)
+    }
+
     pub fn retrieve_tileset(&self, asset_ref: &AssetRef<Material>) -> Option<&Tileset> {
         match self.materials.get(&asset_ref.0) {
             None => None,
@@ -129,6 +179,9 @@ impl Copy for AssetRef<Font>{}
 pub enum AssetType {
     Tileset(String),
     Tilemap(String),
+    Prefab(String),
+    Material(String),
+    Font(String),
 }
 
 #[cfg(test)]
@@ -157,4 +210,13 @@ mod tests {
         assert_eq!(0, asset_ref.0);
         assert_eq!(1, manager.materials.len());
     }
+
+    #[test]
+    fn register_named_material_test() {
+        let mut manager = AssetManager::default();
+        let _r = manager.register_material(Material::Diffuse(Color::new(1, 1, 1, 1.)));
+        let asset_ref = manager.register_named_material("red", Material::Diffuse(Color::new_rgb(255, 0, 0)));
+        assert_eq!(asset_ref.0, manager.retrieve_material_ref("red").unwrap().0);
+        assert!(manager.retrieve_material_ref("blue").is_none());
+    }
 }
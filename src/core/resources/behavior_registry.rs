@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use hecs::Entity;
+
+use crate::core::components::ai::behavior_tree::{BehaviorAction, BehaviorCondition, BehaviorStatus, Blackboard};
+use crate::core::components::ai::state_machine::{StateCallback, TransitionGuard};
+use crate::core::components::ai::BehaviorError;
+use crate::core::world::GameData;
+
+/// `BehaviorRegistry` is the resource holding the named callbacks used by the state machines and the behavior trees
+/// loaded from JSON. Callbacks must be registered before loading the definitions using them.
+#[derive(Default)]
+pub struct BehaviorRegistry {
+    callbacks: HashMap<String, StateCallback>,
+    guards: HashMap<String, TransitionGuard>,
+    actions: HashMap<String, BehaviorAction>,
+    conditions: HashMap<String, BehaviorCondition>,
+}
+
+impl BehaviorRegistry {
+    /// Registers a state callback, usable as `on_enter`, `on_update` or `on_exit` of a state machine state
+    pub fn register_callback(&mut self, name: &str, callback: impl Fn(Entity, &mut GameData) + Send + Sync + 'static) {
+        self.callbacks.insert(name.to_string(), Arc::new(callback));
+    }
+
+    /// Registers a transition guard of a state machine
+    pub fn register_guard(&mut self, name: &str, guard: impl Fn(Entity, &GameData) -> bool + Send + Sync + 'static) {
+        self.guards.insert(name.to_string(), Arc::new(guard));
+    }
+
+    /// Registers an action leaf of a behavior tree
+    pub fn register_action(
+        &mut self,
+        name: &str,
+        action: impl Fn(Entity, &mut GameData, &mut Blackboard) -> BehaviorStatus + Send + Sync + 'static,
+    ) {
+        self.actions.insert(name.to_string(), Arc::new(action));
+    }
+
+    /// Registers a condition leaf of a behavior tree
+    pub fn register_condition(
+        &mut self,
+        name: &str,
+        condition: impl Fn(Entity, &GameData, &Blackboard) -> bool + Send + Sync + 'static,
+    ) {
+        self.conditions.insert(name.to_string(), Arc::new(condition));
+    }
+
+    pub(crate) fn callback(&self, name: &str) -> Result<StateCallback, BehaviorError> {
+        self.callbacks.get(name).cloned().ok_or_else(|| BehaviorError::UnknownCallback(name.to_string()))
+    }
+
+    pub(crate) fn guard(&self, name: &str) -> Result<TransitionGuard, BehaviorError> {
+        self.guards.get(name).cloned().ok_or_else(|| BehaviorError::UnknownCallback(name.to_string()))
+    }
+
+    pub(crate) fn action(&self, name: &str) -> Result<BehaviorAction, BehaviorError> {
+        self.actions.get(name).cloned().ok_or_else(|| BehaviorError::UnknownCallback(name.to_string()))
+    }
+
+    pub(crate) fn condition(&self, name: &str) -> Result<BehaviorCondition, BehaviorError> {
+        self.conditions.get(name).cloned().ok_or_else(|| BehaviorError::UnknownCallback(name.to_string()))
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+
+use hecs::Entity;
+
+use crate::core::components::maths::collider::{Aabb, ColliderShape};
+use crate::core::components::maths::transform::Transform;
+
+/// Uniform grid hashing entities by the cells covered by their bounding box
+#[derive(Default)]
+pub(crate) struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+}
+
+impl SpatialHash {
+    pub(crate) fn new(cell_size: f32) -> Self {
+        Self { cell_size, cells: HashMap::default() }
+    }
+
+    pub(crate) fn insert(&mut self, entity: Entity, aabb: &Aabb) {
+        let (min, max) = self.cell_range(aabb);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(entity);
+            }
+        }
+    }
+
+    /// Appends to `result` every entity sharing at least one cell with `aabb`, each of them only once
+    pub(crate) fn query(&self, aabb: &Aabb, result: &mut HashSet<Entity>) {
+        let (min, max) = self.cell_range(aabb);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(entities) = self.cells.get(&(x, y)) {
+                    result.extend(entities.iter());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    fn cell_range(&self, aabb: &Aabb) -> ((i32, i32), (i32, i32)) {
+        (
+            ((aabb.min_x / self.cell_size).floor() as i32, (aabb.min_y / self.cell_size).floor() as i32),
+            ((aabb.max_x / self.cell_size).floor() as i32, (aabb.max_y / self.cell_size).floor() as i32),
+        )
+    }
+}
+
+/// Shape of a static collider computed during a previous frame
+pub(crate) struct CachedCollider {
+    pub(crate) key: (f32, f32, f32, f32),
+    pub(crate) shape: ColliderShape,
+    pub(crate) aabb: Aabb,
+}
+
+impl CachedCollider {
+    pub(crate) fn key(transform: &Transform) -> (f32, f32, f32, f32) {
+        (transform.global_translation.x, transform.global_translation.y, transform.global_angle, transform.scale)
+    }
+}
+
+/// `Broadphase` is the resource used by the collision system to avoid testing every pair of colliders.
+/// Colliders are hashed in a uniform grid, and only colliders sharing a cell are tested against each other.
+///
+/// Colliders flagged with `Collider::with_static` are kept in a separate grid that is only rebuilt when
+/// one of them moves, is added or removed.
+pub struct Broadphase {
+    cell_size: f32,
+    pub(crate) dynamic_grid: SpatialHash,
+    pub(crate) static_grid: SpatialHash,
+    pub(crate) static_cache: HashMap<Entity, CachedCollider>,
+    pub(crate) static_dirty: bool,
+}
+
+impl Default for Broadphase {
+    fn default() -> Self {
+        Self::new(64.)
+    }
+}
+
+impl Broadphase {
+    /// Creates a new broadphase with the given grid cell size. The cell size should be about the size
+    /// of the most common colliders of the game.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0., "Broadphase cell size must be positive");
+        Self {
+            cell_size,
+            dynamic_grid: SpatialHash::new(cell_size),
+            static_grid: SpatialHash::new(cell_size),
+            static_cache: HashMap::default(),
+            static_dirty: true,
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Changes the grid cell size, rebuilding both grids at the next collision computation
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        *self = Self::new(cell_size);
+    }
+
+    /// Forgets every cached static collider shape. To call when the shape of a static collider is modified
+    pub fn invalidate_static_colliders(&mut self) {
+        self.static_cache.clear();
+        self.static_dirty = true;
+    }
+
+    /// Forgets the cached shape of a single static collider
+    pub fn invalidate_static_collider(&mut self, entity: Entity) {
+        if self.static_cache.remove(&entity).is_some() {
+            self.static_dirty = true;
+        }
+    }
+
+    /// Number of static colliders currently cached
+    pub fn static_colliders(&self) -> usize {
+        self.static_cache.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::world::{GameData, World};
+
+    #[test]
+    fn spatial_hash_query_test() {
+        let mut data = GameData::default();
+        let e1 = data.push((1,));
+        let e2 = data.push((2,));
+        let e3 = data.push((3,));
+
+        let mut hash = SpatialHash::new(10.);
+        hash.insert(e1, &Aabb::new(0., 0., 25., 5.));
+        hash.insert(e2, &Aabb::new(21., 1., 22., 2.));
+        hash.insert(e3, &Aabb::new(-15., -15., -12., -12.));
+
+        let mut result = HashSet::new();
+        hash.query(&Aabb::new(20., 0., 29., 9.), &mut result);
+        assert_eq!(2, result.len());
+        assert!(result.contains(&e1) && result.contains(&e2));
+
+        result.clear();
+        hash.query(&Aabb::new(-11., -11., -10., -10.), &mut result);
+        assert_eq!(1, result.len());
+        assert!(result.contains(&e3));
+    }
+}
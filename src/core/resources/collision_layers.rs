@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use log::warn;
+
+use crate::config::collision_layers_config::CollisionLayersConfig;
+use crate::core::components::maths::collider::{Collider, ColliderMask};
+
+/// Maximum number of collision layers, built-in ones included
+pub const MAX_COLLISION_LAYERS: usize = 64;
+
+/// Number of layers reserved for the built-in `ColliderMask` variants
+const BUILT_IN_LAYERS: u8 = 6;
+
+/// `CollisionLayers` is the resource mapping each `ColliderMask` to a numeric layer, used as a bit in the collision checks.
+///
+/// Built-in masks use the layers 0 to 5, and `ColliderMask::Layer(n)` directly uses the layer n, which must be
+/// between 6 and 63 and not given to a custom name. `ColliderMask::Custom` names are given the next free layer when
+/// registered, or the first time a collider uses them, skipping the layers already used by `ColliderMask::Layer`.
+/// The numeric layers of the colliders are reserved before the names they use are registered, but a custom name
+/// registered earlier (from `scion.json` for example) keeps its layer : a `Layer(n)` on an invalid or already named
+/// layer has no layer, and collides with nothing.
+///
+/// On top of the filters of each collider, a layer-vs-layer collision matrix can be set, in code or
+/// in the `collision_layers` entry of `scion.json`.
+#[derive(Clone)]
+pub struct CollisionLayers {
+    names: HashMap<String, u8>,
+    /// Bits of the layers used by `ColliderMask::Layer`
+    numeric_layers: u64,
+    next_layer: u8,
+    matrix: [u64; MAX_COLLISION_LAYERS],
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self { names: HashMap::default(), numeric_layers: 0, next_layer: BUILT_IN_LAYERS, matrix: [0; MAX_COLLISION_LAYERS] }
+    }
+}
+
+impl CollisionLayers {
+    /// Creates the layers and the collision matrix described in the configuration
+    pub fn from_config(config: &CollisionLayersConfig) -> Self {
+        let mut layers = Self::default();
+        config.layers.iter().for_each(|name| {
+            layers.register_layer(name);
+        });
+        for (name, others) in config.matrix.iter() {
+            let mask = ColliderMask::from_name(name);
+            others.iter().for_each(|other| layers.set_collision(&mask, &ColliderMask::from_name(other), true));
+        }
+        layers
+    }
+
+    /// Registers a named layer usable with `ColliderMask::Custom(name)`, and returns its layer number.
+    /// Registering an already known name returns its existing layer.
+    pub fn register_layer(&mut self, name: &str) -> u8 {
+        if let Some(layer) = self.names.get(name) {
+            return *layer;
+        }
+        while (self.next_layer as usize) < MAX_COLLISION_LAYERS
+            && (self.names.values().any(|l| *l == self.next_layer) || self.numeric_layers & 1u64 << self.next_layer != 0)
+        {
+            self.next_layer += 1;
+        }
+        assert!(
+            (self.next_layer as usize) < MAX_COLLISION_LAYERS,
+            "Impossible to register the collision layer {}, the {} layers are already used",
+            name,
+            MAX_COLLISION_LAYERS
+        );
+        let layer = self.next_layer;
+        self.names.insert(name.to_string(), layer);
+        self.next_layer += 1;
+        layer
+    }
+
+    /// Retrieves the layer of a mask, if it is known. Numeric layers out of the 6 to 63 range, or given to
+    /// a custom name, have no layer
+    pub fn layer(&self, mask: &ColliderMask) -> Option<u8> {
+        match mask {
+            ColliderMask::None => Some(0),
+            ColliderMask::Character => Some(1),
+            ColliderMask::Bullet => Some(2),
+            ColliderMask::Death => Some(3),
+            ColliderMask::Landscape => Some(4),
+            ColliderMask::Item => Some(5),
+            ColliderMask::Layer(layer) => {
+                let valid = (BUILT_IN_LAYERS..MAX_COLLISION_LAYERS as u8).contains(layer);
+                (valid && !self.names.values().any(|l| l == layer)).then_some(*layer)
+            }
+            ColliderMask::Custom(name) => self.names.get(name).copied(),
+        }
+    }
+
+    /// Layer of a mask, registering the unknown custom names and reserving the numeric layers
+    pub(crate) fn layer_or_register(&mut self, mask: &ColliderMask) -> Option<u8> {
+        match mask {
+            ColliderMask::Custom(name) => Some(self.register_layer(name)),
+            ColliderMask::Layer(n) => {
+                let layer = self.layer(mask);
+                match layer {
+                    Some(layer) => self.numeric_layers |= 1u64 << layer,
+                    None => warn!("Collision layer {} is out of the 6 to 63 range or already used by a custom layer, it is ignored", n),
+                }
+                layer
+            }
+            _ => self.layer(mask),
+        }
+    }
+
+    /// Bits of the given masks, registering the unknown custom names. Masks without layer are ignored
+    pub(crate) fn bits(&mut self, masks: &[ColliderMask]) -> u64 {
+        masks.iter().filter_map(|mask| self.layer_or_register(mask)).fold(0u64, |bits, layer| bits | 1u64 << layer)
+    }
+
+    /// Enables or disables the collisions between two layers, in both directions
+    pub fn set_collision(&mut self, mask: &ColliderMask, other: &ColliderMask, enabled: bool) {
+        let (Some(layer), Some(other_layer)) = (self.layer_or_register(mask), self.layer_or_register(other)) else { return };
+        let (layer, other_layer) = (layer as usize, other_layer as usize);
+        if enabled {
+            self.matrix[layer] |= 1 << other_layer;
+            self.matrix[other_layer] |= 1 << layer;
+        } else {
+            self.matrix[layer] &= !(1 << other_layer);
+            self.matrix[other_layer] &= !(1 << layer);
+        }
+    }
+
+    /// Whether the collision matrix makes the two masks collide
+    pub fn collides(&self, mask: &ColliderMask, other: &ColliderMask) -> bool {
+        match (self.layer(mask), self.layer(other)) {
+            (Some(layer), Some(other_layer)) => self.matrix[layer as usize] & 1 << other_layer != 0,
+            _ => false,
+        }
+    }
+
+    /// Bits of every layer a collider collides with : its own filters, and its layer row in the matrix
+    pub(crate) fn collision_bits(&self, collider: &Collider) -> u64 {
+        collider.filter_bits() | collider.layer().map_or(0, |layer| self.matrix[layer as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::maths::collider::ColliderType;
+
+    #[test]
+    fn register_layers_test() {
+        let mut layers = CollisionLayers::default();
+        assert_eq!(6, layers.register_layer("Enemy"));
+        assert_eq!(7, layers.register_layer("Wall"));
+        assert_eq!(6, layers.register_layer("Enemy"));
+        assert_eq!(Some(7), layers.layer(&ColliderMask::Custom("Wall".to_string())));
+        assert_eq!(Some(4), layers.layer(&ColliderMask::Landscape));
+        assert_eq!(None, layers.layer(&ColliderMask::Custom("Unknown".to_string())));
+    }
+
+    #[test]
+    fn collision_matrix_from_config_test() {
+        let mut config = CollisionLayersConfig { layers: vec!["Enemy".to_string()], matrix: HashMap::new() };
+        config.matrix.insert("Enemy".to_string(), vec!["Landscape".to_string(), "Enemy".to_string()]);
+        let mut layers = CollisionLayers::from_config(&config);
+
+        let enemy = ColliderMask::Custom("Enemy".to_string());
+        assert!(layers.collides(&enemy, &ColliderMask::Landscape));
+        assert!(layers.collides(&ColliderMask::Landscape, &enemy));
+        assert!(!layers.collides(&enemy, &ColliderMask::Character));
+
+        let mut collider = Collider::new(ColliderMask::Landscape, vec![ColliderMask::Character], ColliderType::SquareCollider(1));
+        collider.resolve_layers(&mut layers);
+        assert_eq!(1 << 1 | 1 << 6, layers.collision_bits(&collider));
+
+        layers.set_collision(&enemy, &ColliderMask::Landscape, false);
+        assert!(!layers.collides(&ColliderMask::Landscape, &enemy));
+    }
+
+    #[test]
+    fn numeric_layers_are_skipped_by_custom_layers_test() {
+        let mut layers = CollisionLayers::default();
+        assert_eq!(1 << 6, layers.bits(&[ColliderMask::Layer(6)]));
+        assert_eq!(7, layers.register_layer("Enemy"));
+    }
+
+    #[test]
+    fn invalid_numeric_layers_have_no_layer_test() {
+        let mut layers = CollisionLayers::default();
+        layers.register_layer("Enemy");
+        assert_eq!(None, layers.layer(&ColliderMask::Layer(1)));
+        assert_eq!(None, layers.layer(&ColliderMask::Layer(64)));
+        assert_eq!(None, layers.layer(&ColliderMask::Layer(6)));
+        assert_eq!(1 << 4, layers.bits(&[ColliderMask::Layer(1), ColliderMask::Layer(6), ColliderMask::Landscape]));
+    }
+
+    #[test]
+    #[should_panic(expected = "the 64 layers are already used")]
+    fn registering_too_many_layers_test() {
+        let mut layers = CollisionLayers::default();
+        layers.bits(&[ColliderMask::Layer(63)]);
+        for i in 0..=57 {
+            layers.register_layer(&format!("Layer {}", i));
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::core::components::maths::coordinates::Coordinates;
+use crate::core::components::maths::transform::Transform;
+use crate::core::resources::asset_manager::AssetManager;
+use crate::core::resources::pathfinding::{neighbour_offsets, NavigationGrid, Neighbourhood, OpenCell, PathRequest, OFFSETS};
+use crate::core::world::{ScionWorld, World};
+use crate::graphics::components::tiles::tilemap::Tilemap;
+use crate::utils::maths::Vector;
+
+/// Tilemap a flow field is computed from, and the revision of the tilemap used for the last computation
+struct TilemapSource {
+    request: PathRequest,
+    revision: u64,
+}
+
+/// `FlowField` gives, for every cell of a grid, the next cell to go to in order to reach the closest goal.
+///
+/// It is computed once for all the agents following it, and repaired incrementally when the cost of cells change :
+/// only the cells whose way to the goals went through a changed cell are computed again.
+/// The grid is placed in the world using an origin and a cell size, so that agents can sample it at their position.
+pub struct FlowField {
+    width: usize,
+    height: usize,
+    neighbourhood: Neighbourhood,
+    /// Cost to enter each cell, `None` for the cells that are not walkable
+    costs: Vec<Option<f32>>,
+    step_lengths: [f32; 8],
+    goals: Vec<usize>,
+    distances: Vec<f32>,
+    next: Vec<Option<usize>>,
+    origin: Coordinates,
+    cell_width: f32,
+    cell_height: f32,
+    source: Option<TilemapSource>,
+}
+
+impl FlowField {
+    /// Creates a flow field over an arbitrary grid of `width` x `height` cells, where `costs` are the costs
+    /// to enter each cell, line by line, `None` marking the cells that are not walkable
+    pub fn new(
+        width: usize,
+        height: usize,
+        costs: Vec<Option<f32>>,
+        goals: &[(usize, usize)],
+        neighbourhood: Neighbourhood,
+    ) -> Self {
+        assert_eq!(width * height, costs.len(), "A flow field needs a cost for each of its cells");
+        let step_lengths = OFFSETS.map(|(dx, dy)| if dx != 0 && dy != 0 { std::f32::consts::SQRT_2 } else { 1. });
+        Self::with_step_lengths(width, height, costs, goals, neighbourhood, step_lengths)
+    }
+
+    /// Creates a flow field over a layer of a tilemap, using the pathing costs of `request`.
+    /// The field follows the changes of the tilemap, see `FlowFields`.
+    ///
+    /// The grid is placed at the position of the tilemap, with cells of the size of the tiles of its main tileset.
+    /// Agents sample it as a rectangular grid, so isometric and hexagonal tilemaps need their own placement.
+    pub fn from_tilemap(
+        world: &mut ScionWorld,
+        assets: &AssetManager,
+        request: PathRequest,
+        goals: &[(usize, usize)],
+    ) -> Option<Self> {
+        let (origin, (cell_width, cell_height)) = {
+            let mut entry = world.entry::<(&Tilemap, &Transform)>(request.tilemap()).ok()?;
+            let (tilemap, transform) = entry.get()?;
+            let origin = transform.global_translation();
+            let tile_size = assets.retrieve_tileset(tilemap.tileset_ref()).map_or((1., 1.), |t| (t.tile_width as f32, t.tile_height as f32));
+            (Coordinates::new(origin.x(), origin.y()), tile_size)
+        };
+        let grid = NavigationGrid::build(world, assets, request.tilemap(), request.layer())?;
+        let mut field = Self::with_step_lengths(
+            grid.width,
+            grid.height,
+            grid.cell_costs(request.costs()),
+            goals,
+            request.neighbourhood(),
+            grid.step_lengths,
+        )
+        .with_origin(origin)
+        .with_cell_size(cell_width, cell_height);
+        field.source = Some(TilemapSource { request, revision: grid.revision });
+        Some(field)
+    }
+
+    fn with_step_lengths(
+        width: usize,
+        height: usize,
+        costs: Vec<Option<f32>>,
+        goals: &[(usize, usize)],
+        neighbourhood: Neighbourhood,
+        step_lengths: [f32; 8],
+    ) -> Self {
+        let mut field = Self {
+            width,
+            height,
+            neighbourhood,
+            costs,
+            step_lengths,
+            goals: vec![],
+            distances: vec![],
+            next: vec![],
+            origin: Coordinates::default(),
+            cell_width: 1.,
+            cell_height: 1.,
+            source: None,
+        };
+        field.set_goals(goals);
+        field
+    }
+
+    /// Places the grid in the world : `origin` is the top left corner of the cell (0, 0)
+    pub fn with_origin(mut self, origin: Coordinates) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets the size of a cell in world units
+    pub fn with_cell_size(mut self, cell_width: f32, cell_height: f32) -> Self {
+        self.cell_width = cell_width;
+        self.cell_height = cell_height;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Changes the goals of the field, computing it entirely again
+    pub fn set_goals(&mut self, goals: &[(usize, usize)]) {
+        self.goals = goals.iter().filter(|(x, y)| *x < self.width && *y < self.height).map(|(x, y)| y * self.width + x).collect();
+        self.distances = vec![f32::INFINITY; self.costs.len()];
+        self.next = vec![None; self.costs.len()];
+        let all: Vec<usize> = (0..self.costs.len()).collect();
+        self.repair(&all);
+    }
+
+    /// Changes the cost of a single cell, repairing the field
+    pub fn set_cost(&mut self, x: usize, y: usize, cost: Option<f32>) {
+        let index = y * self.width + x;
+        if self.costs[index] != cost {
+            self.costs[index] = cost;
+            self.repair(&[index]);
+        }
+    }
+
+    /// Changes the costs of every cell, repairing the field where they differ from the previous ones.
+    /// Returns the number of changed cells
+    pub fn update_costs(&mut self, costs: Vec<Option<f32>>) -> usize {
+        assert_eq!(self.costs.len(), costs.len(), "A flow field needs a cost for each of its cells");
+        let changed: Vec<usize> = (0..costs.len()).filter(|i| self.costs[*i] != costs[*i]).collect();
+        self.costs = costs;
+        if !changed.is_empty() {
+            self.repair(&changed);
+        }
+        changed.len()
+    }
+
+    /// Cost to reach the closest goal from the cell, `None` if no goal can be reached
+    pub fn distance(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.distances[y * self.width + x]).filter(|d| d.is_finite())
+    }
+
+    /// Next cell to go to from the cell, `None` for the goals and the cells that can't reach them
+    pub fn next_cell(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.next[y * self.width + x].map(|next| (next % self.width, next / self.width))
+    }
+
+    /// Normalized direction, in the grid, from the cell to its next cell
+    pub fn direction(&self, x: usize, y: usize) -> Option<Vector> {
+        let (nx, ny) = self.next_cell(x, y)?;
+        let (dx, dy) = (nx as f32 - x as f32, ny as f32 - y as f32);
+        let length = (dx * dx + dy * dy).sqrt();
+        Some(Vector::new(dx / length, dy / length))
+    }
+
+    /// Whether the cell is one of the goals of the field
+    pub fn is_goal(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.goals.contains(&(y * self.width + x))
+    }
+
+    /// Cell containing the world `point`, if it is inside the grid
+    pub fn cell_at(&self, point: &Coordinates) -> Option<(usize, usize)> {
+        let x = ((point.x - self.origin.x) / self.cell_width).floor();
+        let y = ((point.y - self.origin.y) / self.cell_height).floor();
+        (x >= 0. && y >= 0. && (x as usize) < self.width && (y as usize) < self.height).then_some((x as usize, y as usize))
+    }
+
+    /// World coordinates of the center of the cell
+    pub fn cell_center(&self, x: usize, y: usize) -> Coordinates {
+        Coordinates::new(
+            self.origin.x + (x as f32 + 0.5) * self.cell_width,
+            self.origin.y + (y as f32 + 0.5) * self.cell_height,
+        )
+    }
+
+    /// Cells around `index`, in every direction
+    fn adjacent_cells(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = (index % self.width, index / self.width);
+        OFFSETS.iter().filter_map(move |(dx, dy)| {
+            let (nx, ny) = (x.checked_add_signed(*dx)?, y.checked_add_signed(*dy)?);
+            (nx < self.width && ny < self.height).then_some(ny * self.width + nx)
+        })
+    }
+
+    /// Walkable cells from which `index` can be entered, with the cost of the move
+    fn predecessors(&self, index: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let (x, y) = (index % self.width, index / self.width);
+        let walkable = move |x: usize, y: usize| x < self.width && y < self.height && self.costs[y * self.width + x].is_some();
+        let cost = self.costs[index];
+        neighbour_offsets(self.neighbourhood).iter().zip(self.step_lengths).filter_map(move |((dx, dy), step_length)| {
+            let cost = cost?;
+            let (nx, ny) = (x.checked_add_signed(*dx)?, y.checked_add_signed(*dy)?);
+            if !walkable(nx, ny) || (*dx != 0 && *dy != 0 && !(walkable(nx, y) && walkable(x, ny))) {
+                return None;
+            }
+            Some((ny * self.width + nx, cost * step_length))
+        })
+    }
+
+    /// Computes again the cells whose way to the goals depends on the `changed` cells
+    fn repair(&mut self, changed: &[usize]) {
+        // Changed cells, and every cell whose next cells lead through one of them
+        let mut affected: HashSet<usize> = changed.iter().copied().collect();
+        let mut stack: Vec<usize> = changed.to_vec();
+        // Diagonal moves passing by the corner of a changed cell may not be allowed anymore
+        for index in changed.iter().copied() {
+            for neighbour in self.adjacent_cells(index) {
+                let corner_move = self.next[neighbour].is_some_and(|next| {
+                    let (cx, cy, nx, ny) = (neighbour % self.width, neighbour / self.width, next % self.width, next / self.width);
+                    cx != nx && cy != ny && (index == cy * self.width + nx || index == ny * self.width + cx)
+                });
+                if corner_move && affected.insert(neighbour) {
+                    stack.push(neighbour);
+                }
+            }
+        }
+        while let Some(index) = stack.pop() {
+            for neighbour in self.adjacent_cells(index) {
+                if self.next[neighbour] == Some(index) && affected.insert(neighbour) {
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        let mut open = BinaryHeap::new();
+        for index in affected.iter().copied() {
+            self.distances[index] = f32::INFINITY;
+            self.next[index] = None;
+        }
+        for index in affected.iter().copied() {
+            if self.costs[index].is_none() {
+                continue;
+            }
+            if self.goals.contains(&index) {
+                self.distances[index] = 0.;
+            } else {
+                // Best way through the cells that kept their distance
+                let best = self
+                    .adjacent_cells(index)
+                    .filter(|neighbour| !affected.contains(neighbour) && self.distances[*neighbour].is_finite())
+                    .filter_map(|neighbour| {
+                        self.predecessors(neighbour)
+                            .find(|(predecessor, _)| *predecessor == index)
+                            .map(|(_, cost)| (neighbour, self.distances[neighbour] + cost))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((neighbour, distance)) = best {
+                    self.distances[index] = distance;
+                    self.next[index] = Some(neighbour);
+                }
+            }
+            if self.distances[index].is_finite() {
+                open.push(OpenCell { cost: self.distances[index], index });
+            }
+        }
+
+        while let Some(OpenCell { cost, index }) = open.pop() {
+            if cost > self.distances[index] {
+                continue;
+            }
+            let predecessors: Vec<(usize, f32)> = self.predecessors(index).collect();
+            for (predecessor, step_cost) in predecessors {
+                if cost + step_cost < self.distances[predecessor] && !self.goals.contains(&predecessor) {
+                    self.distances[predecessor] = cost + step_cost;
+                    self.next[predecessor] = Some(index);
+                    open.push(OpenCell { cost: cost + step_cost, index: predecessor });
+                }
+            }
+        }
+    }
+}
+
+/// `FlowFields` is the resource holding the named flow fields followed by the `FlowFieldFollower` entities.
+///
+/// Fields created from a tilemap are repaired each frame in which the tilemap has been modified.
+#[derive(Default)]
+pub struct FlowFields {
+    fields: HashMap<String, FlowField>,
+}
+
+impl FlowFields {
+    pub fn insert(&mut self, name: &str, field: FlowField) {
+        self.fields.insert(name.to_string(), field);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FlowField> {
+        self.fields.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut FlowField> {
+        self.fields.get_mut(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<FlowField> {
+        self.fields.remove(name)
+    }
+
+    /// Repairs the fields whose tilemap has been modified since their last computation
+    pub(crate) fn refresh(&mut self, world: &mut ScionWorld, assets: &AssetManager) {
+        for field in self.fields.values_mut() {
+            let Some(source) = field.source.as_ref() else { continue };
+            let Some(revision) = world.entry::<&Tilemap>(source.request.tilemap()).ok().and_then(|mut e| e.get().map(|t| t.revision()))
+            else {
+                continue;
+            };
+            if revision == source.revision {
+                continue;
+            }
+            let request = source.request.clone();
+            if let Some(grid) = NavigationGrid::build(world, assets, request.tilemap(), request.layer()) {
+                if grid.width == field.width && grid.height == field.height {
+                    field.update_costs(grid.cell_costs(request.costs()));
+                }
+                field.source = Some(TilemapSource { request, revision: grid.revision });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_grid(width: usize, height: usize) -> Vec<Option<f32>> {
+        vec![Some(1.); width * height]
+    }
+
+    #[test]
+    fn flow_field_leads_to_goal() {
+        let mut costs = open_grid(5, 5);
+        (0..4).for_each(|y| costs[y * 5 + 2] = None);
+        let field = FlowField::new(5, 5, costs, &[(4, 0)], Neighbourhood::Four);
+
+        assert_eq!(Some(0.), field.distance(4, 0));
+        assert_eq!(None, field.next_cell(4, 0));
+        assert_eq!(None, field.distance(2, 0));
+        assert_eq!(Some(12.), field.distance(0, 0));
+        assert_eq!(Some((1, 1)), field.next_cell(1, 0));
+        assert_eq!(Some(1.), field.direction(1, 0).map(|d| d.y()));
+
+        let mut cell = (0, 0);
+        let mut steps = 0;
+        while let Some(next) = field.next_cell(cell.0, cell.1) {
+            cell = next;
+            steps += 1;
+        }
+        assert_eq!((4, 0), cell);
+        assert_eq!(12, steps);
+    }
+
+    #[test]
+    fn flow_field_incremental_repair_matches_full_computation() {
+        let mut field = FlowField::new(8, 8, open_grid(8, 8), &[(7, 7), (0, 7)], Neighbourhood::Eight);
+        let mut costs = open_grid(8, 8);
+        for (x, y, cost) in [(3, 3, None), (3, 4, None), (4, 4, Some(5.)), (6, 6, None), (1, 6, Some(0.5))] {
+            costs[y * 8 + x] = cost;
+        }
+        assert_eq!(5, field.update_costs(costs.clone()));
+        let full = FlowField::new(8, 8, costs.clone(), &[(7, 7), (0, 7)], Neighbourhood::Eight);
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(full.distance(x, y), field.distance(x, y), "distance of ({}, {})", x, y);
+            }
+        }
+
+        // Opening a cell again
+        field.set_cost(6, 6, Some(1.));
+        costs[6 * 8 + 6] = Some(1.);
+        let full = FlowField::new(8, 8, costs, &[(7, 7), (0, 7)], Neighbourhood::Eight);
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(full.distance(x, y), field.distance(x, y), "distance of ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn cell_at_world_coordinates() {
+        let field = FlowField::new(4, 4, open_grid(4, 4), &[(0, 0)], Neighbourhood::Four)
+            .with_origin(Coordinates::new(100., 50.))
+            .with_cell_size(16., 16.);
+        assert_eq!(Some((1, 2)), field.cell_at(&Coordinates::new(120., 90.)));
+        assert_eq!(None, field.cell_at(&Coordinates::new(90., 90.)));
+        assert_eq!(Coordinates::new(124., 90.), field.cell_center(1, 2));
+    }
+}
@@ -8,3 +8,13 @@ pub mod font_atlas;
 pub mod focus_manager;
 pub mod global_storage;
 pub mod color_picking;
+pub mod rewind;
+pub mod broadphase;
+pub mod collision_layers;
+pub mod physics;
+pub mod physics_query;
+pub mod pathfinding;
+pub mod flow_fields;
+pub mod navmesh;
+pub mod behavior_registry;
+pub mod tile_object_registry;
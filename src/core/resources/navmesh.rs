@@ -0,0 +1,426 @@
+use std::collections::{BinaryHeap, HashMap};
+
+use geo_clipper::{Clipper, EndType, JoinType};
+use geo_types::{Coord, LineString, MultiPolygon};
+
+use crate::core::components::maths::coordinates::Coordinates;
+use crate::core::resources::pathfinding::OpenCell;
+
+/// Scale applied to the coordinates given to clipper, which works with integers. Keeps two decimals.
+const CLIPPER_FACTOR: f32 = 100.;
+/// Maximum distance between a rounded corner of an inflated obstacle and the perfect circle, in world units
+const ROUND_PRECISION: f32 = 0.25;
+
+/// `NavMeshBaker` describes the navigable area of a world that isn't built on a `Tilemap`, and bakes it into a `NavMesh`.
+///
+/// The navigable area is the union of the walkable areas minus the obstacles. When an agent radius is set,
+/// walkable areas are shrunk and obstacles are inflated by this radius, so that the paths keep agents away from the walls.
+#[derive(Default, Debug, Clone)]
+pub struct NavMeshBaker {
+    walkable_areas: Vec<Vec<Coordinates>>,
+    obstacles: Vec<Vec<Coordinates>>,
+    agent_radius: f32,
+}
+
+impl NavMeshBaker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a walkable polygon, given by its outline
+    pub fn with_walkable_area(mut self, points: Vec<Coordinates>) -> Self {
+        self.walkable_areas.push(points);
+        self
+    }
+
+    /// Adds an obstacle polygon, given by its outline, removed from the walkable areas
+    pub fn with_obstacle(mut self, points: Vec<Coordinates>) -> Self {
+        self.obstacles.push(points);
+        self
+    }
+
+    /// Sets the radius of the agents using the mesh
+    pub fn with_agent_radius(mut self, agent_radius: f32) -> Self {
+        self.agent_radius = agent_radius.max(0.);
+        self
+    }
+
+    /// Computes the navigable area and splits it into triangles
+    pub fn bake(&self) -> NavMesh {
+        let union = |polygons: &[Vec<Coordinates>]| {
+            polygons.iter().filter(|p| p.len() >= 3).fold(MultiPolygon::<f32>::new(vec![]), |union, points| {
+                union.union(&to_geo_polygon(points), CLIPPER_FACTOR)
+            })
+        };
+        let mut walkable = union(&self.walkable_areas);
+        let mut obstacles = union(&self.obstacles);
+        if self.agent_radius > 0. {
+            let delta = self.agent_radius;
+            walkable = walkable.offset(-delta, JoinType::Miter(2.), EndType::ClosedPolygon, CLIPPER_FACTOR);
+            obstacles = obstacles.offset(
+                delta,
+                JoinType::Round((ROUND_PRECISION * CLIPPER_FACTOR) as f64),
+                EndType::ClosedPolygon,
+                CLIPPER_FACTOR,
+            );
+        }
+        let navigable = walkable.difference(&obstacles, CLIPPER_FACTOR);
+
+        let triangles: Vec<[Coordinates; 3]> = navigable
+            .0
+            .iter()
+            .flat_map(|polygon| {
+                let outline = oriented(ring(polygon.exterior()), true);
+                let holes = polygon.interiors().iter().map(|hole| oriented(ring(hole), false)).collect();
+                triangulate(bridge_holes(outline, holes))
+            })
+            .collect();
+        NavMesh::from_triangles(triangles)
+    }
+}
+
+/// End of a triangle edge, in clipper units, so that shared edges can be matched exactly
+type EdgeEnd = (i64, i64);
+
+/// Link between two neighbour triangles of a `NavMesh`
+#[derive(Debug, Clone)]
+struct Portal {
+    triangle: usize,
+    a: Coordinates,
+    b: Coordinates,
+}
+
+/// `NavMesh` is a navigation mesh made of triangles, used to find smooth paths in free-form worlds.
+///
+/// It is created by a `NavMeshBaker`. Paths go through the triangles, then are pulled tight along the corners
+/// of the mesh, using the funnel algorithm.
+#[derive(Debug, Clone, Default)]
+pub struct NavMesh {
+    triangles: Vec<[Coordinates; 3]>,
+    portals: Vec<Vec<Portal>>,
+}
+
+impl NavMesh {
+    fn from_triangles(triangles: Vec<[Coordinates; 3]>) -> Self {
+        let key = |c: &Coordinates| ((c.x * CLIPPER_FACTOR).round() as i64, (c.y * CLIPPER_FACTOR).round() as i64);
+        // Triangles sharing each edge, with the position of the edge in the triangle
+        let mut edges: HashMap<(EdgeEnd, EdgeEnd), Vec<(usize, usize)>> = HashMap::new();
+        for (index, triangle) in triangles.iter().enumerate() {
+            for i in 0..3 {
+                let (a, b) = (key(&triangle[i]), key(&triangle[(i + 1) % 3]));
+                edges.entry(if a < b { (a, b) } else { (b, a) }).or_default().push((index, i));
+            }
+        }
+        let mut portals = vec![Vec::new(); triangles.len()];
+        for sharing in edges.values() {
+            for (t1, i) in sharing.iter() {
+                let (a, b) = (triangles[*t1][*i], triangles[*t1][(i + 1) % 3]);
+                for (t2, _) in sharing.iter().filter(|(t2, _)| t2 != t1) {
+                    portals[*t1].push(Portal { triangle: *t2, a, b });
+                }
+            }
+        }
+        Self { triangles, portals }
+    }
+
+    /// Triangles of the mesh, useful to display it
+    pub fn triangles(&self) -> &Vec<[Coordinates; 3]> {
+        &self.triangles
+    }
+
+    /// Whether `point` is in the navigable area
+    pub fn contains(&self, point: &Coordinates) -> bool {
+        self.triangle_at(point).is_some()
+    }
+
+    fn triangle_at(&self, point: &Coordinates) -> Option<usize> {
+        self.triangles.iter().position(|[a, b, c]| {
+            let (d1, d2, d3) = (cross(a, b, point), cross(b, c, point), cross(c, a, point));
+            !((d1 < 0. || d2 < 0. || d3 < 0.) && (d1 > 0. || d2 > 0. || d3 > 0.))
+        })
+    }
+
+    /// Finds the shortest path between `start` and `goal`, returned as the list of its corners, `start` and `goal` included.
+    /// Returns `None` when one of the points is outside the mesh, or when they are not connected.
+    pub fn find_path(&self, start: Coordinates, goal: Coordinates) -> Option<Vec<Coordinates>> {
+        let (from, to) = (self.triangle_at(&start)?, self.triangle_at(&goal)?);
+        let corridor = self.corridor(from, to, &start, &goal)?;
+
+        let mut portals = vec![(start, start)];
+        for window in corridor.windows(2) {
+            let portal = self.portals[window[0]].iter().find(|p| p.triangle == window[1]).expect("Corridor triangles are neighbours");
+            let [a, b, c] = self.triangles[window[0]];
+            let center = Coordinates::new((a.x + b.x + c.x) / 3., (a.y + b.y + c.y) / 3.);
+            // Left and right sides of the portal, seen from the triangle it leaves
+            if cross(&center, &portal.a, &portal.b) > 0. {
+                portals.push((portal.b, portal.a));
+            } else {
+                portals.push((portal.a, portal.b));
+            }
+        }
+        portals.push((goal, goal));
+        Some(string_pull(&portals))
+    }
+
+    /// Triangles crossed by the path between `start` and `goal`, found with A* going through the middle of the portals
+    fn corridor(&self, from: usize, to: usize, start: &Coordinates, goal: &Coordinates) -> Option<Vec<usize>> {
+        let mut costs = vec![f32::INFINITY; self.triangles.len()];
+        let mut entries = vec![*start; self.triangles.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; self.triangles.len()];
+        let mut open = BinaryHeap::new();
+        costs[from] = 0.;
+        open.push(OpenCell { cost: distance(start, goal), index: from });
+        while let Some(OpenCell { cost, index }) = open.pop() {
+            if index == to {
+                let mut corridor = vec![to];
+                while let Some(triangle) = previous[*corridor.last().expect("Corridor can't be empty")] {
+                    corridor.push(triangle);
+                }
+                corridor.reverse();
+                return Some(corridor);
+            }
+            if cost > costs[index] + distance(&entries[index], goal) {
+                continue;
+            }
+            for portal in self.portals[index].iter() {
+                let middle = Coordinates::new((portal.a.x + portal.b.x) / 2., (portal.a.y + portal.b.y) / 2.);
+                let entry = if portal.triangle == to { *goal } else { middle };
+                let new_cost = costs[index] + distance(&entries[index], &entry);
+                if new_cost < costs[portal.triangle] {
+                    costs[portal.triangle] = new_cost;
+                    entries[portal.triangle] = entry;
+                    previous[portal.triangle] = Some(index);
+                    open.push(OpenCell { cost: new_cost + distance(&entry, goal), index: portal.triangle });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Simple stupid funnel algorithm : pulls the path tight through the `(left, right)` portals
+fn string_pull(portals: &[(Coordinates, Coordinates)]) -> Vec<Coordinates> {
+    let mut path = vec![portals[0].0];
+    let (mut apex, mut left, mut right) = (portals[0].0, portals[0].0, portals[0].1);
+    let (mut left_index, mut right_index) = (0, 0);
+    let mut i = 1;
+    while i < portals.len() {
+        let (new_left, new_right) = portals[i];
+        // Right side of the funnel
+        if cross(&apex, &right, &new_right) >= 0. {
+            if apex == right || cross(&apex, &left, &new_right) < 0. {
+                right = new_right;
+                right_index = i;
+            } else {
+                apex = left;
+                let apex_index = left_index;
+                path.push(apex);
+                (left, right) = (apex, apex);
+                (left_index, right_index) = (apex_index, apex_index);
+                i = apex_index + 1;
+                continue;
+            }
+        }
+        // Left side of the funnel
+        if cross(&apex, &left, &new_left) <= 0. {
+            if apex == left || cross(&apex, &right, &new_left) > 0. {
+                left = new_left;
+                left_index = i;
+            } else {
+                apex = right;
+                let apex_index = right_index;
+                path.push(apex);
+                (left, right) = (apex, apex);
+                (left_index, right_index) = (apex_index, apex_index);
+                i = apex_index + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    let goal = portals[portals.len() - 1].0;
+    if path.last() != Some(&goal) {
+        path.push(goal);
+    }
+    path
+}
+
+fn to_geo_polygon(points: &[Coordinates]) -> geo_types::Polygon<f32> {
+    geo_types::Polygon::new(LineString(points.iter().map(|c| Coord { x: c.x, y: c.y }).collect()), vec![])
+}
+
+/// Points of a closed ring, without the repeated closing point
+fn ring(line: &LineString<f32>) -> Vec<Coordinates> {
+    let mut points: Vec<Coordinates> = line.0.iter().map(|c| Coordinates::new(c.x, c.y)).collect();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+fn signed_area(points: &[Coordinates]) -> f32 {
+    (0..points.len()).map(|i| cross(&Coordinates::new(0., 0.), &points[i], &points[(i + 1) % points.len()])).sum::<f32>() / 2.
+}
+
+/// Reverses the points if needed, so that the ring turns counter clockwise when `counter_clockwise` is true
+fn oriented(mut points: Vec<Coordinates>, counter_clockwise: bool) -> Vec<Coordinates> {
+    if (signed_area(&points) > 0.) != counter_clockwise {
+        points.reverse();
+    }
+    points
+}
+
+fn cross(o: &Coordinates, a: &Coordinates, b: &Coordinates) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn distance(a: &Coordinates, b: &Coordinates) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+fn segments_cross(a: &Coordinates, b: &Coordinates, c: &Coordinates, d: &Coordinates) -> bool {
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    d1 * d2 < 0. && d3 * d4 < 0.
+}
+
+fn ring_contains(points: &[Coordinates], point: &Coordinates) -> bool {
+    let mut inside = false;
+    for i in 0..points.len() {
+        let (p, q) = (points[i], points[(i + points.len() - 1) % points.len()]);
+        if (p.y > point.y) != (q.y > point.y) && point.x < (q.x - p.x) * (point.y - p.y) / (q.y - p.y) + p.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Merges the holes into the outline, linking each of them to a visible point of the outline by a double edge,
+/// so that the result can be triangulated like a simple polygon
+fn bridge_holes(mut outline: Vec<Coordinates>, mut holes: Vec<Vec<Coordinates>>) -> Vec<Coordinates> {
+    let max_x = |hole: &Vec<Coordinates>| hole.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+    holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    for (index, hole) in holes.iter().enumerate() {
+        let start = (0..hole.len()).max_by(|a, b| hole[*a].x.total_cmp(&hole[*b].x)).expect("Holes have points");
+        let point = hole[start];
+        let edges = |ring: &Vec<Coordinates>| (0..ring.len()).map(|i| (ring[i], ring[(i + 1) % ring.len()])).collect::<Vec<_>>();
+        let blocking: Vec<(Coordinates, Coordinates)> =
+            edges(&outline).into_iter().chain(holes[index..].iter().flat_map(edges)).collect();
+        let mut candidates: Vec<usize> = (0..outline.len()).collect();
+        candidates.sort_by(|a, b| distance(&point, &outline[*a]).total_cmp(&distance(&point, &outline[*b])));
+        let visible = candidates.into_iter().find(|candidate| {
+            let target = outline[*candidate];
+            let middle = Coordinates::new((point.x + target.x) / 2., (point.y + target.y) / 2.);
+            !ring_contains(hole, &middle)
+                && blocking.iter().all(|(a, b)| {
+                    [a, b].iter().any(|p| **p == point || **p == target) || !segments_cross(&point, &target, a, b)
+                })
+        });
+        let Some(visible) = visible else { continue };
+        let mut merged = outline[..=visible].to_vec();
+        merged.extend((0..=hole.len()).map(|i| hole[(start + i) % hole.len()]));
+        merged.extend_from_slice(&outline[visible..]);
+        outline = merged;
+    }
+    outline
+}
+
+/// Splits a counter clockwise polygon into triangles, by ear clipping
+fn triangulate(mut points: Vec<Coordinates>) -> Vec<[Coordinates; 3]> {
+    let mut triangles = Vec::new();
+    while points.len() > 3 {
+        let len = points.len();
+        let corners = |i: usize| (points[(i + len - 1) % len], points[i], points[(i + 1) % len]);
+        let is_ear = |i: usize| {
+            let (a, b, c) = corners(i);
+            cross(&a, &b, &c) > 0.
+                && points.iter().filter(|p| ![a, b, c].contains(p)).all(|p| {
+                    cross(&a, &b, p) < 0. || cross(&b, &c, p) < 0. || cross(&c, &a, p) < 0.
+                })
+        };
+        // Degenerate corners are removed, without adding a triangle
+        let flat = (0..len).find(|i| {
+            let (a, b, c) = corners(*i);
+            cross(&a, &b, &c).abs() <= f32::EPSILON && (a.x - b.x) * (c.x - b.x) + (a.y - b.y) * (c.y - b.y) <= 0.
+        });
+        if let Some(i) = flat {
+            points.remove(i);
+            continue;
+        }
+        let Some(ear) = (0..len).find(|i| is_ear(*i)).or_else(|| (0..len).find(|i| cross(&corners(*i).0, &corners(*i).1, &corners(*i).2) > 0.))
+        else {
+            return triangles;
+        };
+        let (a, b, c) = corners(ear);
+        triangles.push([a, b, c]);
+        points.remove(ear);
+    }
+    if points.len() == 3 && cross(&points[0], &points[1], &points[2]) > 0. {
+        triangles.push([points[0], points[1], points[2]]);
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Vec<Coordinates> {
+        vec![
+            Coordinates::new(x, y),
+            Coordinates::new(x + width, y),
+            Coordinates::new(x + width, y + height),
+            Coordinates::new(x, y + height),
+        ]
+    }
+
+    fn length(path: &[Coordinates]) -> f32 {
+        path.windows(2).map(|w| distance(&w[0], &w[1])).sum()
+    }
+
+    #[test]
+    fn navmesh_straight_path() {
+        let navmesh = NavMeshBaker::new().with_walkable_area(rectangle(0., 0., 100., 100.)).bake();
+        let path = navmesh.find_path(Coordinates::new(10., 10.), Coordinates::new(90., 80.)).unwrap();
+        assert_eq!(vec![Coordinates::new(10., 10.), Coordinates::new(90., 80.)], path);
+        assert!(navmesh.find_path(Coordinates::new(10., 10.), Coordinates::new(110., 80.)).is_none());
+    }
+
+    #[test]
+    fn navmesh_path_goes_around_obstacles() {
+        let navmesh = NavMeshBaker::new()
+            .with_walkable_area(rectangle(0., 0., 100., 100.))
+            .with_obstacle(rectangle(40., 10., 20., 80.))
+            .bake();
+        assert!(!navmesh.contains(&Coordinates::new(50., 50.)));
+
+        let path = navmesh.find_path(Coordinates::new(10., 50.), Coordinates::new(90., 50.)).unwrap();
+        // Around the closest corners of the obstacle
+        assert_eq!(4, path.len());
+        assert!(path[1] == Coordinates::new(40., 10.) || path[1] == Coordinates::new(40., 90.));
+        assert!((length(&path) - (2. * (30f32 * 30. + 40. * 40.).sqrt() + 20.)).abs() < 0.01);
+    }
+
+    #[test]
+    fn navmesh_agent_radius() {
+        let navmesh = NavMeshBaker::new()
+            .with_walkable_area(rectangle(0., 0., 100., 100.))
+            .with_obstacle(rectangle(40., 20., 20., 60.))
+            .with_agent_radius(5.)
+            .bake();
+        assert!(!navmesh.contains(&Coordinates::new(2., 50.)));
+        assert!(!navmesh.contains(&Coordinates::new(37., 50.)));
+        assert!(navmesh.contains(&Coordinates::new(33., 50.)));
+
+        let path = navmesh.find_path(Coordinates::new(10., 50.), Coordinates::new(90., 50.)).unwrap();
+        // The obstacle corners are passed at the agent radius
+        for corner in path[1..path.len() - 1].iter() {
+            let closest = [Coordinates::new(40., 20.), Coordinates::new(60., 20.), Coordinates::new(40., 80.), Coordinates::new(60., 80.)]
+                .iter()
+                .map(|c| distance(c, corner))
+                .fold(f32::MAX, f32::min);
+            assert!((closest - 5.).abs() < 0.5, "corner {:?} is {} from the obstacle", corner, closest);
+        }
+        assert!(navmesh.find_path(Coordinates::new(10., 50.), Coordinates::new(50., 50.)).is_none());
+    }
+}
@@ -0,0 +1,603 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use hecs::Entity;
+
+use crate::core::resources::asset_manager::AssetManager;
+use crate::core::world::{ScionWorld, World};
+use crate::graphics::components::tiles::hex::StaggeredGrid;
+use crate::graphics::components::tiles::tilemap::Tilemap;
+use crate::utils::maths::{Position, Vector};
+
+/// Maximum number of paths kept in the cache of the `Pathfinder`
+const MAX_CACHED_PATHS: usize = 1024;
+
+pub(crate) const OFFSETS: [(isize, isize); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Cells considered as neighbours of a cell during a search. Hexagonal and staggered tilemaps
+/// ignore it, the neighbours of their cells being the ones of their grid
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Neighbourhood {
+    /// Horizontal and vertical neighbours only
+    Four,
+    /// Diagonal neighbours too. A diagonal move is only possible when both cells it cuts are walkable
+    Eight,
+}
+
+/// Cost to enter a tile, depending on its pathing value. A tile without cost is not walkable.
+#[derive(Debug, Clone, Default)]
+pub struct PathCosts {
+    costs: HashMap<String, f32>,
+    default_cost: Option<f32>,
+    empty_cost: Option<f32>,
+}
+
+impl PathCosts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the cost to enter a tile having the pathing `pathing`
+    pub fn with_cost(mut self, pathing: &str, cost: f32) -> Self {
+        assert!(cost > 0., "Path cost of {} must be positive", pathing);
+        self.costs.insert(pathing.to_string(), cost);
+        self
+    }
+
+    /// Sets the cost to enter a tile whose pathing has no cost of its own
+    pub fn with_default_cost(mut self, cost: f32) -> Self {
+        assert!(cost > 0., "Default path cost must be positive");
+        self.default_cost = Some(cost);
+        self
+    }
+
+    /// Sets the cost to enter a tile without pathing
+    pub fn with_empty_cost(mut self, cost: f32) -> Self {
+        assert!(cost > 0., "Empty tiles path cost must be positive");
+        self.empty_cost = Some(cost);
+        self
+    }
+
+    /// Cost to enter a tile having the given pathing, if it is walkable
+    pub fn cost(&self, pathing: Option<&str>) -> Option<f32> {
+        match pathing {
+            None => self.empty_cost,
+            Some(pathing) => self.costs.get(pathing).copied().or(self.default_cost),
+        }
+    }
+
+    fn key(&self) -> CostsKey {
+        let mut costs: Vec<(String, u32)> = self.costs.iter().map(|(k, v)| (k.to_string(), v.to_bits())).collect();
+        costs.sort();
+        CostsKey(costs, self.default_cost.map(f32::to_bits), self.empty_cost.map(f32::to_bits))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+struct CostsKey(Vec<(String, u32)>, Option<u32>, Option<u32>);
+
+/// Description of a search on a layer of a tilemap
+#[derive(Debug, Clone)]
+pub struct PathRequest {
+    tilemap: Entity,
+    layer: usize,
+    neighbourhood: Neighbourhood,
+    costs: PathCosts,
+}
+
+impl PathRequest {
+    /// Creates a request on the first layer of `tilemap`, using 4 neighbours
+    pub fn new(tilemap: Entity, costs: PathCosts) -> Self {
+        Self { tilemap, layer: 0, neighbourhood: Neighbourhood::Four, costs }
+    }
+
+    pub fn with_layer(mut self, layer: usize) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_neighbourhood(mut self, neighbourhood: Neighbourhood) -> Self {
+        self.neighbourhood = neighbourhood;
+        self
+    }
+
+    pub fn tilemap(&self) -> Entity {
+        self.tilemap
+    }
+
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    pub fn costs(&self) -> &PathCosts {
+        &self.costs
+    }
+}
+
+/// Path found on a tilemap, from the start cell to the goal cell, both included
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    cells: Vec<Position>,
+    cost: f32,
+}
+
+impl Path {
+    pub fn cells(&self) -> &Vec<Position> {
+        &self.cells
+    }
+
+    /// Sum of the costs of the cells entered along the path
+    pub fn cost(&self) -> f32 {
+        self.cost
+    }
+}
+
+/// Cost to reach each cell of a tilemap layer from the closest source, computed with Dijkstra
+#[derive(Debug, Clone)]
+pub struct DistanceMap {
+    width: usize,
+    height: usize,
+    layer: usize,
+    neighbourhood: Neighbourhood,
+    staggered: Option<StaggeredGrid>,
+    distances: Vec<f32>,
+}
+
+impl DistanceMap {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// Cost to reach the closest source from the cell, `None` if it can't reach any
+    pub fn distance(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.distances[y * self.width + x]).filter(|d| d.is_finite())
+    }
+
+    /// Neighbour of the cell that gets the closest to a source, if the cell is not a source itself
+    pub fn next_step(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let current = self.distance(x, y)?;
+        let offsets = match self.staggered {
+            Some(grid) => grid.neighbour_offsets(x, y),
+            None => neighbour_offsets(self.neighbourhood).to_vec(),
+        };
+        offsets
+            .into_iter()
+            .filter_map(|(dx, dy)| {
+                let (nx, ny) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                let distance = self.distance(nx, ny)?;
+                let corner_free = self.staggered.is_some()
+                    || dx == 0
+                    || dy == 0
+                    || (self.distance(nx, y).is_some() && self.distance(x, ny).is_some());
+                (corner_free && distance < current).then_some(((nx, ny), distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(cell, _)| cell)
+    }
+
+    /// Path from the cell to its closest source
+    pub fn path_from(&self, x: usize, y: usize) -> Option<Path> {
+        let cost = self.distance(x, y)?;
+        let mut cells = vec![Position::new(x, y, self.layer)];
+        let mut current = (x, y);
+        while let Some(next) = self.next_step(current.0, current.1) {
+            cells.push(Position::new(next.0, next.1, self.layer));
+            current = next;
+        }
+        Some(Path { cells, cost })
+    }
+}
+
+pub(crate) fn neighbour_offsets(neighbourhood: Neighbourhood) -> &'static [(isize, isize)] {
+    match neighbourhood {
+        Neighbourhood::Four => &OFFSETS[0..4],
+        Neighbourhood::Eight => &OFFSETS,
+    }
+}
+
+/// Pathing values of a tilemap layer, read once for every search until the tilemap changes
+pub(crate) struct NavigationGrid {
+    pub(crate) revision: u64,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pathings: Vec<Option<String>>,
+    /// Length of a move toward each of the `OFFSETS`, relative to the shortest straight move
+    pub(crate) step_lengths: [f32; 8],
+    /// Grid of hexagonal and staggered tilemaps, whose cells have their own neighbours
+    pub(crate) staggered: Option<StaggeredGrid>,
+}
+
+impl NavigationGrid {
+    pub(crate) fn build(world: &mut ScionWorld, assets: &AssetManager, tilemap: Entity, layer: usize) -> Option<Self> {
+        let (width, height, revision, axes, staggered) = {
+            let mut entry = world.entry::<&Tilemap>(tilemap).ok()?;
+            let tilemap = entry.get()?;
+            let tile_size = assets.retrieve_tileset(tilemap.tileset_ref()).map_or(1., |t| t.tile_width as f32);
+            // Isometric tilemaps don't have the same screen length for every move
+            let axes = if tilemap.is_isometric() { Some(tilemap.grid_axes(tile_size)) } else { None };
+            (tilemap.width(), tilemap.height(), tilemap.revision(), axes, tilemap.tilemap_type().staggered_grid())
+        };
+        let pathings = (0..width * height)
+            .map(|i| Tilemap::retrieve_pathing(world, tilemap, &Position::new(i % width, i / width, layer), assets))
+            .collect();
+
+        let length = |v: &Vector| (v.x * v.x + v.y * v.y).sqrt();
+        let step_lengths = match axes.filter(|(x_axis, y_axis)| length(x_axis).min(length(y_axis)) > f32::EPSILON) {
+            None => OFFSETS.map(|(dx, dy)| if dx != 0 && dy != 0 { std::f32::consts::SQRT_2 } else { 1. }),
+            Some((x_axis, y_axis)) => {
+                let unit = length(&x_axis).min(length(&y_axis));
+                OFFSETS.map(|(dx, dy)| {
+                    let (dx, dy) = (dx as f32, dy as f32);
+                    length(&Vector::new(dx * x_axis.x + dy * y_axis.x, dx * x_axis.y + dy * y_axis.y)) / unit
+                })
+            }
+        };
+        Some(Self { revision, width, height, pathings, step_lengths, staggered })
+    }
+
+    pub(crate) fn cell_costs(&self, costs: &PathCosts) -> Vec<Option<f32>> {
+        self.pathings.iter().map(|p| costs.cost(p.as_deref())).collect()
+    }
+
+    /// Walkable neighbours of a cell, with the cost to enter them
+    fn neighbours<'a>(
+        &'a self,
+        cell_costs: &'a [Option<f32>],
+        index: usize,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (usize, f32)> + 'a {
+        let (x, y) = (index % self.width, index / self.width);
+        let walkable = move |x: usize, y: usize| x < self.width && y < self.height && cell_costs[y * self.width + x].is_some();
+        // Every move of a hexagonal or staggered grid has the same length, and doesn't cut any other cell
+        let offsets: Vec<((isize, isize), f32)> = match self.staggered {
+            Some(grid) => grid.neighbour_offsets(x, y).into_iter().map(|offset| (offset, 1.)).collect(),
+            None => neighbour_offsets(neighbourhood).iter().copied().zip(self.step_lengths).collect(),
+        };
+        let cuts_corners = self.staggered.is_none();
+        offsets.into_iter().filter_map(move |((dx, dy), step_length)| {
+            let (nx, ny) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            if !walkable(nx, ny) || (cuts_corners && dx != 0 && dy != 0 && !(walkable(nx, y) && walkable(x, ny))) {
+                return None;
+            }
+            let neighbour = ny * self.width + nx;
+            Some((neighbour, cell_costs[neighbour].expect("Walkable cells have a cost") * step_length))
+        })
+    }
+
+    /// Lower bound of the cost between two cells, used as A* heuristic
+    fn heuristic(&self, min_cost: f32, from: usize, to: usize, neighbourhood: Neighbourhood) -> f32 {
+        if let Some(grid) = self.staggered {
+            let cell = |index: usize| (index % self.width, index / self.width);
+            return min_cost * grid.distance(cell(from), cell(to)) as f32;
+        }
+        let dx = (from % self.width).abs_diff(to % self.width) as f32;
+        let dy = (from / self.width).abs_diff(to / self.width) as f32;
+        match neighbourhood {
+            Neighbourhood::Four => min_cost * (dx + dy),
+            Neighbourhood::Eight => {
+                let diagonal = self.step_lengths[4..].iter().copied().fold(f32::MAX, f32::min);
+                let (short, long) = (dx.min(dy), dx.max(dy));
+                min_cost * (short * diagonal.min(2.) + (long - short) * diagonal.min(1.))
+            }
+        }
+    }
+
+    fn to_position(&self, index: usize, layer: usize) -> Position {
+        Position::new(index % self.width, index / self.width, layer)
+    }
+}
+
+#[derive(PartialEq)]
+pub(crate) struct OpenCell {
+    pub(crate) cost: f32,
+    pub(crate) index: usize,
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that the binary heap pops the lowest cost first
+        other.cost.total_cmp(&self.cost).then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn a_star(grid: &NavigationGrid, request: &PathRequest, start: usize, goal: usize) -> Option<Path> {
+    let cell_costs = grid.cell_costs(&request.costs);
+    cell_costs[goal]?;
+    let min_cost = cell_costs.iter().flatten().copied().fold(f32::MAX, f32::min);
+    let mut costs = vec![f32::INFINITY; cell_costs.len()];
+    let mut came_from: Vec<Option<usize>> = vec![None; cell_costs.len()];
+    let mut open = BinaryHeap::new();
+    costs[start] = 0.;
+    open.push(OpenCell { cost: grid.heuristic(min_cost, start, goal, request.neighbourhood), index: start });
+
+    while let Some(OpenCell { cost, index }) = open.pop() {
+        if index == goal {
+            let mut cells = vec![grid.to_position(goal, request.layer)];
+            let mut current = goal;
+            while let Some(previous) = came_from[current] {
+                cells.push(grid.to_position(previous, request.layer));
+                current = previous;
+            }
+            cells.reverse();
+            return Some(Path { cells, cost: costs[goal] });
+        }
+        if cost > costs[index] + grid.heuristic(min_cost, index, goal, request.neighbourhood) {
+            continue;
+        }
+        for (neighbour, step_cost) in grid.neighbours(&cell_costs, index, request.neighbourhood) {
+            let neighbour_cost = costs[index] + step_cost;
+            if neighbour_cost < costs[neighbour] {
+                costs[neighbour] = neighbour_cost;
+                came_from[neighbour] = Some(index);
+                open.push(OpenCell {
+                    cost: neighbour_cost + grid.heuristic(min_cost, neighbour, goal, request.neighbourhood),
+                    index: neighbour,
+                });
+            }
+        }
+    }
+    None
+}
+
+fn dijkstra(grid: &NavigationGrid, request: &PathRequest, sources: &[usize]) -> DistanceMap {
+    let cell_costs = grid.cell_costs(&request.costs);
+    let mut distances = vec![f32::INFINITY; cell_costs.len()];
+    let mut open = BinaryHeap::new();
+    for source in sources.iter().filter(|s| cell_costs[**s].is_some()) {
+        distances[*source] = 0.;
+        open.push(OpenCell { cost: 0., index: *source });
+    }
+    while let Some(OpenCell { cost, index }) = open.pop() {
+        if cost > distances[index] {
+            continue;
+        }
+        for (neighbour, step_cost) in grid.neighbours(&cell_costs, index, request.neighbourhood) {
+            if cost + step_cost < distances[neighbour] {
+                distances[neighbour] = cost + step_cost;
+                open.push(OpenCell { cost: cost + step_cost, index: neighbour });
+            }
+        }
+    }
+    DistanceMap {
+        width: grid.width,
+        height: grid.height,
+        layer: request.layer,
+        neighbourhood: request.neighbourhood,
+        staggered: grid.staggered,
+        distances,
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+struct SearchKey {
+    tilemap: Entity,
+    layer: usize,
+    neighbourhood: Neighbourhood,
+    costs: CostsKey,
+    cells: Vec<usize>,
+}
+
+/// `Pathfinder` is the resource computing paths on the layers of tilemaps, using the pathing of their tiles
+/// and the costs of a [`PathRequest`].
+///
+/// Paths and distance maps are cached, and the cache of a tilemap is dropped when one of its tiles is modified
+/// with `Tilemap::modify_sprite_tile`. Changes done in another way need a call to `invalidate`.
+#[derive(Default)]
+pub struct Pathfinder {
+    grids: HashMap<(Entity, usize), NavigationGrid>,
+    paths: HashMap<SearchKey, Option<Path>>,
+    distance_maps: HashMap<SearchKey, DistanceMap>,
+}
+
+impl Pathfinder {
+    /// Finds the cheapest path from `start` to `goal` with A*. The start cell doesn't need to be walkable
+    pub fn find_path(
+        &mut self,
+        world: &mut ScionWorld,
+        assets: &AssetManager,
+        request: &PathRequest,
+        start: (usize, usize),
+        goal: (usize, usize),
+    ) -> Option<Path> {
+        let grid = self.grid(world, assets, request)?;
+        let (start, goal) = (grid_index(grid, start)?, grid_index(grid, goal)?);
+        let key = search_key(request, vec![start, goal]);
+        if let Some(path) = self.paths.get(&key) {
+            return path.clone();
+        }
+        let grid = self.grids.get(&(request.tilemap, request.layer)).expect("Navigation grid computed above");
+        let path = a_star(grid, request, start, goal);
+        if self.paths.len() >= MAX_CACHED_PATHS {
+            self.paths.clear();
+        }
+        self.paths.insert(key, path.clone());
+        path
+    }
+
+    /// Computes, with Dijkstra, the cost to reach the closest of the `sources` from every cell of the layer
+    pub fn distance_map(
+        &mut self,
+        world: &mut ScionWorld,
+        assets: &AssetManager,
+        request: &PathRequest,
+        sources: &[(usize, usize)],
+    ) -> Option<&DistanceMap> {
+        let grid = self.grid(world, assets, request)?;
+        let mut indexes: Vec<usize> = sources.iter().filter_map(|s| grid_index(grid, *s)).collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+        let key = search_key(request, indexes);
+        if !self.distance_maps.contains_key(&key) {
+            let grid = self.grids.get(&(request.tilemap, request.layer)).expect("Navigation grid computed above");
+            let distance_map = dijkstra(grid, request, &key.cells);
+            if self.distance_maps.len() >= MAX_CACHED_PATHS {
+                self.distance_maps.clear();
+            }
+            self.distance_maps.insert(key.clone(), distance_map);
+        }
+        self.distance_maps.get(&key)
+    }
+
+    /// Drops everything computed for `tilemap`
+    pub fn invalidate(&mut self, tilemap: Entity) {
+        self.grids.retain(|(entity, _), _| *entity != tilemap);
+        self.paths.retain(|key, _| key.tilemap != tilemap);
+        self.distance_maps.retain(|key, _| key.tilemap != tilemap);
+    }
+
+    pub fn clear(&mut self) {
+        self.grids.clear();
+        self.paths.clear();
+        self.distance_maps.clear();
+    }
+
+    /// Retrieves the navigation grid of the request, computing it again if the tilemap changed
+    fn grid(&mut self, world: &mut ScionWorld, assets: &AssetManager, request: &PathRequest) -> Option<&NavigationGrid> {
+        let revision = world.entry::<&Tilemap>(request.tilemap).ok()?.get()?.revision();
+        let grid_key = (request.tilemap, request.layer);
+        if self.grids.get(&grid_key).is_none_or(|grid| grid.revision != revision) {
+            self.invalidate(request.tilemap);
+            let grid = NavigationGrid::build(world, assets, request.tilemap, request.layer)?;
+            self.grids.insert(grid_key, grid);
+        }
+        self.grids.get(&grid_key)
+    }
+}
+
+fn grid_index(grid: &NavigationGrid, (x, y): (usize, usize)) -> Option<usize> {
+    (x < grid.width && y < grid.height).then_some(y * grid.width + x)
+}
+
+fn search_key(request: &PathRequest, cells: Vec<usize>) -> SearchKey {
+    SearchKey {
+        tilemap: request.tilemap,
+        layer: request.layer,
+        neighbourhood: request.neighbourhood,
+        costs: request.costs.key(),
+        cells,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::maths::transform::Transform;
+    use crate::core::world::GameData;
+    use crate::graphics::components::tiles::test_utils::{pathing_tilemap, typed_pathing_tilemap};
+    use crate::graphics::components::tiles::tilemap::{TileInfos, TilemapType};
+    use crate::utils::maths::Dimensions;
+
+    const FLOOR: usize = 0;
+    const MUD: usize = 1;
+    const WALL: usize = 2;
+
+    /// 5x5 map with a wall on the column 2, except on the last row which is mud
+    fn map(data: &mut GameData) -> Entity {
+        let pathing: &[(&str, &[usize])] = &[("floor", &[FLOOR]), ("mud", &[MUD]), ("wall", &[WALL])];
+        pathing_tilemap(data, Dimensions::new(5, 5, 1), Transform::default(), pathing, |p| match (p.x(), p.y()) {
+            (2, 4) => TileInfos::new(Some(MUD)),
+            (2, _) => TileInfos::new(Some(WALL)),
+            _ => TileInfos::new(Some(FLOOR)),
+        })
+    }
+
+    fn costs() -> PathCosts {
+        PathCosts::new().with_cost("floor", 1.).with_cost("mud", 3.)
+    }
+
+    fn find(data: &mut GameData, request: &PathRequest, start: (usize, usize), goal: (usize, usize)) -> Option<Path> {
+        let (world, resources) = data.split();
+        let mut pathfinder = resources.get_resource_mut::<Pathfinder>().unwrap();
+        pathfinder.find_path(world, &resources.assets(), request, start, goal)
+    }
+
+    #[test]
+    fn a_star_goes_around_walls() {
+        let mut data = GameData::default();
+        let tilemap = map(&mut data);
+        data.insert_resource(Pathfinder::default());
+
+        let request = PathRequest::new(tilemap, costs());
+        let path = find(&mut data, &request, (0, 0), (4, 0)).unwrap();
+        assert_eq!(Position::new(0, 0, 0), path.cells()[0]);
+        assert_eq!(Position::new(4, 0, 0), *path.cells().last().unwrap());
+        assert!(path.cells().contains(&Position::new(2, 4, 0)));
+        // 4 floor cells down, 1 floor cell right, the mud, then 1 floor cell right, 4 up and the last one
+        assert_eq!(14., path.cost());
+
+        let diagonal = find(&mut data, &request.clone().with_neighbourhood(Neighbourhood::Eight), (0, 0), (4, 0)).unwrap();
+        assert!(diagonal.cost() < path.cost());
+        assert!(find(&mut data, &PathRequest::new(tilemap, PathCosts::new().with_cost("floor", 1.)), (0, 0), (4, 0)).is_none());
+    }
+
+    #[test]
+    fn dijkstra_distance_map() {
+        let mut data = GameData::default();
+        let tilemap = map(&mut data);
+        let mut pathfinder = Pathfinder::default();
+        let (world, resources) = data.split();
+        let distances = pathfinder
+            .distance_map(world, &resources.assets(), &PathRequest::new(tilemap, costs()), &[(0, 0)])
+            .unwrap();
+        assert_eq!(Some(0.), distances.distance(0, 0));
+        assert_eq!(Some(2.), distances.distance(1, 1));
+        assert_eq!(None, distances.distance(2, 0));
+        assert_eq!(Some(14.), distances.distance(4, 0));
+        assert_eq!(Some((3, 0)), distances.next_step(4, 0));
+        assert_eq!(13, distances.path_from(4, 0).unwrap().cells().len());
+    }
+
+    #[test]
+    fn hexagonal_paths_use_the_hexagonal_neighbours() {
+        let mut data = GameData::default();
+        let tilemap_type = TilemapType::pointy_hexagonal(16);
+        let tilemap = typed_pathing_tilemap(&mut data, Dimensions::new(5, 5, 1), Transform::default(), tilemap_type, &[("floor", &[FLOOR])], |_| {
+            TileInfos::new(Some(FLOOR))
+        });
+        data.insert_resource(Pathfinder::default());
+
+        // Odd rows being shifted to the right, 3 moves go down to the third row while moving one column right
+        let path = find(&mut data, &PathRequest::new(tilemap, costs()), (0, 0), (1, 3)).unwrap();
+        assert_eq!(3., path.cost());
+        assert_eq!(4, path.cells().len());
+    }
+
+    #[test]
+    fn cache_is_invalidated_when_tiles_change() {
+        let mut data = GameData::default();
+        let tilemap = map(&mut data);
+        data.insert_resource(Pathfinder::default());
+        let request = PathRequest::new(tilemap, costs());
+
+        assert!(find(&mut data, &request, (0, 0), (4, 0)).is_some());
+        assert_eq!(1, data.get_resource::<Pathfinder>().unwrap().paths.len());
+
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(2, 4, 0), WALL);
+        assert!(find(&mut data, &request, (0, 0), (4, 0)).is_none());
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(2, 0, 0), FLOOR);
+        assert_eq!(4., find(&mut data, &request, (0, 0), (4, 0)).unwrap().cost());
+    }
+}
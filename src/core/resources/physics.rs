@@ -0,0 +1,44 @@
+use crate::utils::maths::Vector;
+
+/// `Physics` is the resource holding the settings of the rigid body simulation, executed at each fixed update.
+pub struct Physics {
+    /// Acceleration applied to every dynamic body, in pixels per second squared
+    gravity: Vector,
+    /// Duration of a simulation step, in seconds
+    timestep: f32,
+    /// Number of collision resolution passes per step
+    iterations: usize,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self { gravity: Vector::new(0., 980.), timestep: 1. / 60., iterations: 4 }
+    }
+}
+
+impl Physics {
+    pub fn gravity(&self) -> &Vector {
+        &self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vector) {
+        self.gravity = gravity;
+    }
+
+    pub fn timestep(&self) -> f32 {
+        self.timestep
+    }
+
+    /// Changes the duration of a step. It should match the fixed update rate, which is 60 per second by default
+    pub fn set_timestep(&mut self, timestep: f32) {
+        self.timestep = timestep;
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+}
@@ -0,0 +1,347 @@
+use hecs::Entity;
+
+use crate::core::components::maths::collider::{sweep_shape, Aabb, Collider, ColliderMask, ColliderShape};
+use crate::core::components::maths::coordinates::Coordinates;
+use crate::core::components::maths::transform::Transform;
+use crate::core::resources::collision_layers::CollisionLayers;
+use crate::utils::maths::Vector;
+
+/// Result of a raycast
+#[derive(Debug, Clone)]
+pub struct RaycastHit {
+    entity: Entity,
+    mask: ColliderMask,
+    point: Coordinates,
+    normal: Vector,
+    distance: f32,
+}
+
+impl RaycastHit {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn mask(&self) -> &ColliderMask {
+        &self.mask
+    }
+
+    /// World coordinates where the ray hit the collider
+    pub fn point(&self) -> &Coordinates {
+        &self.point
+    }
+
+    /// Normal of the collider surface at the hit point
+    pub fn normal(&self) -> &Vector {
+        &self.normal
+    }
+
+    /// Distance from the ray origin to the hit point
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+}
+
+/// Result of a sweep
+#[derive(Debug, Clone)]
+pub struct SweepHit {
+    entity: Entity,
+    mask: ColliderMask,
+    distance: f32,
+    normal: Vector,
+}
+
+impl SweepHit {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn mask(&self) -> &ColliderMask {
+        &self.mask
+    }
+
+    /// Distance the swept collider can travel before touching the hit collider
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Normal of the contact, pointing toward the swept collider
+    pub fn normal(&self) -> &Vector {
+        &self.normal
+    }
+}
+
+struct QueryCollider {
+    entity: Entity,
+    mask: ColliderMask,
+    layer_bits: u64,
+    shape: ColliderShape,
+    aabb: Aabb,
+}
+
+/// `PhysicsQuery` is the resource used to query the colliders of the world : raycasts, overlaps and sweeps.
+///
+/// Queries run against the colliders as they were during the last collision computation, which happens each frame.
+/// Every query takes a `mask_filter`, working like the collider filters : a collider matches when its layer is
+/// one of the filter's layers (see `CollisionLayers`). An empty `mask_filter` matches every mask.
+#[derive(Default)]
+pub struct PhysicsQuery {
+    colliders: Vec<QueryCollider>,
+    layers: CollisionLayers,
+}
+
+impl PhysicsQuery {
+    pub(crate) fn refresh<'a>(&mut self, colliders: impl Iterator<Item = (Entity, &'a Collider, ColliderShape, Aabb)>, layers: &CollisionLayers) {
+        self.colliders = colliders
+            .map(|(entity, collider, shape, aabb)| QueryCollider {
+                entity,
+                mask: collider.mask_cloned(),
+                layer_bits: collider.layer_bits(),
+                shape,
+                aabb,
+            })
+            .collect();
+        self.layers.clone_from(layers);
+    }
+
+    fn filtered<'a>(&'a self, mask_filter: &'a [ColliderMask]) -> impl Iterator<Item = &'a QueryCollider> + 'a {
+        // Unknown custom masks and invalid numeric layers have no layer, they don't match any collider
+        let filter_bits = mask_filter.iter().filter_map(|mask| self.layers.layer(mask)).fold(0u64, |bits, layer| bits | 1u64 << layer);
+        self.colliders.iter().filter(move |c| mask_filter.is_empty() || c.layer_bits & filter_bits != 0)
+    }
+
+    /// Casts a ray from `origin` toward `direction` and returns the first collider hit within `max_distance`
+    pub fn raycast(
+        &self,
+        origin: Coordinates,
+        direction: Vector,
+        max_distance: f32,
+        mask_filter: &[ColliderMask],
+    ) -> Option<RaycastHit> {
+        self.raycast_all(origin, direction, max_distance, mask_filter).into_iter().next()
+    }
+
+    /// Casts a ray from `origin` toward `direction` and returns every collider hit within `max_distance`,
+    /// closest first
+    pub fn raycast_all(
+        &self,
+        origin: Coordinates,
+        direction: Vector,
+        max_distance: f32,
+        mask_filter: &[ColliderMask],
+    ) -> Vec<RaycastHit> {
+        let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+        if length <= f32::EPSILON {
+            return vec![];
+        }
+        let direction = Vector::new(direction.x / length, direction.y / length);
+        let end = Coordinates::new(origin.x + direction.x * max_distance, origin.y + direction.y * max_distance);
+        let ray_aabb = Aabb::new(origin.x.min(end.x), origin.y.min(end.y), origin.x.max(end.x), origin.y.max(end.y));
+
+        let mut hits: Vec<RaycastHit> = self
+            .filtered(mask_filter)
+            .filter(|c| c.aabb.intersects(&ray_aabb))
+            .filter_map(|c| {
+                c.shape.ray_intersection(&origin, &direction, max_distance).map(|(distance, normal)| RaycastHit {
+                    entity: c.entity,
+                    mask: c.mask.clone(),
+                    point: Coordinates::new(origin.x + direction.x * distance, origin.y + direction.y * distance),
+                    normal,
+                    distance,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Returns every collider containing `point`
+    pub fn overlap_point(&self, point: Coordinates, mask_filter: &[ColliderMask]) -> Vec<Entity> {
+        let point_aabb = Aabb::new(point.x, point.y, point.x, point.y);
+        self.filtered(mask_filter)
+            .filter(|c| c.aabb.intersects(&point_aabb) && c.shape.contains_point(&point))
+            .map(|c| c.entity)
+            .collect()
+    }
+
+    /// Returns every collider overlapping `collider` if it was placed at `transform`.
+    /// The shape is computed like any collider, using its offset, pivot and the transform angle.
+    /// `ignored` can be used to exclude the entity owning the collider from the results.
+    pub fn overlap_shape(
+        &self,
+        collider: &Collider,
+        transform: &Transform,
+        mask_filter: &[ColliderMask],
+        ignored: Option<Entity>,
+    ) -> Vec<Entity> {
+        let shape = collider.collider_shape(transform);
+        let aabb = shape.aabb();
+        self.filtered(mask_filter)
+            .filter(|c| Some(c.entity) != ignored && c.aabb.intersects(&aabb) && shape.intersection(&c.shape).is_some())
+            .map(|c| c.entity)
+            .collect()
+    }
+
+    /// Moves `collider`, placed at `transform`, along `direction` and returns the first collider it would touch
+    /// within `max_distance`. Colliders already overlapping at the start position are ignored.
+    pub fn sweep(
+        &self,
+        collider: &Collider,
+        transform: &Transform,
+        direction: Vector,
+        max_distance: f32,
+        mask_filter: &[ColliderMask],
+        ignored: Option<Entity>,
+    ) -> Option<SweepHit> {
+        self.sweep_all(collider, transform, direction, max_distance, mask_filter, ignored).into_iter().next()
+    }
+
+    /// Moves `collider`, placed at `transform`, along `direction` and returns every collider it would touch
+    /// within `max_distance`, closest first. Colliders already overlapping at the start position are ignored.
+    pub fn sweep_all(
+        &self,
+        collider: &Collider,
+        transform: &Transform,
+        direction: Vector,
+        max_distance: f32,
+        mask_filter: &[ColliderMask],
+        ignored: Option<Entity>,
+    ) -> Vec<SweepHit> {
+        self.sweep_shape_all(&collider.collider_shape(transform), direction, max_distance, mask_filter, ignored)
+    }
+
+    pub(crate) fn sweep_shape_all(
+        &self,
+        shape: &ColliderShape,
+        direction: Vector,
+        max_distance: f32,
+        mask_filter: &[ColliderMask],
+        ignored: Option<Entity>,
+    ) -> Vec<SweepHit> {
+        let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+        if length <= f32::EPSILON {
+            return vec![];
+        }
+        let direction = Vector::new(direction.x / length, direction.y / length);
+        let swept_aabb = shape.aabb().swept(&direction, max_distance);
+
+        let mut hits: Vec<SweepHit> = self
+            .filtered(mask_filter)
+            .filter(|c| Some(c.entity) != ignored && c.aabb.intersects(&swept_aabb))
+            .filter_map(|c| {
+                sweep_shape(shape, &direction, max_distance, &c.shape).map(|(distance, normal)| SweepHit {
+                    entity: c.entity,
+                    mask: c.mask.clone(),
+                    distance,
+                    normal,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::maths::collider::ColliderType;
+    use crate::core::world::{GameData, World};
+
+    fn query_with(colliders: Vec<(Entity, Collider, Transform)>) -> PhysicsQuery {
+        query_with_layers(colliders, CollisionLayers::default())
+    }
+
+    fn query_with_layers(mut colliders: Vec<(Entity, Collider, Transform)>, mut layers: CollisionLayers) -> PhysicsQuery {
+        colliders.iter_mut().for_each(|(_, c, _)| c.resolve_layers(&mut layers));
+        let mut query = PhysicsQuery::default();
+        query.refresh(
+            colliders.iter().map(|(e, c, t)| {
+                let shape = c.collider_shape(t);
+                let aabb = shape.aabb();
+                (*e, c, shape, aabb)
+            }),
+            &layers,
+        );
+        query
+    }
+
+    #[test]
+    fn raycast_test() {
+        let mut data = GameData::default();
+        let (wall, ball) = (data.push((1,)), data.push((2,)));
+        let query = query_with(vec![
+            (wall, Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(10, 100)), Transform::from_xy(50., 0.)),
+            (ball, Collider::new(ColliderMask::Item, vec![], ColliderType::CircleCollider(5)), Transform::from_xy(20., 0.)),
+        ]);
+
+        let hit = query.raycast(Coordinates::new(0., 5.), Vector::new(1., 0.), 100., &[]).unwrap();
+        assert_eq!(ball, hit.entity());
+        assert!((hit.distance() - 20.).abs() < 0.001);
+        assert!((hit.normal().x() + 1.).abs() < 0.001);
+
+        let hit = query.raycast(Coordinates::new(0., 5.), Vector::new(1., 0.), 100., &[ColliderMask::Landscape]).unwrap();
+        assert_eq!(wall, hit.entity());
+        assert!((hit.point().x - 50.).abs() < 0.001);
+
+        assert_eq!(2, query.raycast_all(Coordinates::new(0., 5.), Vector::new(2., 0.), 100., &[]).len());
+        assert!(query.raycast(Coordinates::new(0., 5.), Vector::new(1., 0.), 10., &[]).is_none());
+    }
+
+    #[test]
+    fn overlap_test() {
+        let mut data = GameData::default();
+        let (wall, ball) = (data.push((1,)), data.push((2,)));
+        let query = query_with(vec![
+            (wall, Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(10, 100)), Transform::from_xy(50., 0.)),
+            (ball, Collider::new(ColliderMask::Item, vec![], ColliderType::CircleCollider(5)), Transform::from_xy(20., 0.)),
+        ]);
+
+        assert_eq!(vec![ball], query.overlap_point(Coordinates::new(25., 5.), &[]));
+        assert!(query.overlap_point(Coordinates::new(21., 1.), &[]).is_empty());
+
+        let probe = Collider::new(ColliderMask::None, vec![], ColliderType::SquareCollider(10));
+        assert_eq!(vec![wall], query.overlap_shape(&probe, &Transform::from_xy(45., 50.), &[], None));
+        assert!(query.overlap_shape(&probe, &Transform::from_xy(45., 50.), &[ColliderMask::Item], None).is_empty());
+    }
+
+    #[test]
+    fn filters_match_the_layers_test() {
+        let mut data = GameData::default();
+        let (enemy, crate_entity) = (data.push((1,)), data.push((2,)));
+        let mut layers = CollisionLayers::default();
+        layers.register_layer("Enemy");
+        let query = query_with_layers(
+            vec![
+                (enemy, Collider::new(ColliderMask::Custom("Enemy".to_string()), vec![], ColliderType::SquareCollider(10)), Transform::from_xy(0., 0.)),
+                (crate_entity, Collider::new(ColliderMask::Layer(7), vec![], ColliderType::SquareCollider(10)), Transform::from_xy(0., 0.)),
+            ],
+            layers,
+        );
+
+        let point = Coordinates::new(5., 5.);
+        assert_eq!(vec![crate_entity], query.overlap_point(point, &[ColliderMask::Layer(7)]));
+        assert_eq!(vec![enemy], query.overlap_point(point, &[ColliderMask::Custom("Enemy".to_string())]));
+        assert_eq!(2, query.overlap_point(point, &[ColliderMask::Custom("Enemy".to_string()), ColliderMask::Layer(7)]).len());
+        assert!(query.overlap_point(point, &[ColliderMask::Custom("Unknown".to_string())]).is_empty());
+        assert!(query.overlap_point(point, &[ColliderMask::Layer(6)]).is_empty());
+        assert!(query.overlap_point(point, &[ColliderMask::Layer(70)]).is_empty());
+    }
+
+    #[test]
+    fn sweep_test() {
+        let mut data = GameData::default();
+        let wall = data.push((1,));
+        let query = query_with(vec![(
+            wall,
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(10, 100)),
+            Transform::from_xy(50., 0.),
+        )]);
+
+        let probe = Collider::new(ColliderMask::Character, vec![], ColliderType::SquareCollider(10));
+        let hit = query.sweep(&probe, &Transform::from_xy(0., 20.), Vector::new(1., 0.), 100., &[], None).unwrap();
+        assert_eq!(wall, hit.entity());
+        assert!((hit.distance() - 40.).abs() < 0.1);
+        assert!((hit.normal().x() + 1.).abs() < 0.001);
+        assert!(query.sweep(&probe, &Transform::from_xy(0., 20.), Vector::new(1., 0.), 30., &[], None).is_none());
+    }
+}
@@ -0,0 +1,277 @@
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use hecs::{Component, Entity};
+
+use crate::core::command_buffer::CommandBuffer;
+use crate::core::components::maths::transform::Transform;
+use crate::core::components::Rewindable;
+use crate::core::world::{ScionWorld, World};
+use crate::graphics::components::tiles::sprite::Sprite;
+
+type ComponentState = Box<dyn Any + Send + Sync>;
+
+/// Functions used to capture and restore the state of a single component type
+#[derive(Clone, Copy)]
+struct ComponentTracker {
+    capture: fn(&ScionWorld, Entity) -> Option<ComponentState>,
+    restore: fn(&mut ScionWorld, &mut CommandBuffer, Entity, &ComponentState),
+}
+
+/// The state of every `Rewindable` entity at a given fixed step
+struct Snapshot {
+    step: u64,
+    /// For each entity, the state of each tracked component, in the trackers order
+    entities: HashMap<Entity, Vec<Option<ComponentState>>>,
+}
+
+/// `Rewind` is the resource keeping a rolling buffer of the states of the entities marked as
+/// [`Rewindable`]. A snapshot is captured at each fixed update, and `GameData::rewind` restores one of them.
+///
+/// `Transform` and the `Sprite` tile numbers are tracked by default, other components can be added
+/// using `register_component`.
+pub struct Rewind {
+    /// Maximum number of snapshots kept in the buffer
+    capacity: usize,
+    trackers: Vec<ComponentTracker>,
+    snapshots: VecDeque<Snapshot>,
+    /// Number of fixed steps recorded since the start, or since the last rewind target
+    step: u64,
+    recording: bool,
+}
+
+impl Default for Rewind {
+    fn default() -> Self {
+        // 10 seconds of fixed updates
+        Self::new(600)
+    }
+}
+
+impl Rewind {
+    /// Creates a new `Rewind` keeping at most `capacity` snapshots
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            trackers: vec![
+                ComponentTracker { capture: capture_transform, restore: restore_transform },
+                ComponentTracker { capture: capture_sprite, restore: restore_sprite },
+            ],
+            snapshots: VecDeque::with_capacity(capacity),
+            step: 0,
+            recording: true,
+        }
+    }
+
+    /// Adds the component `T` to the list of components captured for `Rewindable` entities
+    pub fn register_component<T: Component + Clone>(&mut self) {
+        self.trackers.push(ComponentTracker { capture: capture_cloned::<T>, restore: restore_cloned::<T> });
+        self.clear();
+    }
+
+    /// Changes the maximum number of snapshots kept, dropping the oldest ones if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.snapshots.len() > capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of steps it is currently possible to go back
+    pub fn available_steps(&self) -> usize {
+        self.snapshots.len().saturating_sub(1)
+    }
+
+    /// The fixed step of the last captured snapshot. After a rewind, this is the step that was restored,
+    /// so that recorded inputs can be replayed from there.
+    pub fn current_step(&self) -> u64 {
+        self.step
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Stops capturing snapshots, for example while displaying a rewind preview
+    pub fn pause_recording(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn resume_recording(&mut self) {
+        self.recording = true;
+    }
+
+    /// Removes every snapshot from the buffer
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    pub(crate) fn capture(&mut self, world: &ScionWorld) {
+        if !self.recording || self.capacity == 0 {
+            return;
+        }
+        let entities: Vec<Entity> = world.query::<&Rewindable>().iter().map(|(e, _)| e).collect();
+        let entities = entities
+            .into_iter()
+            .map(|e| (e, self.trackers.iter().map(|tracker| (tracker.capture)(world, e)).collect()))
+            .collect();
+
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.step += 1;
+        self.snapshots.push_back(Snapshot { step: self.step, entities });
+    }
+
+    /// Goes back `n_steps` snapshots, or to the oldest one available, restoring every tracked component
+    /// and despawning the `Rewindable` entities created after it. Returns the number of steps rewound.
+    pub(crate) fn restore(&mut self, world: &mut ScionWorld, commands: &mut CommandBuffer, n_steps: usize) -> usize {
+        let rewound = n_steps.min(self.available_steps());
+        if rewound == 0 {
+            return 0;
+        }
+        self.snapshots.truncate(self.snapshots.len() - rewound);
+        let target = self.snapshots.back().expect("A rewind target must exist after the steps check");
+
+        let created_after: Vec<Entity> = world
+            .query::<&Rewindable>()
+            .iter()
+            .map(|(e, _)| e)
+            .filter(|e| !target.entities.contains_key(e))
+            .collect();
+        created_after.into_iter().for_each(|e| {
+            let _r = world.remove(e);
+        });
+
+        let alive: HashSet<Entity> = world.entities();
+        for (entity, states) in target.entities.iter().filter(|(e, _)| alive.contains(e)) {
+            self.trackers.iter().zip(states.iter()).for_each(|(tracker, state)| {
+                if let Some(state) = state {
+                    (tracker.restore)(world, commands, *entity, state);
+                }
+            });
+        }
+        self.step = target.step;
+        rewound
+    }
+}
+
+fn capture_cloned<T: Component + Clone>(world: &ScionWorld, entity: Entity) -> Option<ComponentState> {
+    let mut entry = world.entry::<&T>(entity).ok()?;
+    entry.get().map(|c| Box::new(c.clone()) as ComponentState)
+}
+
+fn restore_cloned<T: Component + Clone>(world: &mut ScionWorld, _: &mut CommandBuffer, entity: Entity, state: &ComponentState) {
+    if let Some(component) = state.downcast_ref::<T>() {
+        let _r = world.add_components(entity, (component.clone(),));
+    }
+}
+
+fn capture_transform(world: &ScionWorld, entity: Entity) -> Option<ComponentState> {
+    capture_cloned::<Transform>(world, entity)
+}
+
+/// Transforms are restored through the command buffer, so that children are updated too
+fn restore_transform(_: &mut ScionWorld, commands: &mut CommandBuffer, entity: Entity, state: &ComponentState) {
+    if let Some(transform) = state.downcast_ref::<Transform>() {
+        let translation = transform.translation();
+        commands.transform_commands.set_x(entity, translation.x());
+        commands.transform_commands.set_y(entity, translation.y());
+        commands.transform_commands.set_z(entity, translation.z());
+        commands.transform_commands.set_angle(entity, transform.local_angle);
+        commands.transform_commands.set_scale(entity, transform.get_scale());
+    }
+}
+
+fn capture_sprite(world: &ScionWorld, entity: Entity) -> Option<ComponentState> {
+    let mut entry = world.entry::<&Sprite>(entity).ok()?;
+    entry.get().map(|s| Box::new(s.get_tile_nb()) as ComponentState)
+}
+
+fn restore_sprite(world: &mut ScionWorld, _: &mut CommandBuffer, entity: Entity, state: &ComponentState) {
+    if let (Some(tile_nb), Ok(sprite)) = (state.downcast_ref::<usize>(), world.entry_mut::<&mut Sprite>(entity)) {
+        if sprite.get_tile_nb() != *tile_nb {
+            sprite.set_tile_nb(*tile_nb);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::systems::rewind_system::rewind_capture_system;
+    use crate::core::world::GameData;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Score(usize);
+
+    fn capture(data: &mut GameData) {
+        let (world, resources) = data.split();
+        resources.get_resource_mut::<Rewind>().unwrap().capture(world);
+    }
+
+    #[test]
+    fn rewind_transform_and_sprite_test() {
+        let mut data = GameData::default();
+        data.insert_resource(Rewind::new(10));
+        let e = data.push((Rewindable, Transform::from_xy(0., 0.), Sprite::new(1)));
+        capture(&mut data);
+
+        data.commands().transform_commands.append_x(e, 5.);
+        data.apply_commands();
+        data.entry_mut::<&mut Sprite>(e).unwrap().set_tile_nb(4);
+        capture(&mut data);
+
+        assert_eq!(1, data.rewind(3));
+        assert_eq!(0., data.entry::<&Transform>(e).unwrap().get().unwrap().translation().x());
+        assert_eq!(1, data.entry::<&Sprite>(e).unwrap().get().unwrap().get_tile_nb());
+        assert_eq!(1, data.get_resource::<Rewind>().unwrap().current_step());
+    }
+
+    #[test]
+    fn rewind_custom_component_and_despawn_test() {
+        let mut data = GameData::default();
+        let mut rewind = Rewind::new(10);
+        rewind.register_component::<Score>();
+        data.insert_resource(rewind);
+
+        let e = data.push((Rewindable, Score(1)));
+        capture(&mut data);
+        data.entry_mut::<&mut Score>(e).unwrap().0 = 2;
+        let spawned = data.push((Rewindable, Score(8)));
+        capture(&mut data);
+
+        assert_eq!(1, data.rewind(1));
+        assert_eq!(Score(1), *data.entry::<&Score>(e).unwrap().get().unwrap());
+        assert!(!data.contains(spawned));
+        assert_eq!(0, data.get_resource::<Rewind>().unwrap().available_steps());
+    }
+
+    #[test]
+    fn rewind_capacity_test() {
+        let mut data = GameData::default();
+        data.insert_resource(Rewind::new(3));
+        data.push((Rewindable, Transform::default()));
+        for _ in 0..5 {
+            capture(&mut data);
+        }
+        assert_eq!(2, data.get_resource::<Rewind>().unwrap().available_steps());
+        assert_eq!(5, data.get_resource::<Rewind>().unwrap().current_step());
+    }
+
+    #[test]
+    fn nothing_is_captured_without_rewindable_entities_test() {
+        let mut data = GameData::default();
+        data.insert_resource(Rewind::new(3));
+        data.push((Transform::default(),));
+        rewind_capture_system(&mut data);
+        assert_eq!(0, data.get_resource::<Rewind>().unwrap().current_step());
+
+        data.push((Rewindable, Transform::default()));
+        rewind_capture_system(&mut data);
+        assert_eq!(1, data.get_resource::<Rewind>().unwrap().current_step());
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use hecs::Entity;
+use log::debug;
+
+use crate::core::components::maths::collider::{Collider, ColliderMask, ColliderType};
+use crate::core::components::maths::hierarchy::Parent;
+use crate::core::components::maths::transform::Transform;
+use crate::core::world::{GameData, World};
+use crate::graphics::components::tiles::atlas::data::{TileObject, TileObjectClass, TileObjectShapeType};
+
+/// Function spawning the entity of a tilemap object, given the object and its transform relative to the tilemap
+pub type TileObjectSpawner = Arc<dyn Fn(&mut GameData, &TileObject, Transform) -> Entity + Send + Sync>;
+
+#[derive(Clone)]
+enum Spawner {
+    Function(TileObjectSpawner),
+    Prefab(String),
+}
+
+/// `TileObjectRegistry` is the resource mapping the classes of the tilemap objects to the way their entities are spawned.
+/// When a tilemap is loaded with `importer::load_tilemap`, an entity is spawned for each of its objects whose class is
+/// registered. This entity is a child of the tilemap, placed at the object's position, and gets a `MapObject` component
+/// holding the object's name, class and custom properties.
+#[derive(Default)]
+pub struct TileObjectRegistry {
+    spawners: HashMap<String, Spawner>,
+    colliders: HashMap<String, (ColliderMask, Vec<ColliderMask>)>,
+}
+
+impl TileObjectRegistry {
+    /// Registers the function spawning the objects of class `class` (see `TileObjectClass::name`)
+    pub fn register_spawner(
+        &mut self,
+        class: &str,
+        spawner: impl Fn(&mut GameData, &TileObject, Transform) -> Entity + Send + Sync + 'static,
+    ) {
+        self.spawners.insert(class.to_string(), Spawner::Function(Arc::new(spawner)));
+    }
+
+    /// Registers the prefab spawned for the objects of class `class` (see `TileObjectClass::name`)
+    pub fn register_prefab(&mut self, class: &str, prefab: &str) {
+        self.spawners.insert(class.to_string(), Spawner::Prefab(prefab.to_string()));
+    }
+
+    /// Gives to the entities spawned for the objects of class `class` a collider built from the object's shape,
+    /// unless the spawned entity already has one. Point objects don't get any collider
+    pub fn register_collider(&mut self, class: &str, mask: ColliderMask, filters: Vec<ColliderMask>) {
+        self.colliders.insert(class.to_string(), (mask, filters));
+    }
+
+    pub fn is_registered(&self, class: &str) -> bool {
+        self.spawners.contains_key(class)
+    }
+}
+
+/// Component of the entities spawned from a tilemap object, see `TileObjectRegistry`
+#[derive(Debug, Clone)]
+pub struct MapObject {
+    name: Option<String>,
+    class: TileObjectClass,
+    properties: HashMap<String, String>,
+}
+
+impl MapObject {
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    pub fn class(&self) -> &TileObjectClass {
+        &self.class
+    }
+
+    /// Custom property `key` of the object
+    pub fn property(&self, key: &str) -> Option<&String> {
+        self.properties.get(key)
+    }
+
+    pub fn properties(&self) -> &HashMap<String, String> {
+        &self.properties
+    }
+}
+
+/// Spawns the entities of the `objects` of the tilemap `tilemap` whose class is registered in the `TileObjectRegistry`
+pub(crate) fn spawn_tile_objects(data: &mut GameData, tilemap: Entity, objects: &[TileObject]) -> Vec<Entity> {
+    let mut spawned = Vec::new();
+    for object in objects.iter() {
+        let class = object.get_class().name();
+        let (spawner, collider) = {
+            let Some(registry) = data.get_resource::<TileObjectRegistry>() else { return spawned };
+            let Some(spawner) = registry.spawners.get(class).cloned() else {
+                debug!("No spawner registered for the tilemap objects of class '{}'", class);
+                continue;
+            };
+            (spawner, registry.colliders.get(class).cloned())
+        };
+
+        let transform = Transform::from_xyz(object.get_position().x(), object.get_position().y(), object.get_position().z());
+        let entity = match spawner {
+            Spawner::Function(spawner) => spawner(data, object, transform),
+            Spawner::Prefab(prefab) => data.spawn_prefab(&prefab, transform),
+        };
+
+        let has_parent = data.entry::<&Parent>(entity).is_ok_and(|mut e| e.get().is_some());
+        if !has_parent {
+            let _r = data.add_components(entity, (Parent::new(tilemap),));
+        }
+        let has_collider = data.entry::<&Collider>(entity).is_ok_and(|mut e| e.get().is_some());
+        if let (false, Some((mask, filters)), Some(collider_type)) = (has_collider, collider, collider_type(object)) {
+            let _r = data.add_components(entity, (Collider::new(mask, filters, collider_type),));
+        }
+        let map_object = MapObject {
+            name: object.get_name().cloned(),
+            class: object.get_class().clone(),
+            properties: object.properties.clone().unwrap_or_default(),
+        };
+        let _r = data.add_components(entity, (map_object,));
+        spawned.push(entity);
+    }
+    spawned
+}
+
+fn collider_type(object: &TileObject) -> Option<ColliderType> {
+    match object.shape_type {
+        TileObjectShapeType::Rectangle if object.is_rect() => {
+            Some(ColliderType::RectangleCollider(object.get_rect().width() as usize, object.get_rect().height() as usize))
+        }
+        TileObjectShapeType::Polygon if object.has_polygon() => Some(ColliderType::PolygonCollider(object.get_polygon().clone())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::maths::coordinates::Coordinates;
+    use crate::graphics::components::tiles::atlas::data::TileRectangle;
+
+    fn object(class: TileObjectClass, x: f32, y: f32) -> TileObject {
+        TileObject {
+            name: Some("chest".to_string()),
+            class,
+            shape_type: TileObjectShapeType::Rectangle,
+            position: Coordinates::new(x, y),
+            polygon: None,
+            rectangle: Some(TileRectangle { width: 16., height: 8. }),
+            properties: Some(HashMap::from([("gold".to_string(), "12".to_string())])),
+        }
+    }
+
+    #[test]
+    fn registered_objects_are_spawned_as_children_of_the_tilemap() {
+        let mut data = GameData::default();
+        let tilemap = data.push((Transform::from_xy(100., 100.),));
+        let mut registry = TileObjectRegistry::default();
+        registry.register_spawner("Item", |data, _, transform| data.push((transform,)));
+        registry.register_collider("Item", ColliderMask::Item, vec![ColliderMask::Character]);
+        data.insert_resource(registry);
+
+        let objects = vec![object(TileObjectClass::Item, 32., 48.), object(TileObjectClass::Custom("Unknown".to_string()), 0., 0.)];
+        let spawned = spawn_tile_objects(&mut data, tilemap, &objects);
+
+        assert_eq!(1, spawned.len());
+        let mut entry = data.entry::<(&Transform, &Parent, &Collider, &MapObject)>(spawned[0]).unwrap();
+        let (transform, parent, collider, map_object) = entry.get().unwrap();
+        assert_eq!((32., 48.), (transform.translation().x(), transform.translation().y()));
+        assert_eq!(tilemap, parent.entity());
+        assert!(matches!(collider.collider_type(), ColliderType::RectangleCollider(16, 8)));
+        assert_eq!(Some(&"12".to_string()), map_object.property("gold"));
+        assert_eq!(Some(&"chest".to_string()), map_object.name());
+    }
+}
@@ -6,6 +6,7 @@ use std::collections::LinkedList;
 #[derive(Default)]
 pub(crate) struct Scheduler {
     systems: LinkedList<(Option<fn(&GameState) -> bool>, fn(&mut GameData))>,
+    fixed_systems: LinkedList<fn(&mut GameData)>,
 }
 
 impl Scheduler {
@@ -18,6 +19,10 @@ impl Scheduler {
                                       pause_condition: fn(&GameState) -> bool) {
         self.systems.push_back((Some(pause_condition), system));
     }
+
+    pub(crate) fn add_fixed_system(&mut self, system: fn(&mut GameData)) {
+        self.fixed_systems.push_back(system);
+    }
     #[profile("scheduler::execute")]
     pub(crate) fn execute(&mut self, data: &mut GameData) {
         let systems_to_execute : LinkedList<&(Option<fn(&GameState) -> bool>, fn(&mut GameData))> = {
@@ -26,4 +31,9 @@ impl Scheduler {
         };
         systems_to_execute.iter().for_each(|s| s.1(data));
     }
+
+    #[profile("scheduler::execute_fixed")]
+    pub(crate) fn execute_fixed(&mut self, data: &mut GameData) {
+        self.fixed_systems.iter().for_each(|s| s(data));
+    }
 }
@@ -65,6 +65,8 @@ impl ScionRunner {
 
             if frame_limiter.is_fixed_update() {
                 self.layer_machine.apply_scene_action(SceneAction::FixedUpdate, &mut self.game_data);
+                self.scheduler.execute_fixed(&mut self.game_data);
+                self.game_data.apply_commands();
                 frame_limiter.fixed_tick();
             }
 
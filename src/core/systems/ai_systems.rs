@@ -0,0 +1,202 @@
+use hecs::Entity;
+use profiling_macros::profile;
+
+use crate::core::components::ai::behavior_tree::BehaviorTree;
+use crate::core::components::ai::state_machine::{next_state, StateMachine};
+use crate::core::resources::time::Time;
+use crate::core::world::{GameData, World};
+
+/// System responsible to tick the `StateMachine<S>` components, then call the callbacks of their states
+#[profile("system::state_machine_system")]
+pub(crate) fn state_machine_system<S: PartialEq + Send + Sync + 'static>(data: &mut GameData) {
+    let delta = data.get_resource::<Time>().map_or(0., |t| t.delta_duration().as_secs_f32());
+    let entities: Vec<Entity> = data.query::<&StateMachine<S>>().iter().map(|(e, _)| e).collect();
+    for entity in entities {
+        let Ok((current, requested, transitions)) = data.entry_mut::<&mut StateMachine<S>>(entity).map(|machine| machine.detach()) else {
+            continue;
+        };
+        let next = requested.or_else(|| next_state(&transitions, current, entity, data));
+        let Ok(callbacks) = data.entry_mut::<&mut StateMachine<S>>(entity).map(|machine| machine.attach(transitions, next, delta)) else {
+            continue;
+        };
+        for callback in callbacks {
+            callback(entity, data);
+        }
+    }
+}
+
+/// System responsible to tick the `BehaviorTree` components from their root
+#[profile("system::behavior_tree_system")]
+pub(crate) fn behavior_tree_system(data: &mut GameData) {
+    let entities: Vec<Entity> = data.query::<&BehaviorTree>().iter().map(|(e, _)| e).collect();
+    for entity in entities {
+        let Some((mut root, mut blackboard)) = data.entry_mut::<&mut BehaviorTree>(entity).ok().and_then(|tree| tree.detach()) else {
+            continue;
+        };
+        let status = root.tick(entity, data, &mut blackboard);
+        if let Ok(tree) = data.entry_mut::<&mut BehaviorTree>(entity) {
+            tree.attach(root, blackboard, status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::core::components::ai::behavior_tree::{BehaviorNode, BehaviorStatus};
+    use crate::core::resources::behavior_registry::BehaviorRegistry;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Guard {
+        Patrol,
+        Chase,
+        Dead,
+    }
+
+    struct Health(i32);
+    struct PlayerDistance(f32);
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    fn log(data: &mut GameData, message: &str) {
+        data.get_resource_mut::<Log>().unwrap().0.push(message.to_string());
+    }
+
+    fn logged(data: &GameData) -> Vec<String> {
+        data.get_resource::<Log>().unwrap().0.clone()
+    }
+
+    #[test]
+    fn state_machine_transitions() {
+        let mut data = GameData::default();
+        data.insert_resource(Log::default());
+        let machine = StateMachine::new(Guard::Patrol)
+            .with_on_enter(Guard::Patrol, |_, data| log(data, "enter patrol"))
+            .with_on_exit(Guard::Patrol, |_, data| log(data, "exit patrol"))
+            .with_on_enter(Guard::Chase, |_, data| log(data, "enter chase"))
+            .with_on_update(Guard::Chase, |_, data| log(data, "chase"))
+            .with_transition(Guard::Patrol, Guard::Chase, |e, data| {
+                data.entry::<&PlayerDistance>(e).unwrap().get().unwrap().0 < 10.
+            })
+            .with_transition(Guard::Chase, Guard::Patrol, |e, data| {
+                data.entry::<&PlayerDistance>(e).unwrap().get().unwrap().0 > 50.
+            })
+            .with_any_transition(Guard::Dead, |e, data| data.entry::<&Health>(e).unwrap().get().unwrap().0 <= 0);
+        let guard = data.push((machine, PlayerDistance(100.), Health(3)));
+
+        state_machine_system::<Guard>(&mut data);
+        assert_eq!(vec!["enter patrol"], logged(&data));
+
+        data.entry_mut::<&mut PlayerDistance>(guard).unwrap().0 = 5.;
+        state_machine_system::<Guard>(&mut data);
+        state_machine_system::<Guard>(&mut data);
+        assert_eq!(vec!["enter patrol", "exit patrol", "enter chase", "chase", "chase"], logged(&data));
+        assert!(data.entry::<&StateMachine<Guard>>(guard).unwrap().get().unwrap().is_in(&Guard::Chase));
+
+        data.entry_mut::<&mut Health>(guard).unwrap().0 = 0;
+        state_machine_system::<Guard>(&mut data);
+        assert_eq!(&Guard::Dead, data.entry::<&StateMachine<Guard>>(guard).unwrap().get().unwrap().current_state());
+    }
+
+    #[test]
+    fn state_machine_guards_read_their_own_machine() {
+        let mut data = GameData::default();
+        data.insert_resource(Log::default());
+        let machine = StateMachine::new(Guard::Patrol)
+            .with_on_enter(Guard::Chase, |_, data| log(data, "enter chase"))
+            .with_any_transition(Guard::Chase, |e, data| data.entry::<&StateMachine<Guard>>(e).unwrap().get().unwrap().is_in(&Guard::Patrol));
+        let guard = data.push((machine,));
+
+        state_machine_system::<Guard>(&mut data);
+        assert_eq!(vec!["enter chase"], logged(&data));
+        assert!(data.entry::<&StateMachine<Guard>>(guard).unwrap().get().unwrap().is_in(&Guard::Chase));
+    }
+
+    #[test]
+    fn state_machine_from_json() {
+        let mut data = GameData::default();
+        data.insert_resource(Log::default());
+        let mut registry = BehaviorRegistry::default();
+        registry.register_callback("shout", |_, data| log(data, "shout"));
+        registry.register_guard("always", |_, _| true);
+
+        let json = r#"{
+            "initial": "Patrol",
+            "states": [{ "state": "Chase", "on_enter": "shout" }],
+            "transitions": [{ "from": "Patrol", "to": "Chase", "guard": "always" }]
+        }"#;
+        let guard = data.push((StateMachine::<Guard>::from_json(json, &registry).unwrap(),));
+        state_machine_system::<Guard>(&mut data);
+        assert_eq!(vec!["shout"], logged(&data));
+        assert!(data.entry::<&StateMachine<Guard>>(guard).unwrap().get().unwrap().is_in(&Guard::Chase));
+
+        let unknown = r#"{ "initial": "Patrol", "transitions": [{ "to": "Dead", "guard": "missing" }] }"#;
+        assert!(StateMachine::<Guard>::from_json(unknown, &registry).is_err());
+    }
+
+    #[test]
+    fn behavior_tree_sequence_and_selector() {
+        let mut data = GameData::default();
+        data.insert_resource(Log::default());
+        let tree = BehaviorTree::new(BehaviorNode::selector(vec![
+            BehaviorNode::sequence(vec![
+                BehaviorNode::condition(|_, _, blackboard| blackboard.get::<bool>("enemy_visible").unwrap_or(false)),
+                BehaviorNode::action(|_, data, _| {
+                    log(data, "attack");
+                    BehaviorStatus::Success
+                }),
+            ]),
+            BehaviorNode::repeat(
+                2,
+                BehaviorNode::action(|_, data, blackboard| {
+                    let steps = blackboard.get::<u32>("steps").unwrap_or(0) + 1;
+                    blackboard.set("steps", steps);
+                    log(data, "wander");
+                    BehaviorStatus::Success
+                }),
+            ),
+        ]));
+        let enemy = data.push((tree,));
+
+        behavior_tree_system(&mut data);
+        assert_eq!(Some(BehaviorStatus::Running), data.entry::<&BehaviorTree>(enemy).unwrap().get().unwrap().last_status());
+        behavior_tree_system(&mut data);
+        assert_eq!(Some(BehaviorStatus::Success), data.entry::<&BehaviorTree>(enemy).unwrap().get().unwrap().last_status());
+        assert_eq!(Some(2), data.entry::<&BehaviorTree>(enemy).unwrap().get().unwrap().blackboard().get::<u32>("steps"));
+
+        data.entry_mut::<&mut BehaviorTree>(enemy).unwrap().blackboard_mut().set("enemy_visible", true);
+        behavior_tree_system(&mut data);
+        assert_eq!(vec!["wander", "wander", "attack"], logged(&data));
+    }
+
+    #[test]
+    fn behavior_tree_from_json() {
+        let mut data = GameData::default();
+        let mut registry = BehaviorRegistry::default();
+        registry.register_condition("is_hungry", |_, _, blackboard| blackboard.get::<u32>("food").is_some_and(|f| f < 5));
+        registry.register_action("eat", |_, _, blackboard| {
+            blackboard.set("food", blackboard.get::<u32>("food").unwrap_or(0) + 1);
+            BehaviorStatus::Success
+        });
+
+        let json = r#"{
+            "blackboard": { "food": 3 },
+            "root": { "type": "Repeat", "child": { "type": "Sequence", "children": [
+                { "type": "Condition", "name": "is_hungry" },
+                { "type": "Action", "name": "eat" }
+            ]}}
+        }"#;
+        let animal = data.push((BehaviorTree::from_json(json, &registry).unwrap(),));
+        for _ in 0..5 {
+            behavior_tree_system(&mut data);
+        }
+        let mut tree = data.entry::<&BehaviorTree>(animal).unwrap();
+        let tree = tree.get().unwrap();
+        assert_eq!(Some(5), tree.blackboard().get::<u32>("food"));
+        assert_eq!(Some(BehaviorStatus::Failure), tree.last_status());
+
+        assert!(BehaviorTree::from_json(r#"{ "root": { "type": "Action", "name": "fly" } }"#, &registry).is_err());
+    }
+}
@@ -1,13 +1,24 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::core::components::maths::collider::{Collider, ColliderDebug, ColliderMask, Collision};
+use crate::core::components::maths::collider::{
+    sweep_shape, Aabb, CONTACT_PENETRATION, Collider, ColliderShape, ColliderType, CollisionArea, ColliderDebug, ColliderMask, Collision, CollisionEvent, CollisionEventType,
+    COLLISIONS_TOPIC,
+};
 use crate::core::components::maths::hierarchy::Parent;
 use crate::core::components::maths::transform::Transform;
 use hecs::{Component, Entity};
 use profiling_macros::profile;
+use crate::core::resources::broadphase::{Broadphase, CachedCollider};
+use crate::core::resources::collision_layers::CollisionLayers;
+use crate::core::resources::events::Events;
+use crate::core::resources::physics_query::PhysicsQuery;
 use crate::core::resources::global_storage::GlobalStorage;
 use crate::core::resources::inputs::types::{Input, KeyCode};
 use crate::core::world::{GameData, World};
+use crate::core::components::maths::coordinates::Coordinates;
+use crate::utils::maths::Vector;
+use crate::graphics::components::tiles::tilemap::Tilemap;
+use crate::utils::maths::Position;
 use crate::graphics::components::{color::Color, material::Material, shapes::polygon::Polygon};
 use crate::graphics::rendering::Renderable2D;
 
@@ -18,61 +29,277 @@ pub(crate) fn collider_cleaner_system(data: &mut GameData) {
     }
 }
 
-/// System responsible to compute collision between colliders, following the mask filters
+/// Resolves the collision layers of the colliders added since the last call
+pub(crate) fn resolve_collider_layers(data: &mut GameData) {
+    let (world, resources) = data.split();
+    let mut layers = resources.get_resource_mut::<CollisionLayers>().expect("Missing CollisionLayers resource");
+    // Numeric layers are reserved first, so that the custom names used by the same colliders skip them
+    for (_, collider) in world.query::<&Collider>().iter().filter(|(_, c)| !c.layers_resolved()) {
+        for mask in std::iter::once(collider.mask()).chain(collider.filters()) {
+            if let ColliderMask::Layer(_) = mask {
+                layers.layer_or_register(mask);
+            }
+        }
+    }
+    for (_, collider) in world.query_mut::<&mut Collider>() {
+        if !collider.layers_resolved() {
+            collider.resolve_layers(&mut layers);
+        }
+    }
+}
+
+/// System responsible to compute collision between colliders, following the mask filters.
+/// Candidates are found using the `Broadphase` grids, before testing their exact shapes.
 #[profile("system::compute_collisions_system")]
 pub(crate) fn compute_collisions_system(data: &mut GameData) {
+    resolve_collider_layers(data);
     let mut res: HashMap<Entity, Vec<Collision>> = HashMap::default();
+    let mut clamped: Vec<(Entity, Vector)> = Vec::new();
 
     {
-        let colliders: Vec<(Entity, Transform, Collider)> = {
-            let mut res = Vec::new();
-            for (e, (t, c)) in data.query::<(&Transform, &Collider)>().iter() {
-                res.push((e, *t, c.clone()));
+        let (world, resources) = data.split();
+        let mut broadphase = resources.get_resource_mut::<Broadphase>().expect("Missing Broadphase resource");
+        let layers = resources.get_resource::<CollisionLayers>().expect("Missing CollisionLayers resource");
+
+        let mut static_entities = HashSet::new();
+        let mut dynamic_colliders: HashMap<Entity, (ColliderShape, Aabb)> = HashMap::default();
+        for (e, (t, c)) in world.query::<(&Transform, &Collider)>().iter() {
+            if c.is_static() {
+                static_entities.insert(e);
+                let key = CachedCollider::key(t);
+                if !broadphase.static_cache.get(&e).is_some_and(|cached| cached.key == key) {
+                    let shape = c.collider_shape(t);
+                    let aabb = shape.aabb();
+                    broadphase.static_cache.insert(e, CachedCollider { key, shape, aabb });
+                    broadphase.static_dirty = true;
+                }
+            } else {
+                let shape = c.collider_shape(t);
+                let aabb = shape.aabb();
+                dynamic_colliders.insert(e, (shape, aabb));
             }
-            res
+        }
+
+        let cached_count = broadphase.static_cache.len();
+        broadphase.static_cache.retain(|e, _| static_entities.contains(e));
+        if broadphase.static_dirty || cached_count != broadphase.static_cache.len() {
+            let Broadphase { static_grid, static_cache, .. } = &mut *broadphase;
+            static_grid.clear();
+            static_cache.iter().for_each(|(e, cached)| static_grid.insert(*e, &cached.aabb));
+            broadphase.static_dirty = false;
+        }
+
+        broadphase.dynamic_grid.clear();
+        dynamic_colliders.iter().for_each(|(e, (_, aabb))| broadphase.dynamic_grid.insert(*e, aabb));
+
+        let shape = |e: &Entity| -> Option<(&ColliderShape, &Aabb)> {
+            dynamic_colliders
+                .get(e)
+                .map(|(p, a)| (p, a))
+                .or_else(|| broadphase.static_cache.get(e).map(|c| (&c.shape, &c.aabb)))
         };
 
-        let mut colliders_by_mask: HashMap<
-            ColliderMask,
-            Vec<(Entity, &Transform, &Collider)>,
-        > = HashMap::default();
+        let mut candidates = HashSet::new();
+        for (entity, collider) in world.query::<&Collider>().iter() {
+            let collision_bits = layers.collision_bits(collider);
+            if collision_bits == 0 {
+                continue;
+            }
+            // Colliders without a transform have no shape
+            let Some((self_shape, aabb)) = shape(&entity) else { continue };
+            candidates.clear();
+            broadphase.dynamic_grid.query(aabb, &mut candidates);
+            broadphase.static_grid.query(aabb, &mut candidates);
+
+            for other in candidates.iter().filter(|other| **other != entity) {
+                let mut other_entry = world.entry::<(&Transform, &Collider)>(*other).expect("Missing collider entity");
+                let (other_transform, other_collider) = other_entry.get().expect("Missing collider components");
+                if collision_bits & other_collider.layer_bits() == 0 {
+                    continue;
+                }
+                let Some((other_shape, other_aabb)) = shape(other) else { continue };
+                if !aabb.intersects(other_aabb) {
+                    continue;
+                }
+                if let Some(collision_area) = self_shape.intersection(other_shape) {
+                    res.entry(entity).or_default().push(Collision {
+                        mask: other_collider.mask().clone(),
+                        entity,
+                        other_entity: *other,
+                        coordinates: *other_transform.global_translation(),
+                        collision_area,
+                        time_of_impact: None,
+                    });
+                }
+            }
+        }
 
-        colliders.iter().for_each(|(e, t, c)| {
-            colliders_by_mask.entry(c.mask().clone()).or_default().push((*e, t, c))
-        });
+        // Continuous detection, checking the movement of the fast colliders since the previous frame
+        for (entity, (transform, collider)) in world.query::<(&Transform, &Collider)>().iter() {
+            let collision_bits = layers.collision_bits(collider);
+            let Some(previous) = collider.previous_translation().filter(|_| collider.is_continuous() && collision_bits != 0)
+            else {
+                continue;
+            };
+            let current = transform.global_translation();
+            let (dx, dy) = (current.x - previous.x, current.y - previous.y);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length <= f32::EPSILON {
+                continue;
+            }
+            let direction = Vector::new(dx / length, dy / length);
+            let Some((current_shape, _)) = shape(&entity) else { continue };
+            let mut start_shape = current_shape.clone();
+            start_shape.translate(-dx, -dy);
+            let swept_aabb = start_shape.aabb().swept(&direction, length);
+
+            candidates.clear();
+            broadphase.dynamic_grid.query(&swept_aabb, &mut candidates);
+            broadphase.static_grid.query(&swept_aabb, &mut candidates);
+            let already_colliding: HashSet<Entity> = res.get(&entity).map(|c| c.iter().map(|c| c.other_entity).collect()).unwrap_or_default();
+            let mut impacts: Vec<Collision> = candidates
+                .iter()
+                .filter(|other| **other != entity && !already_colliding.contains(other))
+                .filter_map(|other| {
+                    let mut other_entry = world.entry::<(&Transform, &Collider)>(*other).expect("Missing collider entity");
+                    let (other_transform, other_collider) = other_entry.get().expect("Missing collider components");
+                    if collision_bits & other_collider.layer_bits() == 0 {
+                        return None;
+                    }
+                    let (other_shape, _) = shape(other)?;
+                    let (distance, normal) = sweep_shape(&start_shape, &direction, length, other_shape)?;
+                    // The area is computed just after the contact, where both shapes overlap
+                    let mut contact_shape = start_shape.clone();
+                    let contact_distance = (distance + CONTACT_PENETRATION).min(length);
+                    contact_shape.translate(direction.x * contact_distance, direction.y * contact_distance);
+                    let collision_area = contact_shape.intersection(other_shape).unwrap_or_else(|| CollisionArea {
+                        coordinates: vec![Coordinates::new(
+                            previous.x + direction.x * distance - normal.x,
+                            previous.y + direction.y * distance - normal.y,
+                        )],
+                    });
+                    Some(Collision {
+                        mask: other_collider.mask().clone(),
+                        entity,
+                        other_entity: *other,
+                        coordinates: *other_transform.global_translation(),
+                        collision_area,
+                        time_of_impact: Some(distance / length),
+                    })
+                })
+                .collect();
+            if impacts.is_empty() {
+                continue;
+            }
+            impacts.sort_by(|a, b| a.time_of_impact.unwrap_or(0.).total_cmp(&b.time_of_impact.unwrap_or(0.)));
+            if collider.clamps_on_impact() {
+                let time_of_impact = impacts[0].time_of_impact.expect("Continuous impacts have a time of impact");
+                impacts.truncate(1);
+                let back = length * (1. - time_of_impact);
+                clamped.push((entity, Vector::new(-direction.x * back, -direction.y * back)));
+            }
+            res.entry(entity).or_default().append(&mut impacts);
+        }
 
-        let mut cpt = 0;
-        colliders.iter().for_each(|(entity, transform, collider)| {
-            collider.filters().iter().filter(|mask| colliders_by_mask.contains_key(mask)).for_each(
-                |mask| {
-                    colliders_by_mask
-                        .get(mask)
-                        .expect("Impossible to find a collider's entry")
-                        .iter()
-                        .map(|(_e, t, c)| {
-                            cpt += 1;
-                            (_e, t, c, collider.collides_with(transform, c, t))
-                        })
-                        .filter(|(_e, _t, _c, collision_area)| collision_area.is_some())
-                        .for_each(|(_e, t, c, collision_area)| {
-                            res.entry(*entity).or_default().push(Collision {
-                                mask: c.mask().clone(),
-                                entity: *entity,
-                                coordinates: *t.global_translation(),
-                                collision_area: collision_area.expect("Filtered Option is still KO"),
-                            });
-                        })
-                },
+        if let Some(mut physics_query) = resources.get_resource_mut::<PhysicsQuery>() {
+            physics_query.refresh(
+                world.query::<&Collider>().iter().filter_map(|(e, c)| shape(&e).map(|(shape, aabb)| (e, c, shape.clone(), *aabb))),
+                &layers,
             );
-        });
+        }
     }
 
-
     res.drain().for_each(|(e, mut collisions)| {
         data.entry_mut::<&mut Collider>(e)
             .expect("Collisions on unreachable collider")
             .add_collisions(&mut collisions);
     });
+
+    for (_, (transform, collider)) in data.query_mut::<(&Transform, &mut Collider)>() {
+        if collider.is_continuous() {
+            collider.set_previous_translation(*transform.global_translation());
+        }
+    }
+    for (entity, back) in clamped {
+        data.commands().transform_commands.append_vector(entity, back);
+        let collider = data.entry_mut::<&mut Collider>(entity).expect("Missing clamped collider");
+        let previous = collider.previous_translation().expect("Clamped colliders have a previous translation");
+        collider.set_previous_translation(Coordinates::new(previous.x + back.x, previous.y + back.y));
+    }
+
+    let mut collision_events = Vec::new();
+    for (e, collider) in data.query_mut::<&mut Collider>() {
+        collider.compute_ended_collisions();
+        let mask = collider.mask();
+        let mut push_event = |event_type, other: &Entity, other_mask: &ColliderMask| {
+            collision_events.push(CollisionEvent::new(event_type, e, mask.clone(), *other, other_mask.clone()))
+        };
+        collider.collision_started().iter().for_each(|c| push_event(CollisionEventType::Started, &c.other_entity, &c.mask));
+        if collider.publishes_ongoing_events() {
+            collider.collision_ongoing().iter().for_each(|c| push_event(CollisionEventType::Ongoing, &c.other_entity, &c.mask));
+        }
+        collider.collision_ended().iter().for_each(|(other, mask)| push_event(CollisionEventType::Ended, other, mask));
+    }
+    if let Some(mut events) = data.get_resource_mut::<Events>() {
+        collision_events.drain(..).for_each(|event| {
+            let _r = events.publish(COLLISIONS_TOPIC, event);
+        });
+    }
+}
+
+/// System responsible to read the solid cells of every `TilemapGridCollider` from their tilemap pathing values
+#[profile("system::tilemap_grid_collider_system")]
+pub(crate) fn tilemap_grid_collider_system(data: &mut GameData) {
+    let to_refresh: Vec<(Entity, Vec<String>, usize, usize, usize)> = data
+        .query::<(&Tilemap, &Collider)>()
+        .iter()
+        .filter(|(_, (_, c))| c.is_grid_dirty())
+        .filter_map(|(e, (tilemap, c))| match c.collider_type() {
+            ColliderType::TilemapGridCollider { solid_pathings, .. } => {
+                Some((e, solid_pathings.clone(), tilemap.width(), tilemap.height(), tilemap.depth()))
+            }
+            _ => None,
+        })
+        .collect();
+    if to_refresh.is_empty() {
+        return;
+    }
+
+    let (world, resources) = data.split();
+    let asset_manager = resources.assets();
+    let refreshed: HashSet<Entity> = to_refresh.iter().map(|(e, ..)| *e).collect();
+    for (entity, solid_pathings, width, height, depth) in to_refresh {
+        let mut solid = Vec::new();
+        for x in 0..width {
+            for y in 0..height {
+                let is_solid = (0..depth).any(|z| {
+                    Tilemap::retrieve_pathing(world, entity, &Position::new(x, y, z), &asset_manager)
+                        .is_some_and(|pathing| solid_pathings.contains(&pathing))
+                });
+                if is_solid {
+                    solid.push((x, y));
+                }
+            }
+        }
+        world
+            .entry_mut::<&mut Collider>(entity)
+            .expect("Missing grid collider")
+            .set_grid_cells(width, height, &solid);
+        if let Some(mut broadphase) = resources.get_resource_mut::<Broadphase>() {
+            broadphase.invalidate_static_collider(entity);
+        }
+    }
+
+    // Debug lines of the refreshed grids will be created again by the debug system
+    let outdated_debug_lines: Vec<Entity> = world
+        .query::<(&ColliderDebug, &Parent)>()
+        .iter()
+        .filter(|(_, (_, parent))| refreshed.contains(&parent.entity()))
+        .map(|(e, _)| e)
+        .collect();
+    outdated_debug_lines.into_iter().for_each(|e| {
+        let _r = world.remove(e);
+    });
 }
 
 /// System responsible to add a `ColliderDebug` component to each colliders that are in debug mode
@@ -93,17 +320,23 @@ pub(crate) fn debug_colliders_system(data: &mut GameData) {
                 ColliderMask::Landscape => Color::new_rgb(255, 255, 0),
                 ColliderMask::Custom(_) => Color::new_rgb(0, 0, 255),
                 ColliderMask::Item => Color::new_rgb(0, 255, 255),
+                ColliderMask::Layer(_) => Color::new_rgb(255, 0, 255),
             };
             let offset = collider.offset();
-            let polygon_collider =
-                Polygon::new(collider.collider_coordinates(0.,0.)).pivot(collider.get_pivot());
-            debug_lines_to_add.push((
-                Parent::new(entity),
-                ColliderDebug,
-                Transform::from_xyz(offset.x(), offset.y(), 0),
-                polygon_collider,
-                Material::Diffuse(color),
-            ));
+            let outlines = if let ColliderType::TilemapGridCollider { .. } = collider.collider_type() {
+                collider.grid_rectangles(0., 0.)
+            } else {
+                vec![collider.collider_coordinates(0., 0.)]
+            };
+            outlines.into_iter().for_each(|outline| {
+                debug_lines_to_add.push((
+                    Parent::new(entity),
+                    ColliderDebug,
+                    Transform::from_xyz(offset.x(), offset.y(), 0),
+                    Polygon::new(outline).pivot(collider.get_pivot()),
+                    Material::Diffuse(color.clone()),
+                ));
+            });
         } else if !collider.debug_lines() && !global_debug_activated && collider_debug.0.contains(&entity) {
             debug_lines_to_remove.push(entity);
         }
@@ -113,7 +346,9 @@ pub(crate) fn debug_colliders_system(data: &mut GameData) {
         data.push(components);
     });
     debug_lines_to_remove.drain(0..).for_each(|e| {
-        let _r = data.remove(collider_debug.1.remove(&e).expect(""));
+        collider_debug.1.remove(&e).expect("").into_iter().for_each(|debug_line| {
+            let _r = data.remove(debug_line);
+        });
     });
 }
 
@@ -139,16 +374,25 @@ fn handle_global_debug_colliders(game_data: &mut GameData) -> bool {
     current_val
 }
 
-fn fetch_collider_debug_entities(data: &mut GameData) -> (HashSet<Entity>, HashMap<Entity, Entity>) {
+fn fetch_collider_debug_entities(data: &mut GameData) -> (HashSet<Entity>, HashMap<Entity, Vec<Entity>>) {
     let mut parents = HashSet::new();
-    let mut debug_line: HashMap<Entity, Entity> = HashMap::new();
+    let mut debug_line: HashMap<Entity, Vec<Entity>> = HashMap::new();
     for (e, (_, parent)) in data.query::<(&ColliderDebug, &Parent)>().iter() {
         parents.insert(parent.entity());
-        debug_line.insert(parent.entity(), e);
+        debug_line.entry(parent.entity()).or_default().push(e);
     }
     (parents, debug_line)
 }
 
+/// Game data holding the resources used by the collision systems, as inserted by the `InternalPackage`
+#[cfg(test)]
+pub(crate) fn collision_test_data() -> GameData {
+    let mut data = GameData::default();
+    data.insert_resource(Broadphase::default());
+    data.insert_resource(CollisionLayers::default());
+    data
+}
+
 #[cfg(test)]
 mod tests {
     use crate::core::components::maths::collider::CollisionArea;
@@ -156,14 +400,34 @@ mod tests {
         collider::{Collider, ColliderMask, ColliderType, Collision},
         transform::Transform,
     };
+    use crate::core::resources::asset_manager::{AssetManager, AssetRef};
     use crate::core::resources::inputs::inputs_controller::InputsController;
     use crate::core::world::GameData;
+    use crate::graphics::components::tiles::tilemap::{TileInfos, TilemapInfo, TilemapType};
+    use crate::utils::maths::Dimensions;
+    use std::marker::PhantomData;
 
     use super::*;
 
+    #[test]
+    fn numeric_layers_are_reserved_before_custom_names_test() {
+        let mut world = collision_test_data();
+        let enemy = world.push((
+            Transform::default(),
+            Collider::new(ColliderMask::Custom("Enemy".to_string()), vec![], ColliderType::SquareCollider(5)),
+        ));
+        world.push((Transform::default(), Collider::new(ColliderMask::Layer(6), vec![], ColliderType::SquareCollider(5))));
+
+        resolve_collider_layers(&mut world);
+
+        assert_eq!(Some(7), world.entry::<&Collider>(enemy).unwrap().get().unwrap().layer());
+        let layers = world.get_resource::<CollisionLayers>().unwrap();
+        assert_eq!(Some(6), layers.layer(&ColliderMask::Layer(6)));
+    }
+
     #[test]
     fn clear_collision_system_test() {
-        let mut world = GameData::default();
+        let mut world = collision_test_data();
 
         let mut t = Transform::default();
         t.append_x(1.0);
@@ -177,8 +441,10 @@ mod tests {
         entry.add_collisions(&mut vec![Collision {
             mask: ColliderMask::Character,
             entity: e,
+            other_entity: e,
             coordinates: Default::default(),
             collision_area: CollisionArea { coordinates: vec![]},
+            time_of_impact: None,
         }]);
         assert_eq!(1, entry.collisions().len());
 
@@ -189,7 +455,7 @@ mod tests {
 
     #[test]
     fn compute_collision_system_test() {
-        let mut world = GameData::default();
+        let mut world = collision_test_data();
 
         let mut t = Transform::default();
         t.append_x(1.0);
@@ -211,15 +477,224 @@ mod tests {
             ),
         ));
 
+        // A collider without transform has no shape, it is ignored
+        world.push((Collider::new(ColliderMask::Bullet, vec![ColliderMask::Landscape], ColliderType::SquareCollider(5)),));
+
         compute_collisions_system(&mut world);
 
         assert_eq!(0, world.entry::<&Collider>(e).unwrap().get().unwrap().collisions().len());
-        assert_eq!(1, world.entry::<&Collider>(e2).unwrap().get().unwrap().collisions().len());
+        let mut entry = world.entry::<&Collider>(e2).unwrap();
+        let collisions = entry.get().unwrap().collisions();
+        assert_eq!(1, collisions.len());
+        assert_eq!(e2, *collisions[0].entity());
+        assert_eq!(e, *collisions[0].other_entity());
+        assert_eq!(ColliderMask::Bullet, *collisions[0].mask());
+    }
+
+    #[test]
+    fn compute_collision_system_static_test() {
+        let mut world = collision_test_data();
+        world.insert_resource(Broadphase::new(10.));
+
+        let wall = world.push((
+            Transform::from_xy(0., 0.),
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(100, 5)).with_static(),
+        ));
+        let character = world.push((
+            Transform::from_xy(80., 2.),
+            Collider::new(ColliderMask::Character, vec![ColliderMask::Landscape], ColliderType::SquareCollider(5)),
+        ));
+        let _far = world.push((
+            Transform::from_xy(500., 500.),
+            Collider::new(ColliderMask::Character, vec![ColliderMask::Landscape], ColliderType::SquareCollider(5)),
+        ));
+
+        compute_collisions_system(&mut world);
+        {
+            let mut entry = world.entry::<&Collider>(character).unwrap();
+            let collisions = entry.get().unwrap().collisions();
+            assert_eq!(1, collisions.len());
+            assert_eq!(wall, *collisions[0].other_entity());
+        }
+        assert_eq!(1, world.get_resource::<Broadphase>().unwrap().static_colliders());
+
+        let _r = world.remove(wall);
+        collider_cleaner_system(&mut world);
+        compute_collisions_system(&mut world);
+        assert_eq!(0, world.entry::<&Collider>(character).unwrap().get().unwrap().collisions().len());
+        assert_eq!(0, world.get_resource::<Broadphase>().unwrap().static_colliders());
+    }
+
+    #[test]
+    fn collision_started_ongoing_ended_test() {
+        let mut world = collision_test_data();
+        let mut events = Events::default();
+        events.create_topic(COLLISIONS_TOPIC, Default::default()).unwrap();
+        let subscriber = events.subscribe(COLLISIONS_TOPIC, Default::default()).unwrap();
+        world.insert_resource(events);
+
+        let bullet = world.push((
+            Transform::from_xy(0., 0.),
+            Collider::new(ColliderMask::Bullet, vec![], ColliderType::SquareCollider(5)),
+        ));
+        let ship = world.push((
+            Transform::from_xy(2., 2.),
+            Collider::new(ColliderMask::Character, vec![ColliderMask::Bullet], ColliderType::SquareCollider(5))
+                .with_ongoing_events(),
+        ));
+
+        let frame = |world: &mut GameData| {
+            collider_cleaner_system(world);
+            compute_collisions_system(world);
+        };
+
+        frame(&mut world);
+        {
+            let mut entry = world.entry::<&Collider>(ship).unwrap();
+            let collider = entry.get().unwrap();
+            assert_eq!(1, collider.collision_started().len());
+            assert_eq!(0, collider.collision_ongoing().len());
+        }
+
+        frame(&mut world);
+        {
+            let mut entry = world.entry::<&Collider>(ship).unwrap();
+            let collider = entry.get().unwrap();
+            assert_eq!(0, collider.collision_started().len());
+            assert_eq!(1, collider.collision_ongoing().len());
+        }
+
+        world.commands().transform_commands.set_x(bullet, 100.);
+        world.apply_commands();
+        frame(&mut world);
+        {
+            let mut entry = world.entry::<&Collider>(ship).unwrap();
+            let collider = entry.get().unwrap();
+            assert!(!collider.is_colliding());
+            assert_eq!(vec![(bullet, ColliderMask::Bullet)], *collider.collision_ended());
+        }
+
+        let received: Vec<CollisionEvent> =
+            world.get_resource_mut::<Events>().unwrap().poll(&subscriber).unwrap().into_iter().collect();
+        assert_eq!(
+            vec![CollisionEventType::Started, CollisionEventType::Ongoing, CollisionEventType::Ended],
+            received.iter().map(|e| e.event_type()).collect::<Vec<_>>()
+        );
+        assert_eq!(ship, received[2].entity());
+        assert_eq!(bullet, received[2].other_entity());
+        assert_eq!(ColliderMask::Bullet, *received[2].other_mask());
+    }
+
+    #[test]
+    fn collision_started_with_many_resting_contacts_test() {
+        let mut world = collision_test_data();
+        let mut events = Events::default();
+        events.create_topic(COLLISIONS_TOPIC, Default::default()).unwrap();
+        let subscriber = events.subscribe(COLLISIONS_TOPIC, Default::default()).unwrap();
+        world.insert_resource(events);
+
+        world.push((
+            Transform::from_xy(0., 0.),
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(1000, 10)).with_static(),
+        ));
+        for i in 0..60 {
+            world.push((
+                Transform::from_xy(i as f32 * 10., 5.),
+                Collider::new(ColliderMask::Character, vec![ColliderMask::Landscape], ColliderType::SquareCollider(5)),
+            ));
+        }
+        // Events are cleaned up at the end of each frame, dropping the oldest ones above the topic limit
+        let frame = |world: &mut GameData| {
+            collider_cleaner_system(world);
+            compute_collisions_system(world);
+            world.get_resource_mut::<Events>().unwrap().cleanup();
+        };
+        frame(&mut world);
+        while !world.get_resource_mut::<Events>().unwrap().poll::<CollisionEvent>(&subscriber).unwrap().is_empty() {}
+
+        let falling = world.push((
+            Transform::from_xy(500., 8.),
+            Collider::new(ColliderMask::Item, vec![ColliderMask::Landscape], ColliderType::SquareCollider(5)),
+        ));
+        frame(&mut world);
+        frame(&mut world);
+
+        let received: Vec<CollisionEvent> =
+            world.get_resource_mut::<Events>().unwrap().poll(&subscriber).unwrap().into_iter().collect();
+        assert_eq!(1, received.len());
+        assert_eq!(CollisionEventType::Started, received[0].event_type());
+        assert_eq!(falling, received[0].entity());
+    }
+
+    #[test]
+    fn continuous_collision_detection_test() {
+        let mut world = collision_test_data();
+        let bullet = world.push((
+            Transform::from_xy(0., 0.),
+            Collider::new(ColliderMask::Bullet, vec![ColliderMask::Landscape], ColliderType::SquareCollider(4))
+                .with_continuous_clamping(),
+        ));
+        let wall = world.push((
+            Transform::from_xy(50., -20.),
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(2, 40)),
+        ));
+
+        compute_collisions_system(&mut world);
+        world.commands().transform_commands.set_x(bullet, 100.);
+        world.apply_commands();
+        collider_cleaner_system(&mut world);
+        compute_collisions_system(&mut world);
+        world.apply_commands();
+
+        let mut entry = world.entry::<(&Collider, &Transform)>(bullet).unwrap();
+        let (collider, transform) = entry.get().unwrap();
+        assert_eq!(1, collider.collisions().len());
+        assert_eq!(wall, *collider.collisions()[0].other_entity());
+        let time_of_impact = collider.collisions()[0].time_of_impact().unwrap();
+        assert!((time_of_impact - 0.46).abs() < 0.01, "time of impact = {}", time_of_impact);
+        assert!((transform.translation().x() - 46.).abs() < 0.1);
+    }
+
+    #[test]
+    fn tilemap_grid_collider_system_test() {
+        let mut world = collision_test_data();
+        world.insert_resource(AssetManager::default());
+        let tilemap = Tilemap::create(
+            TilemapInfo::new(Dimensions::new(3, 2, 1), Transform::default(), AssetRef(0, PhantomData), TilemapType::Standard),
+            &mut world,
+            |p| {
+                let infos = TileInfos::new(None);
+                if p.y() == 1 { infos.with_pathing("wall".to_string()) } else { infos.with_pathing("floor".to_string()) }
+            },
+        );
+        let _r = world.add_components(
+            tilemap,
+            (Collider::new(
+                ColliderMask::Landscape,
+                vec![],
+                ColliderType::TilemapGridCollider { tile_width: 16, tile_height: 16, solid_pathings: vec!["wall".to_string()] },
+            )
+            .with_static(),),
+        );
+        let character = world.push((
+            Transform::from_xy(20., 2.),
+            Collider::new(ColliderMask::Character, vec![ColliderMask::Landscape], ColliderType::CircleCollider(4)),
+        ));
+
+        tilemap_grid_collider_system(&mut world);
+        compute_collisions_system(&mut world);
+        assert_eq!(0, world.entry::<&Collider>(character).unwrap().get().unwrap().collisions().len());
+
+        world.commands().transform_commands.set_y(character, 14.);
+        world.apply_commands();
+        collider_cleaner_system(&mut world);
+        compute_collisions_system(&mut world);
+        assert_eq!(1, world.entry::<&Collider>(character).unwrap().get().unwrap().collisions().len());
     }
 
     #[test]
     fn debug_colliders_system_test() {
-        let mut world = GameData::default();
+        let mut world = collision_test_data();
         world.insert_resource(InputsController::default());
         world.insert_resource(GlobalStorage::default());
 
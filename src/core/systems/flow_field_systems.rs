@@ -0,0 +1,131 @@
+use profiling_macros::profile;
+
+use crate::core::components::flow_field_follower::FlowFieldFollower;
+use crate::core::components::maths::transform::Transform;
+use crate::core::resources::flow_fields::FlowFields;
+use crate::core::resources::time::Time;
+use crate::core::world::{GameData, World};
+use crate::utils::maths::Vector;
+
+/// System responsible to repair the flow fields whose tilemap has been modified
+#[profile("system::flow_field_system")]
+pub(crate) fn flow_field_system(data: &mut GameData) {
+    let (world, resources) = data.split();
+    if let Some(mut flow_fields) = resources.get_resource_mut::<FlowFields>() {
+        flow_fields.refresh(world, &resources.assets());
+    }
+}
+
+/// System responsible to move the `FlowFieldFollower` entities toward the next cell of their field
+#[profile("system::flow_field_follower_system")]
+pub(crate) fn flow_field_follower_system(data: &mut GameData) {
+    let dt = data.get_resource::<Time>().map_or(0., |t| t.delta_duration().as_secs_f32());
+    follow_flow_fields(data, dt);
+}
+
+fn follow_flow_fields(data: &mut GameData, dt: f32) {
+    let (world, resources, commands) = data.split_with_command();
+    let Some(flow_fields) = resources.get_resource::<FlowFields>() else { return };
+    for (entity, (follower, transform)) in world.query_mut::<(&mut FlowFieldFollower, &Transform)>() {
+        let Some(field) = flow_fields.get(&follower.field) else { continue };
+        let position = transform.global_translation();
+        let Some((x, y)) = field.cell_at(position) else { continue };
+        follower.arrived = field.is_goal(x, y);
+        let target = match field.next_cell(x, y) {
+            Some((nx, ny)) => field.cell_center(nx, ny),
+            None if follower.arrived => field.cell_center(x, y),
+            None => continue,
+        };
+        let (dx, dy) = (target.x - position.x, target.y - position.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let step = (follower.speed * dt).min(length);
+        commands.transform_commands.append_vector(entity, Vector::new(dx / length * step, dy / length * step));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::maths::coordinates::Coordinates;
+    use crate::core::resources::flow_fields::FlowField;
+    use crate::core::resources::pathfinding::{Neighbourhood, PathCosts, PathRequest};
+    use crate::graphics::components::tiles::test_utils::pathing_tilemap;
+    use crate::graphics::components::tiles::tilemap::{TileInfos, Tilemap};
+    use crate::utils::maths::{Dimensions, Position};
+
+    #[test]
+    fn follower_moves_toward_goal() {
+        let mut data = GameData::default();
+        let mut flow_fields = FlowFields::default();
+        let field = FlowField::new(4, 1, vec![Some(1.); 4], &[(3, 0)], Neighbourhood::Four).with_cell_size(10., 10.);
+        flow_fields.insert("exit", field);
+        data.insert_resource(flow_fields);
+        let entity = data.push((FlowFieldFollower::new("exit", 10.), Transform::from_xy(5., 5.)));
+
+        for _ in 0..40 {
+            follow_flow_fields(&mut data, 0.1);
+            data.apply_commands();
+        }
+        let x = data.entry::<&Transform>(entity).unwrap().get().unwrap().translation().x();
+        assert!((x - 35.).abs() < 0.001);
+        assert!(data.entry::<&FlowFieldFollower>(entity).unwrap().get().unwrap().has_arrived());
+    }
+
+    #[test]
+    fn tilemap_flow_field_follows_tile_changes() {
+        let mut data = GameData::default();
+        let tilemap =
+            pathing_tilemap(&mut data, Dimensions::new(3, 2, 1), Transform::default(), &[("floor", &[0]), ("wall", &[1])], |_| {
+                TileInfos::new(Some(0))
+            });
+
+        let field = {
+            let (world, resources) = data.split();
+            let request = PathRequest::new(tilemap, PathCosts::new().with_cost("floor", 1.));
+            FlowField::from_tilemap(world, &resources.assets(), request, &[(2, 0)]).unwrap()
+        };
+        assert_eq!(Some(2.), field.distance(0, 0));
+        let mut flow_fields = FlowFields::default();
+        flow_fields.insert("exit", field);
+        data.insert_resource(flow_fields);
+
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(1, 0, 0), 1);
+        flow_field_system(&mut data);
+        assert_eq!(Some(4.), data.get_resource::<FlowFields>().unwrap().get("exit").unwrap().distance(0, 0));
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(1, 1, 0), 1);
+        flow_field_system(&mut data);
+        assert_eq!(None, data.get_resource::<FlowFields>().unwrap().get("exit").unwrap().distance(0, 0));
+    }
+
+    #[test]
+    fn follower_moves_on_a_tilemap_flow_field() {
+        let mut data = GameData::default();
+        let tilemap =
+            pathing_tilemap(&mut data, Dimensions::new(3, 1, 1), Transform::from_xy(100., 50.), &[("floor", &[0])], |_| {
+                TileInfos::new(Some(0))
+            });
+
+        let field = {
+            let (world, resources) = data.split();
+            let request = PathRequest::new(tilemap, PathCosts::new().with_cost("floor", 1.));
+            FlowField::from_tilemap(world, &resources.assets(), request, &[(2, 0)]).unwrap()
+        };
+        assert_eq!(Some((1, 0)), field.cell_at(&Coordinates::new(120., 60.)));
+        assert_eq!(None, field.cell_at(&Coordinates::new(20., 10.)));
+        let mut flow_fields = FlowFields::default();
+        flow_fields.insert("exit", field);
+        data.insert_resource(flow_fields);
+        let entity = data.push((FlowFieldFollower::new("exit", 16.), Transform::from_xy(108., 58.)));
+
+        for _ in 0..40 {
+            follow_flow_fields(&mut data, 0.1);
+            data.apply_commands();
+        }
+        let x = data.entry::<&Transform>(entity).unwrap().get().unwrap().translation().x();
+        assert!((x - 140.).abs() < 0.001);
+        assert!(data.entry::<&FlowFieldFollower>(entity).unwrap().get().unwrap().has_arrived());
+    }
+}
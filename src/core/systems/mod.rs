@@ -3,24 +3,40 @@ use crate::core::resources::asset_manager::AssetManager;
 use crate::core::resources::audio::Audio;
 use crate::core::resources::events::topic::TopicConfiguration;
 use crate::core::resources::events::Events;
+use crate::core::components::maths::collider::COLLISIONS_TOPIC;
 use crate::core::resources::focus_manager::FocusManager;
 use crate::core::resources::font_atlas::FontAtlas;
 use crate::core::resources::global_storage::GlobalStorage;
 use crate::core::resources::inputs::inputs_controller::InputsController;
+use crate::core::resources::rewind::Rewind;
+use crate::core::resources::broadphase::Broadphase;
+use crate::core::resources::collision_layers::CollisionLayers;
+use crate::core::resources::physics::Physics;
+use crate::core::resources::physics_query::PhysicsQuery;
+use crate::core::resources::pathfinding::Pathfinder;
+use crate::core::resources::flow_fields::FlowFields;
+use crate::core::resources::behavior_registry::BehaviorRegistry;
+use crate::core::resources::tile_object_registry::TileObjectRegistry;
 use crate::core::resources::time::{Time, TimerType, Timers};
 use crate::core::scene::SceneController;
 use crate::core::state::GameState;
+use crate::core::systems::ai_systems::behavior_tree_system;
 use crate::core::systems::animations_system::animation_executer_system;
 use crate::core::systems::asset_ref_resolver_system::asset_ref_resolver_system;
 use crate::core::systems::asset_ref_resolver_system::MaterialAssetResolverFn;
-use crate::core::systems::collider_systems::{collider_cleaner_system, collider_pivot_propagation_system, compute_collisions_system, debug_colliders_system};
+use crate::core::systems::collider_systems::{collider_cleaner_system, collider_pivot_propagation_system, compute_collisions_system, debug_colliders_system, tilemap_grid_collider_system};
 use crate::core::systems::default_camera_system::default_camera_system;
+use crate::core::systems::flow_field_systems::{flow_field_follower_system, flow_field_system};
 use crate::core::systems::focus_systems::focus_switcher_system;
 use crate::core::systems::hide_propagation_system::{
     hide_propagated_deletion_system, hide_propagation_system,
 };
 use crate::core::systems::missing_ui_component_system::{missing_focus_component_system, missing_ui_component_system};
 use crate::core::systems::parent_transform_system::{dirty_transform_offset_system};
+use crate::core::systems::rewind_system::rewind_capture_system;
+use crate::core::systems::physics_system::rigid_body_system;
+use crate::core::systems::tilemap_chunk_systems::tilemap_chunk_system;
+use crate::core::systems::visibility_systems::{field_of_view_system, fog_of_war_system};
 use crate::core::systems::ui_button_systems::{compute_hover, set_childs_on_buttons};
 use crate::core::systems::ui_input_systems::{register_keyboard_inputs_on_ui_input, set_childs_on_inputs, synchronize_input_and_text};
 use crate::core::systems::ui_text_system::{sync_text_value_system, ui_text_atlas_system, ui_text_material_resolver};
@@ -48,6 +64,12 @@ pub(crate) mod ui_text_system;
 pub(crate) mod ui_input_systems;
 pub(crate) mod focus_systems;
 pub(crate) mod ui_button_systems;
+pub(crate) mod rewind_system;
+pub(crate) mod physics_system;
+pub(crate) mod flow_field_systems;
+pub(crate) mod ai_systems;
+pub(crate) mod visibility_systems;
+pub(crate) mod tilemap_chunk_systems;
 
 pub(crate) struct InternalPackage;
 impl Package for InternalPackage {
@@ -56,6 +78,9 @@ impl Package for InternalPackage {
         events
             .create_topic("Inputs", TopicConfiguration::default())
             .expect("Error while creating topic for inputs event");
+        events
+            .create_topic(COLLISIONS_TOPIC, TopicConfiguration::default())
+            .expect("Error while creating topic for collisions event");
 
         let mut timers = Timers::default();
 
@@ -74,6 +99,15 @@ impl Package for InternalPackage {
         data.insert_resource(Audio::default());
         data.insert_resource(FontAtlas::default());
         data.insert_resource(GlobalStorage::default());
+        data.insert_resource(Rewind::default());
+        data.insert_resource(Broadphase::default());
+        data.insert_resource(CollisionLayers::default());
+        data.insert_resource(Physics::default());
+        data.insert_resource(PhysicsQuery::default());
+        data.insert_resource(Pathfinder::default());
+        data.insert_resource(FlowFields::default());
+        data.insert_resource(BehaviorRegistry::default());
+        data.insert_resource(TileObjectRegistry::default());
     }
 
     fn load(&self, builder: ScionBuilder) -> ScionBuilder {
@@ -91,11 +125,13 @@ impl Package for InternalPackage {
             .with_system(collider_pivot_propagation_system::<Triangle>)
             .with_system(collider_pivot_propagation_system::<Polygon>)
             .with_system(collider_pivot_propagation_system::<Line>)
+            .with_system(tilemap_grid_collider_system)
             .with_system(debug_colliders_system)
             .with_system(missing_ui_component_system::<UiImage>)
             .with_system(missing_ui_component_system::<UiText>)
             .with_system(missing_ui_component_system::<UiButton>)
             .with_system(missing_focus_component_system::<UiInput>)
+            .with_system(tilemap_chunk_system)
             .with_system(asset_ref_resolver_system::<Material, MaterialAssetResolverFn>)
             .with_system(animation_executer_system)
             .with_system(compute_collisions_system)
@@ -106,5 +142,12 @@ impl Package for InternalPackage {
             .with_system(focus_switcher_system)
             .with_system(register_keyboard_inputs_on_ui_input)
             .with_system(synchronize_input_and_text)
+            .with_system(flow_field_system)
+            .with_system(flow_field_follower_system)
+            .with_system(behavior_tree_system)
+            .with_system(field_of_view_system)
+            .with_system(fog_of_war_system)
+            .with_fixed_system(rigid_body_system)
+            .with_fixed_system(rewind_capture_system)
     }
 }
@@ -0,0 +1,343 @@
+use std::collections::{HashMap, HashSet};
+
+use hecs::Entity;
+use profiling_macros::profile;
+
+use crate::core::components::maths::collider::{sweep_shape, Collider, ColliderShape, CONTACT_PENETRATION};
+use crate::core::components::maths::rigid_body::{BodyType, RigidBody, Velocity};
+use crate::core::components::maths::transform::Transform;
+use crate::core::resources::broadphase::SpatialHash;
+use crate::core::resources::collision_layers::CollisionLayers;
+use crate::core::resources::physics::Physics;
+use crate::core::systems::collider_systems::resolve_collider_layers;
+use crate::core::world::{GameData, World};
+use crate::utils::maths::Vector;
+
+/// State of a body during a simulation step
+struct SimulatedBody {
+    entity: Entity,
+    body_type: BodyType,
+    inverse_mass: f32,
+    restitution: f32,
+    friction: f32,
+    gravity_scale: f32,
+    velocity: Vector,
+    /// Translation applied to the body during this step
+    moved: Vector,
+    /// Shape of the collider, its layer bits and the bits of the layers it collides with
+    shape: Option<(ColliderShape, u64, u64)>,
+    /// Whether the movement of the body is checked by continuous collision detection
+    continuous: bool,
+}
+
+impl SimulatedBody {
+    fn translate(&mut self, dx: f32, dy: f32) {
+        self.moved.x += dx;
+        self.moved.y += dy;
+        if let Some((shape, _, _)) = self.shape.as_mut() {
+            shape.translate(dx, dy);
+        }
+    }
+}
+
+/// System responsible to move the rigid bodies, and to separate them when they overlap.
+/// Colliders without `RigidBody` are considered as static bodies.
+#[profile("system::rigid_body_system")]
+pub(crate) fn rigid_body_system(data: &mut GameData) {
+    resolve_collider_layers(data);
+    let (world, resources, commands) = data.split_with_command();
+    let physics = resources.get_resource::<Physics>().expect("Missing Physics resource");
+    let layers = resources.get_resource::<CollisionLayers>().expect("Missing CollisionLayers resource");
+    let dt = physics.timestep();
+
+    let static_body = RigidBody::fixed();
+    let mut bodies: Vec<SimulatedBody> = Vec::new();
+    let mut has_dynamic = false;
+    for (entity, (transform, collider, body, velocity)) in
+        world.query::<(&Transform, Option<&Collider>, Option<&RigidBody>, Option<&Velocity>)>().iter()
+    {
+        if collider.is_none() && body.is_none() {
+            continue;
+        }
+        let body = body.unwrap_or(&static_body);
+        has_dynamic |= body.body_type() == BodyType::Dynamic;
+        bodies.push(SimulatedBody {
+            entity,
+            body_type: body.body_type(),
+            inverse_mass: body.inverse_mass(),
+            restitution: body.restitution(),
+            friction: body.friction(),
+            gravity_scale: body.gravity_scale(),
+            velocity: velocity.map_or(Vector::default(), |v| v.linear),
+            moved: Vector::default(),
+            shape: collider.map(|c| (c.collider_shape(transform), c.layer_bits(), layers.collision_bits(c))),
+            continuous: collider.is_some_and(|c| c.is_continuous()),
+        });
+    }
+    if bodies.iter().all(|b| b.body_type == BodyType::Static) {
+        return;
+    }
+
+    let gravity = *physics.gravity();
+    for body in bodies.iter_mut().filter(|b| b.body_type != BodyType::Static) {
+        if body.body_type == BodyType::Dynamic {
+            body.velocity.x += gravity.x * body.gravity_scale * dt;
+            body.velocity.y += gravity.y * body.gravity_scale * dt;
+        }
+        if !body.continuous {
+            let (dx, dy) = (body.velocity.x * dt, body.velocity.y * dt);
+            body.translate(dx, dy);
+        }
+    }
+    for i in 0..bodies.len() {
+        if bodies[i].continuous && bodies[i].body_type != BodyType::Static {
+            move_continuous_body(&mut bodies, i, dt);
+        }
+    }
+
+    if has_dynamic {
+        for _ in 0..physics.iterations() {
+            resolve_collisions(&mut bodies);
+        }
+    }
+
+    for body in bodies.iter().filter(|b| b.body_type != BodyType::Static) {
+        if body.moved.x != 0. || body.moved.y != 0. {
+            commands.transform_commands.append_vector(body.entity, body.moved);
+        }
+        if let Ok(velocity) = world.entry_mut::<&mut Velocity>(body.entity) {
+            velocity.linear = body.velocity;
+        } else if body.body_type == BodyType::Dynamic {
+            // Dynamic bodies created without `Velocity` get one, to keep the velocity they gained
+            let _r = world.add_components(body.entity, (Velocity { linear: body.velocity },));
+        }
+    }
+}
+
+/// Moves a continuous body along its velocity, stopping it just inside the first shape on its way,
+/// so that the collision resolution applies the contact instead of letting it go through
+fn move_continuous_body(bodies: &mut [SimulatedBody], index: usize, dt: f32) {
+    let (dx, dy) = (bodies[index].velocity.x * dt, bodies[index].velocity.y * dt);
+    let length = (dx * dx + dy * dy).sqrt();
+    let Some((shape, layer_bits, collision_bits)) = bodies[index].shape.as_ref().filter(|_| length > f32::EPSILON) else {
+        bodies[index].translate(dx, dy);
+        return;
+    };
+    let direction = Vector::new(dx / length, dy / length);
+    let swept_aabb = shape.aabb().swept(&direction, length);
+    let travel = bodies
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != index)
+        .filter_map(|(_, other)| other.shape.as_ref())
+        .filter(|(other_shape, other_layer_bits, other_collision_bits)| {
+            interacts(*layer_bits, *collision_bits, *other_layer_bits, *other_collision_bits)
+                && other_shape.aabb().intersects(&swept_aabb)
+        })
+        .filter_map(|(other_shape, _, _)| sweep_shape(shape, &direction, length, other_shape).map(|(distance, _)| distance))
+        .fold(length, f32::min);
+    let travel = if travel < length { (travel + CONTACT_PENETRATION).min(length) } else { length };
+    bodies[index].translate(direction.x * travel, direction.y * travel);
+}
+
+fn interacts(layer_bits: u64, collision_bits: u64, other_layer_bits: u64, other_collision_bits: u64) -> bool {
+    collision_bits & other_layer_bits != 0 || other_collision_bits & layer_bits != 0
+}
+
+/// Separates every overlapping pair involving a dynamic body, and applies the bounce and friction impulses
+fn resolve_collisions(bodies: &mut [SimulatedBody]) {
+    let mut grid = SpatialHash::new(64.);
+    let mut aabbs = HashMap::new();
+    for (index, body) in bodies.iter().enumerate() {
+        if let Some((shape, _, _)) = body.shape.as_ref() {
+            let aabb = shape.aabb();
+            grid.insert(body.entity, &aabb);
+            aabbs.insert(body.entity, (index, aabb));
+        }
+    }
+
+    let mut candidates = HashSet::new();
+    for i in 0..bodies.len() {
+        if bodies[i].body_type != BodyType::Dynamic {
+            continue;
+        }
+        let Some((_, aabb)) = aabbs.get(&bodies[i].entity) else { continue };
+        candidates.clear();
+        grid.query(aabb, &mut candidates);
+        let mut others: Vec<usize> = candidates
+            .iter()
+            .map(|e| aabbs[e].0)
+            // Pairs of dynamic bodies are resolved once
+            .filter(|j| *j != i && (bodies[*j].body_type != BodyType::Dynamic || *j > i))
+            .collect();
+        others.sort_unstable();
+
+        for j in others {
+            let mtv = {
+                let (a, b) = (&bodies[i], &bodies[j]);
+                let (Some((shape, layer_bits, collision_bits)), Some((other_shape, other_layer_bits, other_collision_bits))) =
+                    (&a.shape, &b.shape)
+                else {
+                    continue;
+                };
+                if !interacts(*layer_bits, *collision_bits, *other_layer_bits, *other_collision_bits) {
+                    continue;
+                }
+                match shape.minimum_translation_vector(other_shape) {
+                    Some(mtv) => mtv,
+                    None => continue,
+                }
+            };
+            resolve_pair(bodies, i, j, mtv);
+        }
+    }
+}
+
+fn resolve_pair(bodies: &mut [SimulatedBody], i: usize, j: usize, mtv: Vector) {
+    let total_inverse_mass = bodies[i].inverse_mass + bodies[j].inverse_mass;
+    if total_inverse_mass <= 0. {
+        return;
+    }
+    let (ratio_i, ratio_j) = (bodies[i].inverse_mass / total_inverse_mass, bodies[j].inverse_mass / total_inverse_mass);
+    bodies[i].translate(mtv.x * ratio_i, mtv.y * ratio_i);
+    bodies[j].translate(-mtv.x * ratio_j, -mtv.y * ratio_j);
+
+    let depth = (mtv.x * mtv.x + mtv.y * mtv.y).sqrt();
+    if depth <= f32::EPSILON {
+        return;
+    }
+    let normal = Vector::new(mtv.x / depth, mtv.y / depth);
+    let relative = Vector::new(bodies[i].velocity.x - bodies[j].velocity.x, bodies[i].velocity.y - bodies[j].velocity.y);
+    let normal_speed = relative.x * normal.x + relative.y * normal.y;
+    if normal_speed >= 0. {
+        return;
+    }
+
+    let restitution = bodies[i].restitution.max(bodies[j].restitution);
+    let impulse = -(1. + restitution) * normal_speed / total_inverse_mass;
+
+    let tangent = Vector::new(relative.x - normal_speed * normal.x, relative.y - normal_speed * normal.y);
+    let tangent_length = (tangent.x * tangent.x + tangent.y * tangent.y).sqrt();
+    let (tangent, friction_impulse) = if tangent_length > f32::EPSILON {
+        let tangent = Vector::new(tangent.x / tangent_length, tangent.y / tangent_length);
+        let friction = (bodies[i].friction * bodies[j].friction).sqrt();
+        let friction_impulse = (-tangent_length / total_inverse_mass).max(-friction * impulse);
+        (tangent, friction_impulse)
+    } else {
+        (Vector::default(), 0.)
+    };
+
+    let apply = |body: &mut SimulatedBody, sign: f32| {
+        body.velocity.x += sign * body.inverse_mass * (impulse * normal.x + friction_impulse * tangent.x);
+        body.velocity.y += sign * body.inverse_mass * (impulse * normal.y + friction_impulse * tangent.y);
+    };
+    apply(&mut bodies[i], 1.);
+    apply(&mut bodies[j], -1.);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::systems::collider_systems::collision_test_data;
+    use crate::core::components::maths::collider::{ColliderMask, ColliderType};
+
+    fn step(data: &mut GameData) {
+        rigid_body_system(data);
+        data.apply_commands();
+    }
+
+    #[test]
+    fn dynamic_body_falls_and_lands_on_static_collider() {
+        let mut data = collision_test_data();
+        data.insert_resource(Physics::default());
+        let body = data.push((
+            Transform::from_xy(0., 0.),
+            RigidBody::dynamic(),
+            Velocity::default(),
+            Collider::new(ColliderMask::Character, vec![ColliderMask::Landscape], ColliderType::SquareCollider(10)),
+        ));
+        let _floor = data.push((
+            Transform::from_xy(-50., 30.),
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(200, 10)),
+        ));
+
+        step(&mut data);
+        assert!(data.entry::<&Transform>(body).unwrap().get().unwrap().translation().y() > 0.);
+
+        for _ in 0..120 {
+            step(&mut data);
+        }
+        let y = data.entry::<&Transform>(body).unwrap().get().unwrap().translation().y();
+        assert!((y - 20.).abs() < 0.5, "body should rest on the floor, y = {}", y);
+        assert!(data.entry::<&Velocity>(body).unwrap().get().unwrap().y().abs() < 1.);
+    }
+
+    #[test]
+    fn dynamic_body_without_velocity_accelerates() {
+        let mut data = collision_test_data();
+        data.insert_resource(Physics::default());
+        let body = data.push((Transform::from_xy(0., 0.), RigidBody::dynamic()));
+
+        step(&mut data);
+        let first = data.entry::<&Transform>(body).unwrap().get().unwrap().translation().y();
+        step(&mut data);
+        let second = data.entry::<&Transform>(body).unwrap().get().unwrap().translation().y();
+        assert!(second - first > first, "body should accelerate, moved {} then {}", first, second - first);
+        assert!(data.entry::<&Velocity>(body).unwrap().get().unwrap().y() > 0.);
+    }
+
+    #[test]
+    fn kinematic_body_pushes_dynamic_body() {
+        let mut data = collision_test_data();
+        let mut physics = Physics::default();
+        physics.set_gravity(Vector::default());
+        data.insert_resource(physics);
+
+        let pusher = data.push((
+            Transform::from_xy(0., 0.),
+            RigidBody::kinematic(),
+            Velocity::new(60., 0.),
+            Collider::new(ColliderMask::Character, vec![ColliderMask::Item], ColliderType::SquareCollider(10)),
+        ));
+        let crate_entity = data.push((
+            Transform::from_xy(10.5, 0.),
+            RigidBody::dynamic(),
+            Collider::new(ColliderMask::Item, vec![], ColliderType::SquareCollider(10)),
+        ));
+
+        for _ in 0..30 {
+            step(&mut data);
+        }
+        let pusher_x = data.entry::<&Transform>(pusher).unwrap().get().unwrap().translation().x();
+        let crate_x = data.entry::<&Transform>(crate_entity).unwrap().get().unwrap().translation().x();
+        assert!((pusher_x - 30.).abs() < 0.01);
+        assert!(crate_x >= pusher_x + 9.9);
+    }
+
+    #[test]
+    fn continuous_body_does_not_go_through_thin_wall() {
+        let mut data = collision_test_data();
+        let mut physics = Physics::default();
+        physics.set_gravity(Vector::default());
+        data.insert_resource(physics);
+
+        let bullet = data.push((
+            Transform::from_xy(0., 0.),
+            RigidBody::dynamic(),
+            Velocity::new(3000., 0.),
+            Collider::new(ColliderMask::Bullet, vec![ColliderMask::Landscape], ColliderType::SquareCollider(4))
+                .with_continuous_detection(),
+        ));
+        let _wall = data.push((
+            Transform::from_xy(30., -20.),
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(2, 40)),
+        ));
+
+        for _ in 0..5 {
+            step(&mut data);
+        }
+        let x = data.entry::<&Transform>(bullet).unwrap().get().unwrap().translation().x();
+        assert!(x <= 26.1, "bullet should stop at the wall, x = {}", x);
+        assert!(data.entry::<&Velocity>(bullet).unwrap().get().unwrap().x() <= 0.);
+    }
+}
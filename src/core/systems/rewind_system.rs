@@ -0,0 +1,18 @@
+use profiling_macros::profile;
+
+use crate::core::components::Rewindable;
+use crate::core::resources::rewind::Rewind;
+use crate::core::world::{GameData, World};
+
+/// System responsible to capture the state of the `Rewindable` entities, at each fixed update.
+/// Nothing is captured while no entity is `Rewindable`
+#[profile("system::rewind_capture_system")]
+pub(crate) fn rewind_capture_system(data: &mut GameData) {
+    let (world, resources) = data.split();
+    if world.query::<&Rewindable>().iter().next().is_none() {
+        return;
+    }
+    if let Some(mut rewind) = resources.get_resource_mut::<Rewind>() {
+        rewind.capture(world);
+    }
+}
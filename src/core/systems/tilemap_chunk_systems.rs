@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use hecs::Entity;
+use profiling_macros::profile;
+
+use crate::core::components::maths::camera::Camera;
+use crate::core::components::maths::transform::Transform;
+use crate::core::world::{GameData, World};
+use crate::graphics::components::tiles::chunk::{load_chunk, unload_chunk};
+use crate::graphics::components::tiles::tilemap::Tilemap;
+
+type Chunks = Vec<(usize, usize)>;
+
+/// System responsible to load the chunks of the chunked tilemaps near the cameras, and to unload the far ones
+#[profile("system::tilemap_chunk_system")]
+pub(crate) fn tilemap_chunk_system(data: &mut GameData) {
+    // Visible rectangles of the cameras, in world coordinates
+    let views: Vec<((f32, f32), (f32, f32))> = data
+        .query::<(&Camera, &Transform)>()
+        .iter()
+        .map(|(_, (camera, transform))| {
+            let (x, y) = (transform.global_translation().x(), transform.global_translation().y());
+            ((x + camera.left, y - camera.top), (x + camera.right, y - camera.bottom))
+        })
+        .collect();
+    if views.is_empty() {
+        return;
+    }
+
+    // Chunks to load and to unload, for each tilemap
+    let mut changes: Vec<(Entity, Chunks, Chunks)> = Vec::new();
+    {
+        let assets = data.assets();
+        for (entity, (tilemap, transform)) in data.query::<(&Tilemap, &Transform)>().iter() {
+            let Some(chunks) = tilemap.chunks.as_ref() else { continue };
+            let Some(tileset) = assets.retrieve_tileset(tilemap.tileset_ref()) else { continue };
+            let (axis_x, axis_y) = tilemap.grid_axes(tileset.tile_width as f32);
+            let determinant = axis_x.x() * axis_y.y() - axis_x.y() * axis_y.x();
+            if determinant.abs() < f32::EPSILON {
+                continue;
+            }
+            let origin = (transform.global_translation().x(), transform.global_translation().y());
+            let to_tile = |(x, y): (f32, f32)| {
+                let (x, y) = (x - origin.0, y - origin.1);
+                ((x * axis_y.y() - y * axis_y.x()) / determinant, (y * axis_x.x() - x * axis_x.y()) / determinant)
+            };
+
+            let mut wanted = HashSet::new();
+            let mut kept = HashSet::new();
+            for (min, max) in views.iter() {
+                let corners = [*min, (max.0, min.1), (min.0, max.1), *max].map(to_tile);
+                let tile_min = corners.iter().fold((f32::MAX, f32::MAX), |acc, c| (acc.0.min(c.0), acc.1.min(c.1)));
+                let tile_max = corners.iter().fold((f32::MIN, f32::MIN), |acc, c| (acc.0.max(c.0), acc.1.max(c.1)));
+                let margin = chunks.settings.load_margin;
+                wanted.extend(chunks.chunks_around(tile_min, tile_max, tilemap.width(), tilemap.height(), margin));
+                kept.extend(chunks.chunks_around(tile_min, tile_max, tilemap.width(), tilemap.height(), margin + 1));
+            }
+
+            let to_load: Chunks = wanted.into_iter().filter(|c| !chunks.loaded.contains_key(c)).collect();
+            let to_unload: Chunks = chunks.loaded.keys().filter(|c| !kept.contains(*c)).copied().collect();
+            if !to_load.is_empty() || !to_unload.is_empty() {
+                changes.push((entity, to_load, to_unload));
+            }
+        }
+    }
+
+    for (tilemap, to_load, to_unload) in changes {
+        for chunk in to_unload {
+            unload_chunk(data, tilemap, chunk);
+        }
+        for chunk in to_load {
+            load_chunk(data, tilemap, chunk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::components::tiles::chunk::ChunkSettings;
+    use crate::graphics::components::tiles::test_utils::pathing_tileset;
+    use crate::graphics::components::tiles::tilemap::{TileInfos, TilemapInfo, TilemapType};
+    use crate::utils::maths::{Dimensions, Position};
+
+    /// 100x100 tilemap of 16px tiles, in chunks of 10x10 tiles, seen by a 320x160 camera at the origin
+    fn chunked_world() -> (GameData, Entity) {
+        let mut data = GameData::default();
+        let tileset = pathing_tileset(&mut data, "chunks", 4, &[("wall", &[1])]);
+        data.push((Camera::new(320., 160.), Transform::default()));
+        let infos = TilemapInfo::new(Dimensions::new(100, 100, 1), Transform::default(), tileset, TilemapType::Standard);
+        let tilemap = Tilemap::create_chunked(infos, ChunkSettings::new(10, 10), &mut data, |p| {
+            TileInfos::new(Some(if p.x() == p.y() { 1 } else { 0 }))
+        });
+        (data, tilemap)
+    }
+
+    fn loaded_chunks(data: &mut GameData, tilemap: Entity) -> HashSet<(usize, usize)> {
+        data.entry_mut::<&Tilemap>(tilemap).unwrap().loaded_chunks().into_iter().map(|(c, _)| c).collect()
+    }
+
+    #[test]
+    fn only_chunks_near_the_camera_are_loaded() {
+        let (mut data, tilemap) = chunked_world();
+        tilemap_chunk_system(&mut data);
+
+        // The camera reaches the tiles (20, 10), in the chunk (2, 1), and chunks are loaded with a margin of one chunk
+        let expected: HashSet<(usize, usize)> = (0..=3).flat_map(|x| (0..=2).map(move |y| (x, y))).collect();
+        assert_eq!(expected, loaded_chunks(&mut data, tilemap));
+        assert_eq!(12 * 100, data.query::<&crate::graphics::components::tiles::tilemap::Tile>().iter().count());
+
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(800., 800.);
+        }
+        tilemap_chunk_system(&mut data);
+        let loaded = loaded_chunks(&mut data, tilemap);
+        assert!(!loaded.contains(&(0, 0)));
+        assert!(loaded.contains(&(5, 5)));
+        assert!(loaded.contains(&(7, 6)));
+    }
+
+    #[test]
+    fn tiles_of_unloaded_chunks_stay_addressable() {
+        let (mut data, tilemap) = chunked_world();
+        tilemap_chunk_system(&mut data);
+
+        assert_eq!(Some(1), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(50, 50, 0)));
+        assert_eq!(Some(0), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(50, 51, 0)));
+        assert_eq!(None, Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(100, 51, 0)));
+        let (world, resources) = data.split();
+        assert_eq!(Some("wall".to_string()), Tilemap::retrieve_pathing(world, tilemap, &Position::new(60, 60, 0), &resources.assets()));
+
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(60, 61, 0), 1);
+        assert_eq!(Some(1), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(60, 61, 0)));
+    }
+
+    #[test]
+    fn modified_tiles_are_kept_when_their_chunk_is_reloaded() {
+        let (mut data, tilemap) = chunked_world();
+        tilemap_chunk_system(&mut data);
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(3, 4, 0), 2);
+
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(1600., 1600.);
+        }
+        tilemap_chunk_system(&mut data);
+        assert!(!loaded_chunks(&mut data, tilemap).contains(&(0, 0)));
+        assert_eq!(Some(2), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(3, 4, 0)));
+
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(0., 0.);
+        }
+        tilemap_chunk_system(&mut data);
+        assert!(loaded_chunks(&mut data, tilemap).contains(&(0, 0)));
+        let tile = data.entry_mut::<&Tilemap>(tilemap).unwrap().tile_entity(&Position::new(3, 4, 0)).unwrap();
+        assert_eq!(2, data.entry_mut::<&crate::graphics::components::tiles::sprite::Sprite>(tile).unwrap().get_tile_nb());
+    }
+
+    #[test]
+    fn flips_are_kept_when_their_chunk_is_reloaded() {
+        use crate::graphics::components::tiles::sprite::{Sprite, TileFlip};
+
+        let (mut data, tilemap) = chunked_world();
+        tilemap_chunk_system(&mut data);
+        let flip = TileFlip { horizontal: true, vertical: false, diagonal: false };
+        let tile = data.entry_mut::<&Tilemap>(tilemap).unwrap().tile_entity(&Position::new(3, 4, 0)).unwrap();
+        data.entry_mut::<&mut Sprite>(tile).unwrap().set_flip(flip);
+
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(1600., 1600.);
+        }
+        tilemap_chunk_system(&mut data);
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(0., 0.);
+        }
+        tilemap_chunk_system(&mut data);
+        let tile = data.entry_mut::<&Tilemap>(tilemap).unwrap().tile_entity(&Position::new(3, 4, 0)).unwrap();
+        assert_eq!(flip, data.entry_mut::<&Sprite>(tile).unwrap().flip());
+    }
+
+    #[test]
+    fn highlights_are_kept_when_their_chunk_is_reloaded() {
+        use crate::graphics::components::color::Color;
+        use crate::graphics::components::tiles::sprite::Sprite;
+        use crate::graphics::rendering::Highlight;
+
+        let (mut data, tilemap) = chunked_world();
+        tilemap_chunk_system(&mut data);
+        let tile = data.entry_mut::<&Tilemap>(tilemap).unwrap().tile_entity(&Position::new(3, 4, 0)).unwrap();
+        data.entry_mut::<&mut Sprite>(tile).unwrap().set_highlight(Some(Highlight::Tint(Color::new_rgb(255, 0, 0))));
+
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(1600., 1600.);
+        }
+        tilemap_chunk_system(&mut data);
+        assert!(!loaded_chunks(&mut data, tilemap).contains(&(0, 0)));
+
+        for (_, t) in data.query_mut::<&mut Transform>().with::<&Camera>() {
+            *t = Transform::from_xy(0., 0.);
+        }
+        tilemap_chunk_system(&mut data);
+        let tile = data.entry_mut::<&Tilemap>(tilemap).unwrap().tile_entity(&Position::new(3, 4, 0)).unwrap();
+        let highlight = data.entry_mut::<&Sprite>(tile).unwrap().highlight().cloned();
+        assert!(matches!(highlight, Some(Highlight::Tint(color)) if (color.red(), color.green()) == (255, 0)));
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::{HashMap, HashSet};
+
+use hecs::Entity;
+use profiling_macros::profile;
+
+use crate::core::world::{GameData, World};
+use crate::graphics::components::tiles::sprite::Sprite;
+use crate::graphics::components::tiles::tilemap::Tilemap;
+use crate::graphics::components::tiles::visibility::{tilemap_field_of_view, CellVisibility, FieldOfView, FogOfWar};
+use crate::graphics::rendering::Highlight;
+use crate::utils::maths::Position;
+
+/// System responsible to compute again the `FieldOfView` whose origin or tilemap changed
+#[profile("system::field_of_view_system")]
+pub(crate) fn field_of_view_system(data: &mut GameData) {
+    let (world, resources) = data.split();
+    let outdated: Vec<(Entity, (usize, usize), u64)> = world
+        .query::<&FieldOfView>()
+        .iter()
+        .filter_map(|(entity, fov)| {
+            let revision = world.entry::<&Tilemap>(fov.tilemap).ok()?.get()?.revision();
+            (fov.computed != Some((fov.origin, revision))).then_some((entity, fov.origin, revision))
+        })
+        .collect();
+
+    for (entity, origin, revision) in outdated {
+        let (tilemap, layer, radius, opacity) = {
+            let fov = world.entry_mut::<&mut FieldOfView>(entity).expect("Missing field of view");
+            (fov.tilemap, fov.layer, fov.radius, fov.opacity.clone())
+        };
+        let visible = tilemap_field_of_view(world, &resources.assets(), tilemap, layer, origin, radius, &opacity);
+        let fov = world.entry_mut::<&mut FieldOfView>(entity).expect("Missing field of view");
+        fov.visible = visible;
+        fov.computed = Some((origin, revision));
+        fov.revision += 1;
+    }
+}
+
+/// System responsible to tint the tiles of the tilemaps having a `FogOfWar`, depending on what their viewers see
+#[profile("system::fog_of_war_system")]
+pub(crate) fn fog_of_war_system(data: &mut GameData) {
+    let fogged: Vec<Entity> = data.query::<&FogOfWar>().with::<&Tilemap>().iter().map(|(e, _)| e).collect();
+    for tilemap in fogged {
+        let viewers: HashMap<Entity, u64> =
+            data.query::<&FieldOfView>().iter().filter(|(_, fov)| fov.tilemap == tilemap).map(|(e, fov)| (e, fov.revision)).collect();
+        let up_to_date = data.entry::<&FogOfWar>(tilemap).ok().and_then(|mut e| e.get().map(|fog| !fog.dirty && fog.viewers == viewers));
+        if up_to_date != Some(false) {
+            continue;
+        }
+        let visible: HashSet<(usize, usize)> = data
+            .query::<&FieldOfView>()
+            .iter()
+            .filter(|(_, fov)| fov.tilemap == tilemap)
+            .flat_map(|(_, fov)| fov.visible.iter().map(|p| (p.x(), p.y())).collect::<Vec<_>>())
+            .collect();
+
+        let mut changes = Vec::new();
+        let (width, height, depth) = {
+            let Ok(mut entry) = data.entry::<(&Tilemap, &mut FogOfWar)>(tilemap) else { continue };
+            let Some((map, fog)) = entry.get() else { continue };
+            // Only the cells entering or leaving the sight can change, unless everything has to be checked
+            let cells: Vec<(usize, usize)> = if fog.dirty {
+                (0..map.width()).flat_map(|x| (0..map.height()).map(move |y| (x, y))).collect()
+            } else {
+                fog.visible.symmetric_difference(&visible).copied().collect()
+            };
+            fog.explored.extend(visible.iter().copied());
+            fog.visible = visible;
+            fog.viewers = viewers;
+            fog.dirty = false;
+            for (x, y) in cells {
+                let visibility = fog.visibility(x, y);
+                if fog.applied.insert((x, y), visibility) != Some(visibility) {
+                    let highlight = match visibility {
+                        CellVisibility::Unexplored => Some(Highlight::Tint(fog.unexplored_color.clone())),
+                        CellVisibility::Remembered => Some(Highlight::Tint(fog.remembered_color.clone())),
+                        CellVisibility::Visible => None,
+                    };
+                    changes.push(((x, y), highlight));
+                }
+            }
+            (map.width(), map.height(), map.depth())
+        };
+        if changes.is_empty() || width * height == 0 {
+            continue;
+        }
+
+        let mut hidden_highlights = data
+            .entry_mut::<&mut FogOfWar>(tilemap)
+            .map(|fog| std::mem::take(&mut fog.hidden_highlights))
+            .expect("Missing fog of war");
+        let tiles: Vec<(Entity, Option<Highlight>)> = {
+            let mut entry = data.entry::<&Tilemap>(tilemap).expect("Missing tilemap");
+            let map = entry.get().expect("Missing tilemap");
+            changes
+                .into_iter()
+                .flat_map(|((x, y), highlight)| {
+                    (0..depth).filter_map(move |z| map.tile_entity(&Position::new(x, y, z)).map(|tile| (tile, highlight.clone())))
+                })
+                .collect()
+        };
+        for (tile, highlight) in tiles {
+            if let Ok(sprite) = data.entry_mut::<&mut Sprite>(tile) {
+                match highlight {
+                    Some(fog_tint) => {
+                        // A highlight set by the game while the tile was fogged replaces the saved one
+                        if !sprite.is_fog_tinted() {
+                            hidden_highlights.insert(tile, sprite.highlight().cloned());
+                        }
+                        sprite.set_fog_tint(fog_tint);
+                    }
+                    None => {
+                        // Tiles never hidden by the fog, or highlighted by the game since, keep their highlight
+                        let hidden = hidden_highlights.remove(&tile);
+                        if let Some(highlight) = hidden.filter(|_| sprite.is_fog_tinted()) {
+                            sprite.set_highlight(highlight);
+                        }
+                    }
+                }
+            }
+        }
+        if let Ok(fog) = data.entry_mut::<&mut FogOfWar>(tilemap) {
+            fog.hidden_highlights = hidden_highlights;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::maths::transform::Transform;
+    use crate::graphics::components::color::Color;
+    use crate::graphics::components::tiles::test_utils::pathing_tilemap;
+    use crate::graphics::components::tiles::tilemap::TileInfos;
+    use crate::graphics::components::tiles::visibility::Opacity;
+    use crate::utils::maths::Dimensions;
+
+    const FLOOR: usize = 0;
+    const WALL: usize = 1;
+
+    /// Corridor of 7 cells, with a wall in the middle
+    fn corridor(data: &mut GameData) -> Entity {
+        pathing_tilemap(data, Dimensions::new(7, 1, 1), Transform::default(), &[("floor", &[FLOOR]), ("wall", &[WALL])], |p| {
+            TileInfos::new(Some(if p.x() == 3 { WALL } else { FLOOR }))
+        })
+    }
+
+    fn tint(data: &GameData, tilemap: Entity, x: usize) -> Option<Highlight> {
+        let tile = data.entry::<&Tilemap>(tilemap).unwrap().get().unwrap().tile_entity(&Position::new(x, 0, 0)).unwrap();
+        data.entry::<&Sprite>(tile).unwrap().get().unwrap().highlight().cloned()
+    }
+
+    #[test]
+    fn fog_of_war_follows_viewers_and_tile_changes() {
+        let mut data = GameData::default();
+        let tilemap = corridor(&mut data);
+        let _r = data.add_components(tilemap, (FogOfWar::new(),));
+        let viewer = data.push((FieldOfView::new(tilemap, (1, 0), Opacity::new().with_opaque_pathing("wall")),));
+
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+        assert!(data.entry::<&FieldOfView>(viewer).unwrap().get().unwrap().is_visible(3, 0));
+        assert!(!data.entry::<&FieldOfView>(viewer).unwrap().get().unwrap().is_visible(4, 0));
+        assert!(tint(&data, tilemap, 0).is_none());
+        assert!(matches!(tint(&data, tilemap, 5), Some(Highlight::Tint(_))));
+
+        // Opening the wall and moving the viewer
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(3, 0, 0), FLOOR);
+        data.entry_mut::<&mut FieldOfView>(viewer).unwrap().set_origin(6, 0);
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+        let mut fog = data.entry::<&FogOfWar>(tilemap).unwrap();
+        let fog = fog.get().unwrap();
+        assert_eq!(CellVisibility::Visible, fog.visibility(0, 0));
+        assert_eq!(7, fog.explored().len());
+    }
+
+    #[test]
+    fn fog_of_war_restores_the_highlights_of_the_tiles() {
+        let mut data = GameData::default();
+        let tilemap = corridor(&mut data);
+        let selected = Highlight::ColorNonTransparent(Color::new(255, 0, 0, 1.));
+        let tile = data.entry::<&Tilemap>(tilemap).unwrap().get().unwrap().tile_entity(&Position::new(5, 0, 0)).unwrap();
+        data.entry_mut::<&mut Sprite>(tile).unwrap().set_highlight(Some(selected.clone()));
+        let untouched = data.entry::<&Tilemap>(tilemap).unwrap().get().unwrap().tile_entity(&Position::new(4, 0, 0)).unwrap();
+        data.entry_mut::<&mut Sprite>(untouched).unwrap().set_highlight(Some(selected));
+        let _r = data.add_components(tilemap, (FogOfWar::new(),));
+        let viewer = data.push((FieldOfView::new(tilemap, (1, 0), Opacity::new().with_opaque_pathing("wall")),));
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+        assert!(matches!(tint(&data, tilemap, 5), Some(Highlight::Tint(_))));
+
+        // Without any new field of view, the tiles are left untouched
+        data.entry_mut::<&mut Sprite>(tile).unwrap().set_highlight(None);
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+        assert!(tint(&data, tilemap, 5).is_none());
+
+        // The newest highlight set by the game is kept when the tile becomes visible
+        data.entry_mut::<&mut Sprite>(tile).unwrap().set_highlight(Some(Highlight::Tint(Color::new(0, 0, 255, 1.))));
+        data.entry_mut::<&mut FieldOfView>(viewer).unwrap().set_origin(6, 0);
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+        assert!(matches!(tint(&data, tilemap, 5), Some(Highlight::Tint(c)) if c.blue() == 255));
+
+        // An untouched tile gets back the highlight the game set before the fog
+        assert!(matches!(tint(&data, tilemap, 4), Some(Highlight::ColorNonTransparent(_))));
+    }
+
+    #[test]
+    fn fog_of_war_remembered_tiles() {
+        let mut data = GameData::default();
+        let tilemap = corridor(&mut data);
+        let _r = data.add_components(tilemap, (FogOfWar::new(),));
+        let viewer = data.push((FieldOfView::new(tilemap, (4, 0), Opacity::new().with_opaque_pathing("wall")).with_radius(1),));
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+
+        data.entry_mut::<&mut FieldOfView>(viewer).unwrap().set_origin(6, 0);
+        field_of_view_system(&mut data);
+        fog_of_war_system(&mut data);
+        let mut fog = data.entry::<&FogOfWar>(tilemap).unwrap();
+        let fog = fog.get().unwrap();
+        assert_eq!(CellVisibility::Remembered, fog.visibility(3, 0));
+        assert_eq!(CellVisibility::Remembered, fog.visibility(4, 0));
+        assert_eq!(CellVisibility::Visible, fog.visibility(5, 0));
+        assert_eq!(CellVisibility::Unexplored, fog.visibility(2, 0));
+    }
+}
@@ -15,12 +15,15 @@ use crate::core::components::Dirty;
 use crate::core::components::maths::camera::{Camera, DefaultCamera};
 use crate::core::components::maths::hierarchy::{init_parent_children_link, retrieve_children, retrieve_parent, update_children_if_needed, update_parent_if_needed, Children, Parent};
 use crate::core::components::maths::transform::{Transform, TransformOperation};
+use crate::core::prefab::data::PrefabOverrides;
+use crate::core::prefab::importer::{spawn_prefab, spawn_prefab_with_overrides};
 use crate::core::resources::asset_manager::AssetManager;
 use crate::core::resources::audio::Audio;
 use crate::core::resources::events::Events;
 use crate::core::resources::focus_manager::FocusManager;
 use crate::core::resources::font_atlas::FontAtlas;
 use crate::core::resources::inputs::inputs_controller::InputsController;
+use crate::core::resources::rewind::Rewind;
 use crate::core::resources::time::Timers;
 use crate::core::resources::window::Window;
 use crate::core::scene::SceneController;
@@ -167,6 +170,28 @@ impl GameData {
             .expect("The engine is missing the mandatory focus manager resource")
     }
 
+    /// Spawns the prefab `name` (see `AssetManager`) at the given transform and returns the root entity
+    pub fn spawn_prefab(&mut self, name: &str, transform: Transform) -> Entity {
+        spawn_prefab(self, name, transform)
+    }
+
+    /// Spawns the prefab `name` (see `AssetManager`) at the given transform, using per instance overrides
+    pub fn spawn_prefab_with_overrides(&mut self, name: &str, transform: Transform, overrides: PrefabOverrides) -> Entity {
+        spawn_prefab_with_overrides(self, name, transform, overrides)
+    }
+
+    /// Restores the state of the `Rewindable` entities as it was `n_steps` fixed updates ago, or as old
+    /// as the `Rewind` buffer allows. Returns the number of steps actually rewound.
+    pub fn rewind(&mut self, n_steps: usize) -> usize {
+        let (world, resources, commands) = self.split_with_command();
+        let rewound = resources
+            .get_resource_mut::<Rewind>()
+            .expect("The engine is missing the mandatory rewind resource")
+            .restore(world, commands, n_steps);
+        self.apply_commands();
+        rewound
+    }
+
     pub(crate) fn has_camera(&self)-> bool{
         self.subworld.query::<&Camera>().iter().count() > 0
     }
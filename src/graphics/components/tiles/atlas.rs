@@ -12,17 +12,24 @@ pub mod importer {
 
     use crate::core::components::maths::transform::Transform;
     use crate::core::resources::asset_manager::{AssetRef, AssetType};
+    use crate::core::resources::tile_object_registry::spawn_tile_objects;
     use crate::core::world::{GameData, Resources};
     use crate::graphics::components::animations::{Animation, AnimationModifier};
     use crate::graphics::components::material::Material;
     use crate::graphics::components::tiles::atlas::data::{TilemapAtlas, TilesetAtlas};
+    use crate::graphics::components::tiles::layer::LayerSettings;
+    use crate::graphics::components::tiles::tiled::{self, TiledTileset};
     use crate::graphics::components::tiles::SPRITE_ANIMATION_PRELOAD;
-    use crate::graphics::components::tiles::tilemap::{TileInfos, Tilemap, TilemapInfo, TilemapType};
+    use crate::graphics::components::tiles::tilemap::{TileInfos, Tilemap, TilemapInfo, TilemapMetadata, TilemapType};
     use crate::graphics::components::tiles::tileset::Tileset;
     use crate::utils::maths::Dimensions;
 
-    /// Import a tilemap from a .scion format located at `path`, into a TilemapAtlas
+    /// Import a tilemap from a .scion format located at `path`, into a TilemapAtlas.
+    /// Tiled maps (.tmx, .tmj) are imported too, without their tilesets (see `tiled::import_tiled_map`)
     pub fn import_tilemap(path: &str) -> TilemapAtlas {
+        if tiled::is_tiled_map(path) {
+            return import_tiled_tilemap(path).0;
+        }
         match crate::utils::file::read_file(Path::new(path)) {
             Ok(file) => {
                 let mut tilemap: TilemapAtlas = serde_json::from_slice(file.as_slice()).expect(format!("Error while reading file at path {:?}", path).as_str());
@@ -38,8 +45,14 @@ pub mod importer {
         }
     }
 
-    /// Import a tileset from a .scion format located at `path`, into a TilesetAtlas
+    /// Import a tileset from a .scion format located at `path`, into a TilesetAtlas.
+    /// Tiled tilesets (.tsx, .tsj) are imported too
     pub fn import_tileset(path: &str) -> TilesetAtlas {
+        if tiled::is_tiled_tileset(path) {
+            return tiled::import_tiled_tileset(path)
+                .unwrap_or_else(|e| panic!("Error while importing the Tiled tileset at path {:?} : {:?}", path, e))
+                .atlas;
+        }
         match crate::utils::file::read_file(Path::new(path)) {
             Ok(file) => {
                 let tileset: TilesetAtlas = serde_json::from_slice(file.as_slice()).expect(format!("Error while reading file at path {:?}", path).as_str());
@@ -56,33 +69,49 @@ pub mod importer {
 
     /// Load a tilemap into the world.
     /// To use this function, you need to have an entry into the registry for the `AssetType::Tilemap(name)` (see `AssetManager`)
-    /// You also need to have an entry in the registry for each AssetType::Tileset used in the tilemap
-    /// Scion will load the tilesets into the asset manager or reuse them if they exist
+    /// You also need to have an entry in the registry for each AssetType::Tileset used in the tilemap, except for
+    /// Tiled maps (.tmx, .tmj) which load their own tilesets
+    /// Scion will load the tilesets into the asset manager or reuse them if they exist. Tiles of the layers are
+    /// global tile ids, the tiles of the tilesets being numbered one tileset after the other.
+    /// Objects of the tilemap whose class is registered in the `TileObjectRegistry` are spawned as children of the tilemap
     pub fn load_tilemap(data: &mut GameData, name: &str, tilemap_transform: Transform) -> (TilemapAtlas, Entity) {
         let (subworld, resources) = data.split();
 
         let tilemap_path = resources.assets().get_atlas_path_for_asset_type(AssetType::Tilemap(name.to_string()));
-        let tilemap = import_tilemap(&tilemap_path);
-
-        if tilemap.tilesets.len() > 1 {
-            panic!("Multiple tilesets not yet implemented !");
-        }
+        let tilemap = if tiled::is_tiled_map(&tilemap_path) {
+            // Tiled maps come with their tilesets, they are registered unless a tileset with the same name already is
+            let (tilemap, tiled_tilesets) = import_tiled_tilemap(&tilemap_path);
+            tiled_tilesets.into_iter().for_each(|t| {
+                resources.assets_mut().register_tileset(t.into_tileset());
+            });
+            tilemap
+        } else {
+            import_tilemap(&tilemap_path)
+        };
 
         let tileset_refs: Vec<AssetRef<Material>> = tilemap.tilesets.iter().map(|t| {
             load_tileset(resources, &t.name)
         }).collect();
 
-        let tileset_ref = tileset_refs.first().unwrap();
-        let asset_manager = resources.assets();
-        let opt_tileset = asset_manager.retrieve_tileset(tileset_ref);
-        let tileset = opt_tileset.unwrap();
-
-        let tilemap_info = create_tilemap_info(&tilemap, tileset_refs, tilemap_transform);
-        let entity = Tilemap::create(tilemap_info, subworld, |p| {
-            let tile = tilemap.tile_at(p);
-            let animation = compute_animation(&tile, tileset);
-            TileInfos::new(tile).with_animation(animation)
-        });
+        let entity = {
+            let asset_manager = resources.assets();
+            let tilesets: Vec<&Tileset> = tileset_refs.iter().map(|r| asset_manager.retrieve_tileset(r).unwrap()).collect();
+
+            let tilemap_info = create_tilemap_info(&tilemap, &tileset_refs, tilemap_transform);
+            Tilemap::create(tilemap_info, subworld, |p| {
+                let tile = tilemap.tile_at(p);
+                let animation = tile.and_then(|tile| {
+                    let (tileset, local_tile) = tilemap.resolve_tile(tile);
+                    compute_animation(&Some(local_tile), tilesets[tileset])
+                });
+                let infos = TileInfos::new(tile).with_animation(animation).with_flip(tilemap.flip_at(p)).with_event(tilemap.event_at(p));
+                match tilemap.pathing_at(p) {
+                    Some(pathing) => infos.with_pathing(pathing),
+                    None => infos,
+                }
+            })
+        };
+        spawn_tile_objects(data, entity, &tilemap.objects);
         (tilemap, entity)
     }
 
@@ -108,7 +137,11 @@ pub mod importer {
         }
     }
 
-    fn load_tileset(resources: &mut Resources, name: &str) -> AssetRef<Material> {
+    fn import_tiled_tilemap(path: &str) -> (TilemapAtlas, Vec<TiledTileset>) {
+        tiled::import_tiled_map(path).unwrap_or_else(|e| panic!("Error while importing the Tiled map at path {:?} : {:?}", path, e))
+    }
+
+    pub(crate) fn load_tileset(resources: &mut Resources, name: &str) -> AssetRef<Material> {
         let existing_ref = resources.assets().retrieve_asset_ref_for_tileset_name(name);
         match existing_ref {
             None => {
@@ -124,21 +157,49 @@ pub mod importer {
         }
     }
 
-    fn create_tilemap_info(tilemap: &TilemapAtlas, mut tileset_refs: Vec<AssetRef<Material>>, tilemap_transform: Transform) -> TilemapInfo {
-        TilemapInfo::new(Dimensions::new(tilemap.width, tilemap.height, tilemap.layers.len()),
-                         tilemap_transform,
-                         tileset_refs.remove(0),
-                         tilemap.tilemap_type.as_ref().unwrap_or(&TilemapType::Standard).clone()) // TODO : When support for multi tileset is developped
+    fn create_tilemap_info(tilemap: &TilemapAtlas, tileset_refs: &[AssetRef<Material>], tilemap_transform: Transform) -> TilemapInfo {
+        let info = TilemapInfo::new(Dimensions::new(tilemap.width, tilemap.height, tilemap.layers.len()),
+                                    tilemap_transform,
+                                    tileset_refs[0],
+                                    tilemap.tilemap_type.as_ref().unwrap_or(&TilemapType::Standard).clone());
+        let info = tilemap.layers.iter().enumerate()
+            .fold(info, |info, (z, layer)| info.with_layer_settings(z, LayerSettings::from_properties(&layer.properties)))
+            .with_metadata(TilemapMetadata::from_atlas(tilemap));
+        tileset_refs.iter().enumerate().skip(1)
+            .fold(info, |info, (index, tileset_ref)| info.with_tileset(*tileset_ref, tilemap.first_tile(index)))
+    }
+}
+
+pub mod exporter {
+    use std::fs;
+    use std::io;
+
+    use log::debug;
+
+    use crate::graphics::components::tiles::atlas::data::TilemapAtlas;
+
+    /// Writes a tilemap to `path` in the .scion format, to be loaded back with `importer::import_tilemap`.
+    /// See `Tilemap::export` to retrieve the `TilemapAtlas` of a tilemap of the world
+    pub fn export_tilemap(tilemap: &TilemapAtlas, path: &str) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(tilemap)?;
+        fs::write(path, json)?;
+        debug!("Tilemap has been exported to path {}", path);
+        Ok(())
     }
 }
 
 pub mod data {
     use std::collections::{HashMap, HashSet};
 
+    use base64::prelude::BASE64_STANDARD;
+    use base64::Engine;
     use serde::{Deserialize, Serialize};
 
     use crate::core::components::maths::coordinates::Coordinates;
-    use crate::graphics::components::tiles::tilemap::TilemapType;
+    use crate::graphics::components::tiles::autotile::TerrainSet;
+    use crate::graphics::components::tiles::generation::wfc::AdjacencyRules;
+    use crate::graphics::components::tiles::sprite::TileFlip;
+    use crate::graphics::components::tiles::tilemap::{TileEvent, TilemapType};
     use crate::graphics::components::tiles::tileset::Tileset;
     use crate::utils::maths::Position;
 
@@ -152,6 +213,12 @@ pub mod data {
         pub(crate) tile_height: usize,
         pub(crate) pathing: Option<HashMap<String, HashSet<usize>>>,
         pub(crate) tiles: HashMap<usize, TileConfig>,
+        /// Autotiling rules of the tileset
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub(crate) terrain_sets: Vec<TerrainSet>,
+        /// Wave Function Collapse rules of the tileset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub(crate) adjacency_rules: Option<AdjacencyRules>,
     }
 
     impl TilesetAtlas {
@@ -165,6 +232,8 @@ pub mod data {
                 texture: texture_path,
                 pathing: self.pathing.unwrap_or_default(),
                 tiles: self.tiles,
+                terrain_sets: self.terrain_sets,
+                adjacency_rules: self.adjacency_rules.map(Box::new),
             }
         }
 
@@ -178,12 +247,19 @@ pub mod data {
     pub struct TileConfig {
         pub(crate) animation: Option<Vec<TileAnimationFrame>>,
         pub(crate) objects: Vec<TileObject>,
+        #[serde(default)]
+        pub(crate) properties: Option<HashMap<String, String>>,
     }
 
     impl TileConfig{
         pub fn objects(&self) -> &Vec<TileObject>{
             &self.objects
         }
+
+        /// Custom property `key` of this tile
+        pub fn property(&self, key: &str) -> Option<&String> {
+            self.properties.as_ref().and_then(|p| p.get(key))
+        }
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -194,14 +270,25 @@ pub mod data {
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct TileObject {
-        class: TileObjectClass,
-        shape_type: TileObjectShapeType,
-        position: Coordinates,
-        polygon: Option<Vec<Coordinates>>,
-        rectangle: Option<TileRectangle>,
+        #[serde(default)]
+        pub(crate) name: Option<String>,
+        pub(crate) class: TileObjectClass,
+        pub(crate) shape_type: TileObjectShapeType,
+        pub(crate) position: Coordinates,
+        pub(crate) polygon: Option<Vec<Coordinates>>,
+        pub(crate) rectangle: Option<TileRectangle>,
+        #[serde(default)]
+        pub(crate) properties: Option<HashMap<String, String>>,
     }
 
     impl TileObject{
+        pub fn get_name(&self) -> Option<&String> {
+            self.name.as_ref()
+        }
+        /// Custom property `key` of this object
+        pub fn get_property(&self, key: &str) -> Option<&String> {
+            self.properties.as_ref().and_then(|p| p.get(key))
+        }
         pub fn get_class(&self) -> &TileObjectClass{
             &self.class
         }
@@ -217,12 +304,15 @@ pub mod data {
         pub fn get_polygon(&self) -> &Vec<Coordinates>{
             self.polygon.as_ref().unwrap()
         }
+        pub fn has_polygon(&self) -> bool{
+            self.polygon.is_some()
+        }
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct TileRectangle {
-        width: f32,
-        height: f32,
+        pub(crate) width: f32,
+        pub(crate) height: f32,
     }
 
     impl TileRectangle {
@@ -246,6 +336,24 @@ pub mod data {
         Custom(String),
     }
 
+    impl TileObjectClass {
+        /// Whether this is the custom class named `name`
+        pub fn is_custom(&self, name: &str) -> bool {
+            matches!(self, TileObjectClass::Custom(class) if class == name)
+        }
+
+        /// Name of this class, as used by the `TileObjectRegistry`
+        pub fn name(&self) -> &str {
+            match self {
+                TileObjectClass::CollisionArea => "CollisionArea",
+                TileObjectClass::Item => "Item",
+                TileObjectClass::Door => "Door",
+                TileObjectClass::Trigger => "Trigger",
+                TileObjectClass::Custom(name) => name,
+            }
+        }
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub enum TileObjectShapeType {
         Polygon,
@@ -264,12 +372,21 @@ pub mod data {
         pub(crate) objects: Vec<TileObject>,
         pub(crate) tilesets: Vec<TiledTilemapTileset>,
         pub(crate) tilemap_type: Option<TilemapType>,
+        /// Pathing forced on tiles, overriding the pathing of their tileset
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub(crate) pathing: Vec<TilePathing>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub(crate) events: Vec<TilemapEvent>,
     }
 
     impl TilemapAtlas{
         pub fn get_objects(&self) -> &Vec<TileObject>{
             &self.objects
         }
+
+        pub fn get_layers(&self) -> &Vec<TilemapLayer> {
+            &self.layers
+        }
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -280,16 +397,77 @@ pub mod data {
         #[serde(skip_deserializing)]
         pub(crate) tiles: Vec<Vec<isize>>,
         pub(crate) properties: HashMap<String, String>,
+        /// Flipped tiles of the layer, the other ones are not flipped
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub(crate) flipped_tiles: Vec<FlippedTile>,
+    }
+
+    impl TilemapLayer {
+        pub fn get_name(&self) -> &str {
+            &self.name
+        }
+
+        pub fn get_properties(&self) -> &HashMap<String, String> {
+            &self.properties
+        }
+
+        /// Tiles of the layer, by line then by column. Negative values are empty tiles
+        pub fn get_tiles(&self) -> &Vec<Vec<isize>> {
+            &self.tiles
+        }
+
+        /// Encodes the tiles of the layer as expected by the .scion format
+        pub(crate) fn encode_tiles(&mut self) {
+            let json = serde_json::to_vec(&self.tiles).expect("Error while encoding the tiles of a layer");
+            self.tiles_encoded = Some(BASE64_STANDARD.encode(json));
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct FlippedTile {
+        pub(crate) x: usize,
+        pub(crate) y: usize,
+        pub(crate) flip: TileFlip,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct TilePathing {
+        pub(crate) position: Position,
+        pub(crate) pathing: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct TilemapEvent {
+        pub(crate) position: Position,
+        pub(crate) event: TileEvent,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct TiledTilemapTileset {
+        /// Position of this tileset in the tilemap's tilesets
         pub(crate) index: usize,
         pub(crate) total_tiles: usize,
         pub(crate) name: String,
     }
 
     impl TilemapAtlas {
+        /// First global tile id of the tileset at `tileset`, the tiles of the tilesets being numbered one tileset after the other
+        pub(crate) fn first_tile(&self, tileset: usize) -> usize {
+            self.tilesets.iter().take(tileset).map(|t| t.total_tiles).sum()
+        }
+
+        /// Resolves a global tile id to the index of its tileset in `tilesets` and the tile id local to this tileset
+        pub(crate) fn resolve_tile(&self, tile: usize) -> (usize, usize) {
+            let mut local_tile = tile;
+            for (index, tileset) in self.tilesets.iter().enumerate() {
+                if local_tile < tileset.total_tiles || index == self.tilesets.len() - 1 {
+                    return (index, local_tile);
+                }
+                local_tile -= tileset.total_tiles;
+            }
+            (0, tile)
+        }
+
         pub(crate) fn tile_at(&self, position: &Position) -> Option<usize> {
             if position.x() > self.width || position.y() > self.height || position.z() > self.layers.len() {
                 panic!("Position of requested tile is not coherent with tilemap informations");
@@ -301,5 +479,44 @@ pub mod data {
                 None
             }
         }
+
+        pub(crate) fn pathing_at(&self, position: &Position) -> Option<String> {
+            self.pathing.iter().find(|p| p.position == *position).map(|p| p.pathing.clone())
+        }
+
+        pub(crate) fn event_at(&self, position: &Position) -> Option<TileEvent> {
+            self.events.iter().find(|e| e.position == *position).map(|e| e.event.clone())
+        }
+
+        pub(crate) fn flip_at(&self, position: &Position) -> TileFlip {
+            self.layers[position.z()]
+                .flipped_tiles
+                .iter()
+                .find(|t| t.x == position.x() && t.y == position.y())
+                .map_or_else(TileFlip::default, |t| t.flip)
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::data::TilemapAtlas;
+
+    #[test]
+    fn global_tiles_are_resolved_to_the_exported_tilesets() {
+        // Tilesets as written by the Tiled exporter : `index` is the position of the tileset, and the global tile ids
+        // of a tileset start after all the tiles of the previous ones
+        let atlas: TilemapAtlas = serde_json::from_str(
+            r#"{"width": 2, "height": 2, "tile_width": 16, "tile_height": 16, "properties": {}, "layers": [], "objects": [],
+               "tilesets": [{"index": 0, "total_tiles": 16, "name": "ground"}, {"index": 1, "total_tiles": 8, "name": "props"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(0, atlas.first_tile(0));
+        assert_eq!(16, atlas.first_tile(1));
+        assert_eq!((0, 0), atlas.resolve_tile(0));
+        assert_eq!((0, 15), atlas.resolve_tile(15));
+        assert_eq!((1, 0), atlas.resolve_tile(16));
+        assert_eq!((1, 7), atlas.resolve_tile(23));
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Terrains of the 8 parts of a tile's border, in the order top, top-right, right, bottom-right,
+/// bottom, bottom-left, left, top-left. `0` means no terrain, `n` is the nth terrain of the terrain set
+pub type TerrainId = [usize; 8];
+
+/// Offsets of the 8 parts of a tile's border, in half tiles from the center of the tile, in the `TerrainId` order
+const PARTS: [(isize, isize); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+
+/// Which parts of the tiles' borders are used to match the tiles of a terrain set
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TerrainSetKind {
+    /// Wang 2-corner tiles : only the 4 corners of the tiles are matched
+    Corner,
+    /// Wang 2-edge tiles : only the 4 edges of the tiles are matched
+    Edge,
+    /// Corners and edges are matched, as in the blob-47 tilesets
+    Blob,
+}
+
+impl TerrainSetKind {
+    fn uses(&self, part: usize) -> bool {
+        match self {
+            TerrainSetKind::Corner => !part.is_multiple_of(2),
+            TerrainSetKind::Edge => part.is_multiple_of(2),
+            TerrainSetKind::Blob => true,
+        }
+    }
+}
+
+/// Autotiling rules of a tileset : each tile of the set declares the terrain of each part of its border,
+/// so that the tile matching the terrains around a position can be picked automatically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainSet {
+    pub(crate) name: String,
+    pub(crate) kind: TerrainSetKind,
+    /// Names of the terrains of this set, the first one having the id 1
+    pub(crate) terrains: Vec<String>,
+    /// Terrains of the border of each tile of this set, tiles being local to the tileset
+    pub(crate) tiles: HashMap<usize, TerrainId>,
+}
+
+impl TerrainSet {
+    pub fn new(name: &str, kind: TerrainSetKind, terrains: Vec<String>) -> Self {
+        Self { name: name.to_string(), kind, terrains, tiles: HashMap::new() }
+    }
+
+    /// Adds a tile to this terrain set, with the terrain ids of its border
+    pub fn with_tile(mut self, tile: usize, terrain_id: TerrainId) -> Self {
+        self.tiles.insert(tile, terrain_id);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> TerrainSetKind {
+        self.kind
+    }
+
+    /// Id of the terrain named `terrain` in this set
+    pub fn terrain(&self, terrain: &str) -> Option<usize> {
+        self.terrains.iter().position(|t| t == terrain).map(|index| index + 1)
+    }
+
+    /// Terrains of the border of `tile`, if it belongs to this set
+    pub fn terrain_id(&self, tile: usize) -> Option<&TerrainId> {
+        self.tiles.get(&tile)
+    }
+
+    /// Tile of this set whose border best matches `wanted`, `None` parts matching any terrain. Tiles matching
+    /// all the wanted parts are picked first, then the ones matching the most parts, then the lowest tile
+    pub(crate) fn best_tile(&self, wanted: &[Option<usize>; 8]) -> Option<usize> {
+        self.tiles
+            .iter()
+            .map(|(tile, id)| {
+                let mismatches = (0..8)
+                    .filter(|part| self.kind.uses(*part))
+                    .filter(|part| wanted[*part].is_some_and(|terrain| terrain != id[*part]))
+                    .count();
+                (mismatches, *tile)
+            })
+            .min()
+            .map(|(_, tile)| tile)
+    }
+
+    /// Wanted border of the tile at `offset` (in tiles) from a tile painted with `terrain`, given its current border.
+    /// Returns `None` if the tile doesn't share any matched part with the painted tile
+    pub(crate) fn wanted_around(&self, current: Option<&TerrainId>, offset: (isize, isize), terrain: usize) -> Option<[Option<usize>; 8]> {
+        let mut wanted = [None; 8];
+        if let Some(current) = current {
+            (0..8).for_each(|part| wanted[part] = Some(current[part]));
+        }
+        let mut shared = false;
+        for (part, (x, y)) in PARTS.iter().enumerate() {
+            // Parts of the painted tile, seen from the tile at `offset`
+            if !self.kind.uses(part) {
+                continue;
+            }
+            let (x, y) = (x - 2 * offset.0, y - 2 * offset.1);
+            if let Some(neighbour_part) = PARTS.iter().position(|p| *p == (x, y)) {
+                wanted[neighbour_part] = Some(terrain);
+                shared = true;
+            }
+        }
+        shared.then_some(wanted)
+    }
+
+    /// Whether `neighbour` can be placed at `offset` (in tiles) from `tile`, the matched parts of their borders they share
+    /// having the same terrains. Tiles not belonging to this set never fit
+    pub(crate) fn fits(&self, tile: usize, offset: (isize, isize), neighbour: usize) -> bool {
+        let (Some(id), Some(neighbour_id)) = (self.tiles.get(&tile), self.tiles.get(&neighbour)) else { return false };
+        PARTS.iter().enumerate().filter(|(part, _)| self.kind.uses(*part)).all(|(part, (x, y))| {
+            match PARTS.iter().position(|p| *p == (x - 2 * offset.0, y - 2 * offset.1)) {
+                Some(neighbour_part) => id[part] == neighbour_id[neighbour_part],
+                None => true,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2-corner set of grass (1) and water (2), the 16 tiles being numbered by their water corners
+    fn corner_set() -> TerrainSet {
+        (0..16).fold(TerrainSet::new("coast", TerrainSetKind::Corner, vec!["grass".to_string(), "water".to_string()]), |set, tile| {
+            let corner = |bit: usize| if tile & (1 << bit) != 0 { 2 } else { 1 };
+            set.with_tile(tile, [0, corner(0), 0, corner(1), 0, corner(2), 0, corner(3)])
+        })
+    }
+
+    #[test]
+    fn neighbours_share_the_corners_of_the_painted_tile() {
+        let set = corner_set();
+        let water = set.terrain("water").unwrap();
+        let grass = set.terrain_id(0);
+
+        // The right neighbour gets water on its top-left and bottom-left corners
+        let wanted = set.wanted_around(grass, (1, 0), water).unwrap();
+        assert_eq!(Some(0b1100), set.best_tile(&wanted));
+        // The bottom-right neighbour only gets water on its top-left corner
+        let wanted = set.wanted_around(grass, (1, 1), water).unwrap();
+        assert_eq!(Some(0b1000), set.best_tile(&wanted));
+        assert_eq!(Some(15), set.best_tile(&set.wanted_around(grass, (0, 0), water).unwrap()));
+        assert!(set.wanted_around(grass, (2, 0), water).is_none());
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use hecs::Entity;
+
+use crate::core::components::maths::hierarchy::Parent;
+use crate::core::components::maths::transform::Transform;
+use crate::core::world::World;
+use crate::graphics::components::tiles::sprite::{Sprite, TileFlip};
+use crate::graphics::components::tiles::tilemap::{TileInfos, Tilemap};
+use crate::graphics::components::tiles::visibility::FogOfWar;
+use crate::graphics::rendering::{Highlight, PivotOffset};
+use crate::utils::maths::Position;
+
+/// Size of the chunks of a chunked tilemap, and how far around the cameras they are loaded
+#[derive(Debug, Clone)]
+pub struct ChunkSettings {
+    pub(crate) chunk_width: usize,
+    pub(crate) chunk_height: usize,
+    pub(crate) load_margin: usize,
+}
+
+impl ChunkSettings {
+    /// Creates chunks of `chunk_width` x `chunk_height` tiles, loaded one chunk around the visible ones
+    pub fn new(chunk_width: usize, chunk_height: usize) -> Self {
+        if chunk_width == 0 || chunk_height == 0 {
+            panic!("The chunks of a tilemap can't be empty");
+        }
+        Self { chunk_width, chunk_height, load_margin: 1 }
+    }
+
+    /// Number of chunks loaded around the visible ones. Chunks are unloaded once they
+    /// are farther than `load_margin + 1` chunks from a camera, to avoid reloading them constantly
+    pub fn with_load_margin(mut self, load_margin: usize) -> Self {
+        self.load_margin = load_margin;
+        self
+    }
+
+    pub fn chunk_width(&self) -> usize {
+        self.chunk_width
+    }
+
+    pub fn chunk_height(&self) -> usize {
+        self.chunk_height
+    }
+
+    pub fn load_margin(&self) -> usize {
+        self.load_margin
+    }
+}
+
+/// Component of a loaded chunk of a chunked tilemap. Its tiles are its children, and are rendered as a single mesh
+#[derive(Debug)]
+pub struct TilemapChunk {
+    pub(crate) tilemap: Entity,
+    pub(crate) chunk: (usize, usize),
+}
+
+impl TilemapChunk {
+    pub fn tilemap(&self) -> Entity {
+        self.tilemap
+    }
+
+    /// Coordinates of this chunk, in chunks
+    pub fn chunk(&self) -> (usize, usize) {
+        self.chunk
+    }
+}
+
+impl PivotOffset for TilemapChunk {}
+
+/// Chunk related data of a chunked tilemap. Tiles, pathing, flips and highlights modified at runtime survive the
+/// unloading of their chunk, while any other state of the tile entities (running animations, components
+/// added to them...) is lost and reset from the tile resolver when the chunk is loaded again
+pub(crate) struct TilemapChunks {
+    pub(crate) settings: ChunkSettings,
+    resolver: Arc<dyn Fn(&Position) -> TileInfos + Send + Sync>,
+    pub(crate) loaded: HashMap<(usize, usize), Entity>,
+    /// Tiles modified with `Tilemap::modify_sprite_tile`, kept when their chunk is unloaded
+    pub(crate) modified_tiles: HashMap<Position, usize>,
+    /// Pathing forced with `Tilemap::modify_pathing`, kept when their chunk is unloaded
+    pub(crate) modified_pathing: HashMap<Position, Option<String>>,
+    /// Flips set with `Sprite::set_flip` that differ from the resolved ones, kept when their chunk is unloaded
+    pub(crate) flips: HashMap<Position, TileFlip>,
+    /// Highlights of the tiles of the unloaded chunks, restored when their chunk is loaded again
+    pub(crate) highlights: HashMap<Position, Highlight>,
+}
+
+impl TilemapChunks {
+    pub(crate) fn new(settings: ChunkSettings, resolver: Arc<dyn Fn(&Position) -> TileInfos + Send + Sync>) -> Self {
+        Self { settings, resolver, loaded: HashMap::default(), modified_tiles: HashMap::default(), modified_pathing: HashMap::default(), flips: HashMap::default(), highlights: HashMap::default() }
+    }
+
+    /// Infos of the tile at `position`, taking into account the modified tiles, pathing and flips
+    pub(crate) fn tile_infos(&self, position: &Position) -> TileInfos {
+        let mut infos = (self.resolver)(position);
+        if let Some(tile_nb) = self.modified_tiles.get(position) {
+            infos.tile_nb = Some(*tile_nb);
+        }
+        if let Some(pathing) = self.modified_pathing.get(position) {
+            infos.pathing_type = pathing.clone();
+        }
+        if let Some(flip) = self.flips.get(position) {
+            infos.flip = *flip;
+        }
+        infos
+    }
+
+    /// Keeps the flip of the tile at `position` when it differs from the resolved one
+    fn keep_flip(&mut self, position: Position, flip: TileFlip) {
+        if (self.resolver)(&position).flip == flip {
+            self.flips.remove(&position);
+        } else {
+            self.flips.insert(position, flip);
+        }
+    }
+
+    /// Range of tiles covered by `chunk`, in x and y, clamped to the tilemap
+    pub(crate) fn chunk_tiles(&self, chunk: (usize, usize), width: usize, height: usize) -> (Range<usize>, Range<usize>) {
+        let start_x = chunk.0 * self.settings.chunk_width;
+        let start_y = chunk.1 * self.settings.chunk_height;
+        (
+            start_x.min(width)..(start_x + self.settings.chunk_width).min(width),
+            start_y.min(height)..(start_y + self.settings.chunk_height).min(height),
+        )
+    }
+
+    /// Chunks covering the tiles from `min` to `max` (in tile coordinates, possibly outside the tilemap),
+    /// extended by `margin` chunks and clamped to the tilemap
+    pub(crate) fn chunks_around(&self, min: (f32, f32), max: (f32, f32), width: usize, height: usize, margin: usize) -> Vec<(usize, usize)> {
+        let chunks_x = width.div_ceil(self.settings.chunk_width) as i64;
+        let chunks_y = height.div_ceil(self.settings.chunk_height) as i64;
+        let to_chunk = |tile: f32, size: usize| (tile.floor() as i64).div_euclid(size as i64);
+        let margin = margin as i64;
+        let start_x = (to_chunk(min.0, self.settings.chunk_width) - margin).max(0);
+        let end_x = (to_chunk(max.0, self.settings.chunk_width) + margin).min(chunks_x - 1);
+        let start_y = (to_chunk(min.1, self.settings.chunk_height) - margin).max(0);
+        let end_y = (to_chunk(max.1, self.settings.chunk_height) + margin).min(chunks_y - 1);
+        (start_x..=end_x).flat_map(|x| (start_y..=end_y).map(move |y| (x as usize, y as usize))).collect()
+    }
+}
+
+/// Loads the chunk `chunk` of the chunked tilemap `tilemap_entity`, creating all its tiles. Does nothing if
+/// the chunk is already loaded
+pub(crate) fn load_chunk(world: &mut impl World, tilemap_entity: Entity, chunk: (usize, usize)) {
+    let (tileset_ref, tiles) = {
+        let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+        let Some(chunks) = tilemap.chunks.as_ref() else { return };
+        if chunks.loaded.contains_key(&chunk) {
+            return;
+        }
+        let (xs, ys) = chunks.chunk_tiles(chunk, tilemap.width(), tilemap.height());
+        let mut tiles = Vec::new();
+        for x in xs {
+            for y in ys.clone() {
+                for z in 0..tilemap.depth() {
+                    let position = Position::new(x, y, z);
+                    let infos = chunks.tile_infos(&position);
+                    tiles.push((position, infos));
+                }
+            }
+        }
+        (*tilemap.tileset_ref(), tiles)
+    };
+
+    let chunk_entity = world.push((
+        TilemapChunk { tilemap: tilemap_entity, chunk },
+        Parent::new(tilemap_entity),
+        Transform::default(),
+        tileset_ref,
+    ));
+    let mut highlights = Vec::new();
+    for (position, infos) in tiles {
+        Tilemap::spawn_tile(world, tilemap_entity, Some(chunk_entity), position.clone(), infos);
+        let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+        if let Some(highlight) = tilemap.chunks.as_mut().and_then(|chunks| chunks.highlights.remove(&position)) {
+            highlights.extend(tilemap.tile_entity(&position).map(|tile| (tile, highlight)));
+        }
+    }
+    for (tile, highlight) in highlights {
+        if let Ok(sprite) = world.entry_mut::<&mut Sprite>(tile) {
+            sprite.set_highlight(Some(highlight));
+        }
+    }
+    // The fog of war has to be applied again on the new tiles
+    if let Ok(fog) = world.entry_mut::<&mut FogOfWar>(tilemap_entity) {
+        fog.applied.clear();
+        fog.dirty = true;
+    }
+    let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+    if let Some(chunks) = tilemap.chunks.as_mut() {
+        chunks.loaded.insert(chunk, chunk_entity);
+    }
+}
+
+/// Unloads the chunk `chunk` of the chunked tilemap `tilemap_entity`, removing all its tiles from the world.
+/// The flips and highlights of the tiles are kept, without the tint of the fog of war
+pub(crate) fn unload_chunk(world: &mut impl World, tilemap_entity: Entity, chunk: (usize, usize)) {
+    let (chunk_entity, tiles) = {
+        let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+        let (width, height, depth) = (tilemap.width(), tilemap.height(), tilemap.depth());
+        let Some(chunks) = tilemap.chunks.as_mut() else { return };
+        let Some(chunk_entity) = chunks.loaded.remove(&chunk) else { return };
+        let (xs, ys) = chunks.chunk_tiles(chunk, width, height);
+        let mut tiles = Vec::new();
+        for x in xs {
+            for y in ys.clone() {
+                for z in 0..depth {
+                    let position = Position::new(x, y, z);
+                    if let Some(tile) = tilemap.remove_tile_entity(&position) {
+                        tiles.push((position, tile));
+                    }
+                }
+            }
+        }
+        (chunk_entity, tiles)
+    };
+    let mut highlights = Vec::new();
+    let mut flips = Vec::new();
+    for (position, tile) in tiles {
+        let fog_hidden = world.entry_mut::<&mut FogOfWar>(tilemap_entity).ok().and_then(|fog| fog.hidden_highlights.remove(&tile));
+        let (fog_tinted, sprite_highlight, flip) = world
+            .entry::<&Sprite>(tile)
+            .ok()
+            .and_then(|mut entry| entry.get().map(|sprite| (sprite.is_fog_tinted(), sprite.highlight().cloned(), Some(sprite.flip()))))
+            .unwrap_or_default();
+        flips.extend(flip.map(|flip| (position.clone(), flip)));
+        let highlight = if fog_tinted { fog_hidden.flatten() } else { sprite_highlight };
+        highlights.extend(highlight.map(|highlight| (position, highlight)));
+        let _r = world.remove(tile);
+    }
+    let _r = world.remove(chunk_entity);
+    if let Some(chunks) = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap().chunks.as_mut() {
+        chunks.highlights.extend(highlights);
+        flips.into_iter().for_each(|(position, flip)| chunks.keep_flip(position, flip));
+    }
+}
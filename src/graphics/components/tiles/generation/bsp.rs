@@ -0,0 +1,173 @@
+use crate::graphics::components::tiles::generation::{GenerationRng, Grid};
+
+/// Cell of a generated dungeon
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DungeonCell {
+    Wall,
+    Room,
+    Corridor,
+}
+
+/// Rectangular room of a generated dungeon, in cells
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Room {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Room {
+    pub fn center(&self) -> (usize, usize) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Dungeon generated by a `BspDungeon`
+#[derive(Debug, Clone)]
+pub struct Dungeon {
+    grid: Grid<DungeonCell>,
+    rooms: Vec<Room>,
+}
+
+impl Dungeon {
+    pub fn grid(&self) -> &Grid<DungeonCell> {
+        &self.grid
+    }
+
+    pub fn rooms(&self) -> &Vec<Room> {
+        &self.rooms
+    }
+}
+
+/// Room and corridor dungeon generator. The map is recursively split in two (binary space partitioning),
+/// a room is carved in each final part, and the rooms of both sides of each split are joined by a corridor
+#[derive(Debug, Clone)]
+pub struct BspDungeon {
+    width: usize,
+    height: usize,
+    min_room_size: usize,
+    max_depth: usize,
+}
+
+impl BspDungeon {
+    /// Creates a generator of `width` x `height` dungeons, with rooms of at least 4x4 cells and 4 levels of splits
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, min_room_size: 4, max_depth: 4 }
+    }
+
+    pub fn with_min_room_size(mut self, min_room_size: usize) -> Self {
+        self.min_room_size = min_room_size.max(1);
+        self
+    }
+
+    /// Maximum number of successive splits, the dungeon having at most 2^max_depth rooms
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn generate(&self, seed: u64) -> Dungeon {
+        if self.width < self.min_room_size + 2 || self.height < self.min_room_size + 2 {
+            panic!("The dungeon is too small for its rooms");
+        }
+        let mut rng = GenerationRng::new(seed);
+        let mut dungeon = Dungeon { grid: Grid::new(self.width, self.height, DungeonCell::Wall), rooms: Vec::new() };
+        let root = Room { x: 0, y: 0, width: self.width, height: self.height };
+        self.split(root, 0, &mut rng, &mut dungeon);
+        dungeon
+    }
+
+    /// Splits `area` or carves a room in it, and returns the index of a room of `area`
+    fn split(&self, area: Room, depth: usize, rng: &mut GenerationRng, dungeon: &mut Dungeon) -> usize {
+        // Each part keeps room for a room and the walls around it
+        let min_part = self.min_room_size + 2;
+        let vertical = area.width >= 2 * min_part;
+        let horizontal = area.height >= 2 * min_part;
+        if depth == self.max_depth || (!vertical && !horizontal) {
+            return carve_room(area, self.min_room_size, rng, dungeon);
+        }
+
+        let (first, second) = if vertical && (!horizontal || rng.chance(0.5)) {
+            let cut = rng.range(min_part..area.width - min_part + 1);
+            (Room { width: cut, ..area }, Room { x: area.x + cut, width: area.width - cut, ..area })
+        } else {
+            let cut = rng.range(min_part..area.height - min_part + 1);
+            (Room { height: cut, ..area }, Room { y: area.y + cut, height: area.height - cut, ..area })
+        };
+        let first = self.split(first, depth + 1, rng, dungeon);
+        let second = self.split(second, depth + 1, rng, dungeon);
+        let (from, to) = (dungeon.rooms[first].center(), dungeon.rooms[second].center());
+        carve_corridor(from, to, rng.chance(0.5), &mut dungeon.grid);
+        if rng.chance(0.5) { first } else { second }
+    }
+}
+
+fn carve_room(area: Room, min_size: usize, rng: &mut GenerationRng, dungeon: &mut Dungeon) -> usize {
+    let width = rng.range(min_size..area.width - 1);
+    let height = rng.range(min_size..area.height - 1);
+    let room = Room {
+        x: area.x + 1 + rng.range(0..area.width - 1 - width),
+        y: area.y + 1 + rng.range(0..area.height - 1 - height),
+        width,
+        height,
+    };
+    for y in room.y..room.y + room.height {
+        for x in room.x..room.x + room.width {
+            dungeon.grid.set(x, y, DungeonCell::Room);
+        }
+    }
+    dungeon.rooms.push(room);
+    dungeon.rooms.len() - 1
+}
+
+/// Carves an L shaped corridor between two cells, through the walls only
+fn carve_corridor(from: (usize, usize), to: (usize, usize), horizontal_first: bool, grid: &mut Grid<DungeonCell>) {
+    let corner = if horizontal_first { (to.0, from.1) } else { (from.0, to.1) };
+    for (start, end) in [(from, corner), (corner, to)] {
+        for x in start.0.min(end.0)..=start.0.max(end.0) {
+            for y in start.1.min(end.1)..=start.1.max(end.1) {
+                if grid.get(x, y) == Some(&DungeonCell::Wall) {
+                    grid.set(x, y, DungeonCell::Corridor);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dungeon_rooms_are_separate_and_connected() {
+        let generator = BspDungeon::new(60, 40);
+        let dungeon = generator.generate(7);
+        assert_eq!(dungeon.rooms(), generator.generate(7).rooms());
+        assert!(dungeon.rooms().len() > 1);
+        for (index, room) in dungeon.rooms().iter().enumerate() {
+            assert!(room.width >= 4 && room.height >= 4);
+            assert!(dungeon.rooms().iter().skip(index + 1).all(|other| !(0..60).any(|x| (0..40).any(|y| room.contains(x, y) && other.contains(x, y)))));
+        }
+
+        // Every walkable cell is reached from the first room
+        let grid = dungeon.grid();
+        let walkable = |x: usize, y: usize| grid.get(x, y).is_some_and(|c| *c != DungeonCell::Wall);
+        let mut reached = Grid::new(60, 40, false);
+        let mut stack = vec![dungeon.rooms()[0].center()];
+        while let Some((x, y)) = stack.pop() {
+            if !walkable(x, y) || *reached.get(x, y).unwrap() {
+                continue;
+            }
+            reached.set(x, y, true);
+            stack.extend([(x + 1, y), (x, y + 1)]);
+            stack.extend(x.checked_sub(1).map(|x| (x, y)));
+            stack.extend(y.checked_sub(1).map(|y| (x, y)));
+        }
+        assert!((0..60).all(|x| (0..40).all(|y| walkable(x, y) == *reached.get(x, y).unwrap())));
+    }
+}
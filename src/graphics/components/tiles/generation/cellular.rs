@@ -0,0 +1,90 @@
+use crate::graphics::components::tiles::generation::{GenerationRng, Grid};
+
+/// Cave generator : the map is randomly filled with walls, then smoothed by a cellular automaton.
+/// A cell becomes a wall when more than 4 of its 8 neighbours are walls, and a floor when less than 4 are.
+/// Cells outside the map count as walls, so that caves are closed
+#[derive(Debug, Clone)]
+pub struct CellularCaves {
+    width: usize,
+    height: usize,
+    fill_probability: f32,
+    iterations: usize,
+}
+
+impl CellularCaves {
+    /// Creates a generator of `width` x `height` caves, initially filled with 45% of walls and smoothed 5 times
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, fill_probability: 0.45, iterations: 5 }
+    }
+
+    /// Probability for each cell to initially be a wall
+    pub fn with_fill_probability(mut self, fill_probability: f32) -> Self {
+        self.fill_probability = fill_probability;
+        self
+    }
+
+    /// Number of smoothing steps of the automaton
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Generates the caves, `true` cells being walls
+    pub fn generate(&self, seed: u64) -> Grid<bool> {
+        let mut rng = GenerationRng::new(seed);
+        let mut grid = Grid::new(self.width, self.height, true);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let border = x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height;
+                grid.set(x, y, border || rng.chance(self.fill_probability));
+            }
+        }
+
+        for _ in 0..self.iterations {
+            let previous = grid.clone();
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    match walls_around(&previous, x, y) {
+                        walls if walls > 4 => grid.set(x, y, true),
+                        walls if walls < 4 => grid.set(x, y, false),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        grid
+    }
+}
+
+fn walls_around(grid: &Grid<bool>, x: usize, y: usize) -> usize {
+    let mut walls = 0;
+    for dy in -1..=1isize {
+        for dx in -1..=1isize {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let neighbour = x.checked_add_signed(dx).zip(y.checked_add_signed(dy)).and_then(|(nx, ny)| grid.get(nx, ny));
+            if neighbour.copied().unwrap_or(true) {
+                walls += 1;
+            }
+        }
+    }
+    walls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caves_are_reproducible_and_closed() {
+        let caves = CellularCaves::new(40, 30);
+        let grid = caves.generate(42);
+        assert_eq!(grid, caves.generate(42));
+        assert_ne!(grid, caves.generate(43));
+
+        assert!((0..40).all(|x| *grid.get(x, 0).unwrap() && *grid.get(x, 29).unwrap()));
+        assert!((0..30).all(|y| *grid.get(0, y).unwrap() && *grid.get(39, y).unwrap()));
+        assert!(grid.cells().iter().any(|wall| !wall));
+    }
+}
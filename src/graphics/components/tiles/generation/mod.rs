@@ -0,0 +1,104 @@
+//! Procedural generation of tilemaps : cellular automata caves, BSP dungeons, noise height maps and
+//! Wave Function Collapse. Every generator produces a [`Grid`] from a seed, the same seed always giving the same
+//! grid, which is then turned into the tiles of a tilemap with [`Grid::tile_resolver`].
+use std::ops::Range;
+
+use crate::graphics::components::tiles::tilemap::TileInfos;
+use crate::utils::maths::Position;
+
+pub mod bsp;
+pub mod cellular;
+pub mod noise;
+pub mod wfc;
+
+/// Seeded pseudo random number generator used by the generators (SplitMix64)
+#[derive(Debug, Clone)]
+pub struct GenerationRng {
+    state: u64,
+}
+
+impl GenerationRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Random number in `range`, which must not be empty
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "Can't pick a number in an empty range");
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+
+    /// Returns `true` with the given probability
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+/// Result of a generator : a value for each cell of a `width` x `height` map
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self { width, height, cells: vec![value; width * height] }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.cells[y * self.width + x])
+    }
+
+    /// Replaces the value of a cell, cells outside the grid being ignored
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = value;
+        }
+    }
+
+    /// Values of the cells, line by line
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Tile resolver for `Tilemap::create`, placing on the layer `layer` the tile given by `to_tile` for each cell.
+    /// Other layers and positions outside the grid are empty
+    pub fn tile_resolver<'a>(&'a self, layer: usize, to_tile: impl Fn(&T) -> Option<usize> + 'a) -> impl Fn(&Position) -> TileInfos + 'a {
+        move |position| {
+            let tile = match position.z() == layer {
+                true => self.get(position.x(), position.y()).and_then(&to_tile),
+                false => None,
+            };
+            TileInfos::new(tile)
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::graphics::components::tiles::generation::{GenerationRng, Grid};
+
+/// Height map generator, summing several octaves of Perlin noise. Heights are normalized in [0, 1]
+/// and can be turned into tiles with `Grid::to_tiles`
+#[derive(Debug, Clone)]
+pub struct NoiseMap {
+    width: usize,
+    height: usize,
+    scale: f32,
+    octaves: usize,
+    persistence: f32,
+}
+
+impl NoiseMap {
+    /// Creates a generator of `width` x `height` height maps, with 4 octaves of noise whose features are 16 cells wide
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, scale: 16., octaves: 4, persistence: 0.5 }
+    }
+
+    /// Size, in cells, of the features of the first octave
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(f32::EPSILON);
+        self
+    }
+
+    /// Number of noise layers summed, each one having features twice smaller than the previous one
+    pub fn with_octaves(mut self, octaves: usize) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    /// Amplitude of each octave relative to the previous one
+    pub fn with_persistence(mut self, persistence: f32) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    pub fn generate(&self, seed: u64) -> Grid<f32> {
+        let perlin = Perlin::new(seed);
+        let mut grid = Grid::new(self.width, self.height, 0.);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (mut value, mut amplitude, mut frequency, mut total) = (0., 1., 1. / self.scale, 0.);
+                for _ in 0..self.octaves {
+                    value += perlin.noise(x as f32 * frequency, y as f32 * frequency) * amplitude;
+                    total += amplitude;
+                    amplitude *= self.persistence;
+                    frequency *= 2.;
+                }
+                grid.set(x, y, (value / total * 0.5 + 0.5).clamp(0., 1.));
+            }
+        }
+        grid
+    }
+}
+
+impl Grid<f32> {
+    /// Maps each height to a tile, using `thresholds` sorted by increasing height : a cell gets the tile of the first
+    /// threshold above its height, or the tile of the last threshold if none is
+    pub fn to_tiles(&self, thresholds: &[(f32, usize)]) -> Grid<usize> {
+        assert!(!thresholds.is_empty(), "At least one threshold is needed to map heights to tiles");
+        self.map(|height| thresholds.iter().find(|(threshold, _)| height < threshold).unwrap_or(&thresholds[thresholds.len() - 1]).1)
+    }
+}
+
+/// Classic 2D Perlin noise, with a permutation shuffled from the seed
+struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    fn new(seed: u64) -> Self {
+        let mut rng = GenerationRng::new(seed);
+        let mut values: Vec<u8> = (0..=255).collect();
+        for i in (1..values.len()).rev() {
+            values.swap(i, rng.range(0..i + 1));
+        }
+        let mut permutation = [0; 512];
+        (0..512).for_each(|i| permutation[i] = values[i % 256]);
+        Self { permutation }
+    }
+
+    /// Noise at the given point, within [-1, 1]
+    fn noise(&self, x: f32, y: f32) -> f32 {
+        let (cell_x, cell_y) = (x.floor() as i64 & 255, y.floor() as i64 & 255);
+        let (x, y) = (x - x.floor(), y - y.floor());
+        let hash = |i: i64, j: i64| self.permutation[self.permutation[i as usize] as usize + j as usize];
+        let (u, v) = (fade(x), fade(y));
+        let bottom = lerp(u, gradient(hash(cell_x, cell_y), x, y), gradient(hash(cell_x + 1, cell_y), x - 1., y));
+        let top = lerp(u, gradient(hash(cell_x, cell_y + 1), x, y - 1.), gradient(hash(cell_x + 1, cell_y + 1), x - 1., y - 1.));
+        lerp(v, bottom, top)
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn gradient(hash: u8, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heights_are_reproducible_and_mapped_to_tiles() {
+        let map = NoiseMap::new(32, 32).with_scale(8.);
+        let heights = map.generate(3);
+        assert_eq!(heights, map.generate(3));
+        assert_ne!(heights, map.generate(4));
+        assert!(heights.cells().iter().all(|h| (0. ..=1.).contains(h)));
+
+        let tiles = heights.to_tiles(&[(0.4, 0), (0.6, 1), (1., 2)]);
+        for (height, tile) in heights.cells().iter().zip(tiles.cells()) {
+            assert_eq!(*tile, if *height < 0.4 { 0 } else if *height < 0.6 { 1 } else { 2 });
+        }
+    }
+}
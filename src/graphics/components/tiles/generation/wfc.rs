@@ -0,0 +1,263 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::graphics::components::tiles::autotile::TerrainSet;
+use crate::graphics::components::tiles::generation::{GenerationRng, Grid};
+use crate::graphics::components::tiles::tileset::Tileset;
+
+/// Side of a tile on which a neighbour is placed
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    /// Offset, in tiles, of the neighbour placed in this direction
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+}
+
+/// Tiles allowed next to each other, used by `WaveFunctionCollapse`. Rules can be declared in a tileset atlas,
+/// or derived from its terrain sets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdjacencyRules {
+    rules: BTreeSet<(usize, Direction, usize)>,
+    /// Relative frequency of the tiles, 1.0 when not set
+    #[serde(default)]
+    weights: BTreeMap<usize, f32>,
+}
+
+impl AdjacencyRules {
+    /// Allows `neighbour` in the `direction` of `tile`, and so `tile` in the opposite direction of `neighbour`
+    pub fn with_rule(mut self, tile: usize, direction: Direction, neighbour: usize) -> Self {
+        self.rules.insert((tile, direction, neighbour));
+        self.rules.insert((neighbour, direction.opposite(), tile));
+        self
+    }
+
+    pub fn with_weight(mut self, tile: usize, weight: f32) -> Self {
+        self.weights.insert(tile, weight.max(0.));
+        self
+    }
+
+    /// Rules allowing two tiles of `terrain_set` next to each other when the border parts they share have the same terrains
+    pub fn from_terrain_set(terrain_set: &TerrainSet) -> Self {
+        let tiles: BTreeSet<usize> = terrain_set.tiles.keys().copied().collect();
+        let mut rules = AdjacencyRules::default();
+        for tile in tiles.iter() {
+            for neighbour in tiles.iter() {
+                for direction in Direction::ALL {
+                    if terrain_set.fits(*tile, direction.offset(), *neighbour) {
+                        rules.rules.insert((*tile, direction, *neighbour));
+                    }
+                }
+            }
+        }
+        rules
+    }
+
+    /// Rules declared for `tileset`, or derived from its terrain sets if it has none
+    pub fn from_tileset(tileset: &Tileset) -> Self {
+        if let Some(rules) = &tileset.adjacency_rules {
+            return rules.as_ref().clone();
+        }
+        tileset.terrain_sets.iter().fold(AdjacencyRules::default(), |mut rules, terrain_set| {
+            rules.rules.extend(AdjacencyRules::from_terrain_set(terrain_set).rules);
+            rules
+        })
+    }
+
+    /// Tiles appearing in the rules
+    pub fn tiles(&self) -> BTreeSet<usize> {
+        self.rules.iter().flat_map(|(tile, _, neighbour)| [*tile, *neighbour]).collect()
+    }
+
+    pub fn allows(&self, tile: usize, direction: Direction, neighbour: usize) -> bool {
+        self.rules.contains(&(tile, direction, neighbour))
+    }
+
+    pub fn weight(&self, tile: usize) -> f32 {
+        self.weights.get(&tile).copied().unwrap_or(1.)
+    }
+}
+
+/// Wave Function Collapse generator : each cell starts with all the tiles of the rules possible, then the most
+/// constrained cell is repeatedly set to a random tile, and the tiles not allowed next to it are removed from its neighbours.
+/// When a cell ends up without any possible tile, the generation starts again, up to a maximum number of attempts
+#[derive(Debug, Clone)]
+pub struct WaveFunctionCollapse {
+    width: usize,
+    height: usize,
+    rules: AdjacencyRules,
+    fixed_tiles: Vec<(usize, usize, usize)>,
+    max_attempts: usize,
+}
+
+impl WaveFunctionCollapse {
+    /// Creates a generator of `width` x `height` maps following `rules`, trying 10 times before giving up
+    pub fn new(width: usize, height: usize, rules: AdjacencyRules) -> Self {
+        Self { width, height, rules, fixed_tiles: Vec::new(), max_attempts: 10 }
+    }
+
+    /// Forces the tile of the cell `x`, `y`
+    pub fn with_fixed_tile(mut self, x: usize, y: usize, tile: usize) -> Self {
+        self.fixed_tiles.push((x, y, tile));
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Generates a map, or `None` if no attempt satisfied the rules
+    pub fn generate(&self, seed: u64) -> Option<Grid<usize>> {
+        let tiles: Vec<usize> = self.rules.tiles().into_iter().collect();
+        if tiles.is_empty() || self.fixed_tiles.iter().any(|(_, _, tile)| !tiles.contains(tile)) {
+            return None;
+        }
+        let mut rng = GenerationRng::new(seed);
+        (0..self.max_attempts).find_map(|_| self.attempt(&tiles, &mut rng))
+    }
+
+    fn attempt(&self, tiles: &[usize], rng: &mut GenerationRng) -> Option<Grid<usize>> {
+        let mut wave = vec![vec![true; tiles.len()]; self.width * self.height];
+        for (x, y, tile) in self.fixed_tiles.iter().filter(|(x, y, _)| *x < self.width && *y < self.height) {
+            wave[y * self.width + x].iter_mut().enumerate().for_each(|(index, possible)| *possible &= tiles[index] == *tile);
+        }
+        // Tiles not allowed in any direction, or next to the fixed tiles, are removed before any choice
+        if !self.propagate(&mut wave, tiles, (0..self.width * self.height).collect()) {
+            return None;
+        }
+
+        loop {
+            let counts: Vec<usize> = wave.iter().map(|cell| cell.iter().filter(|p| **p).count()).collect();
+            let Some(min) = counts.iter().filter(|count| **count > 1).min() else { break };
+            let candidates: Vec<usize> = (0..counts.len()).filter(|cell| counts[*cell] == *min).collect();
+            let cell = candidates[rng.range(0..candidates.len())];
+
+            let possible: Vec<usize> = (0..tiles.len()).filter(|index| wave[cell][*index]).collect();
+            let total: f32 = possible.iter().map(|index| self.rules.weight(tiles[*index])).sum();
+            let mut pick = rng.next_f32() * total;
+            let chosen = *possible
+                .iter()
+                .find(|index| {
+                    pick -= self.rules.weight(tiles[**index]);
+                    pick < 0.
+                })
+                .unwrap_or(&possible[possible.len() - 1]);
+            wave[cell].iter_mut().enumerate().for_each(|(index, p)| *p = index == chosen);
+            if !self.propagate(&mut wave, tiles, vec![cell]) {
+                return None;
+            }
+        }
+
+        let mut grid = Grid::new(self.width, self.height, 0);
+        for (cell, possible) in wave.iter().enumerate() {
+            grid.set(cell % self.width, cell / self.width, tiles[possible.iter().position(|p| *p)?]);
+        }
+        Some(grid)
+    }
+
+    /// Removes the tiles not allowed anymore around the `changed` cells, returning `false` if a cell has no possible tile left
+    fn propagate(&self, wave: &mut [Vec<bool>], tiles: &[usize], changed: Vec<usize>) -> bool {
+        let mut stack = changed;
+        while let Some(cell) = stack.pop() {
+            let (x, y) = (cell % self.width, cell / self.width);
+            for direction in Direction::ALL {
+                let (dx, dy) = direction.offset();
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else { continue };
+                if nx >= self.width || ny >= self.height {
+                    continue;
+                }
+                let neighbour = ny * self.width + nx;
+                let mut updated = false;
+                for index in 0..tiles.len() {
+                    let allowed = (0..tiles.len()).any(|t| wave[cell][t] && self.rules.allows(tiles[t], direction, tiles[index]));
+                    if wave[neighbour][index] && !allowed {
+                        wave[neighbour][index] = false;
+                        updated = true;
+                    }
+                }
+                if updated {
+                    if !wave[neighbour].iter().any(|p| *p) {
+                        return false;
+                    }
+                    stack.push(neighbour);
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::components::tiles::autotile::TerrainSetKind;
+
+    fn coast_rules() -> AdjacencyRules {
+        // 0 is grass, 1 sand and 2 water : sand is needed between grass and water
+        [(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)].iter().fold(AdjacencyRules::default(), |rules, (tile, neighbour)| {
+            Direction::ALL.iter().fold(rules, |rules, direction| rules.with_rule(*tile, *direction, *neighbour))
+        })
+    }
+
+    #[test]
+    fn generated_maps_follow_the_rules() {
+        let generator = WaveFunctionCollapse::new(12, 10, coast_rules()).with_fixed_tile(0, 0, 0).with_fixed_tile(11, 9, 2);
+        let grid = generator.generate(5).unwrap();
+        assert_eq!(Some(grid.clone()), generator.generate(5));
+        assert_eq!((Some(&0), Some(&2)), (grid.get(0, 0), grid.get(11, 9)));
+        for y in 0..10 {
+            for x in 0..12 {
+                let tile = *grid.get(x, y).unwrap();
+                assert!(grid.get(x + 1, y).is_none_or(|right| coast_rules().allows(tile, Direction::Right, *right)));
+                assert!(grid.get(x, y + 1).is_none_or(|down| coast_rules().allows(tile, Direction::Down, *down)));
+            }
+        }
+
+        // Tiles can't be stacked vertically, so no map can be generated
+        let horizontal = AdjacencyRules::default().with_rule(0, Direction::Right, 0);
+        assert!(WaveFunctionCollapse::new(2, 2, horizontal).generate(5).is_none());
+    }
+
+    #[test]
+    fn rules_are_derived_from_the_terrain_sets() {
+        // Grass (1) on the left and water (2) on the right of tile 2
+        let terrain_set = TerrainSet::new("coast", TerrainSetKind::Corner, vec!["grass".to_string(), "water".to_string()])
+            .with_tile(0, [1; 8])
+            .with_tile(1, [2; 8])
+            .with_tile(2, [1, 2, 2, 2, 1, 1, 1, 1]);
+        let tileset = Tileset::new("coast".to_string(), "coast.png".to_string(), 3, 1, 16, 16).with_terrain_set(terrain_set);
+        let rules = AdjacencyRules::from_tileset(&tileset);
+
+        assert!(rules.allows(0, Direction::Right, 2));
+        assert!(rules.allows(2, Direction::Right, 1));
+        assert!(rules.allows(2, Direction::Up, 2));
+        assert!(!rules.allows(2, Direction::Right, 0));
+        assert!(!rules.allows(0, Direction::Up, 1));
+        assert!(rules.allows(1, Direction::Left, 2));
+    }
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+/// Axis along which every other line of tiles is shifted by half a tile
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum StaggerAxis {
+    /// Columns are shifted vertically. Hexagons of such tilemaps are flat-topped
+    X,
+    /// Rows are shifted horizontally. Hexagons of such tilemaps are pointy-topped
+    Y,
+}
+
+/// Which lines of tiles are shifted along the `StaggerAxis`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum StaggerIndex {
+    Odd,
+    Even,
+}
+
+/// Grid of a hexagonal or staggered isometric tilemap, in Tiled's offset coordinates : every other line of tiles
+/// is shifted by half a tile. Computations are done as if rows were shifted, coordinates being swapped otherwise
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StaggeredGrid {
+    pub(crate) axis: StaggerAxis,
+    pub(crate) index: StaggerIndex,
+    /// Length of the flat side of the hexagons, along the stagger axis. `None` for staggered isometric tilemaps
+    pub(crate) side_length: Option<f32>,
+}
+
+impl StaggeredGrid {
+    pub fn is_hexagonal(&self) -> bool {
+        self.side_length.is_some()
+    }
+
+    fn swap<T>(&self, (a, b): (T, T)) -> (T, T) {
+        match self.axis {
+            StaggerAxis::X => (b, a),
+            StaggerAxis::Y => (a, b),
+        }
+    }
+
+    fn shifted(&self, line: isize) -> bool {
+        (self.index == StaggerIndex::Odd) == (line.rem_euclid(2) == 1)
+    }
+
+    /// Offsets toward the neighbours of the cell (x, y) : the 6 surrounding hexagons on a hexagonal grid,
+    /// the 4 diamonds sharing an edge with the cell on a staggered isometric grid
+    pub fn neighbour_offsets(&self, x: usize, y: usize) -> Vec<(isize, isize)> {
+        let (_, line) = self.swap((x as isize, y as isize));
+        let side = if self.shifted(line) { 0 } else { -1 };
+        let mut offsets = vec![(side, -1), (side + 1, -1), (side, 1), (side + 1, 1)];
+        if self.is_hexagonal() {
+            offsets.extend([(-1, 0), (1, 0)]);
+        }
+        offsets.into_iter().map(|offset| self.swap(offset)).collect()
+    }
+
+    /// Number of moves between two cells, moving from a cell to one of its neighbours
+    pub fn distance(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        let (q1, r1) = self.axial(from);
+        let (q2, r2) = self.axial(to);
+        let (dq, dr) = (q2 - q1, r2 - r1);
+        if self.is_hexagonal() {
+            ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+        } else {
+            // Moves of a staggered grid are the hexagonal ones, without the moves along the lines
+            (dq.abs() + (dq + dr).abs()) as usize
+        }
+    }
+
+    /// Axial coordinates of a cell, as used on hexagonal grids
+    fn axial(&self, cell: (usize, usize)) -> (isize, isize) {
+        let (x, line) = self.swap((cell.0 as isize, cell.1 as isize));
+        let first_shift = self.shifted(0) as isize;
+        ((2 * x + self.shifted(line) as isize - line - first_shift) / 2, line)
+    }
+
+    /// Position of the top left corner of the cell (x, y), in pixels from the tilemap's origin
+    pub fn tile_position(&self, x: usize, y: usize, tile_width: f32, tile_height: f32) -> (f32, f32) {
+        let (column, line) = self.swap((x as isize, y as isize));
+        let (width, height) = self.swap((tile_width, tile_height));
+        let line_gap = (height + self.side_length.unwrap_or(0.)) / 2.;
+        let shift = if self.shifted(line) { width / 2. } else { 0. };
+        self.swap((column as f32 * width + shift, line as f32 * line_gap))
+    }
+
+    /// Order of the cell (x, y) from the top of the screen, cells of the same rank being on the same line
+    pub(crate) fn draw_rank(&self, x: usize, y: usize) -> usize {
+        match self.axis {
+            StaggerAxis::X => 2 * y + self.shifted(x as isize) as usize,
+            StaggerAxis::Y => y,
+        }
+    }
+
+    /// Cell containing the point, in pixels from the tilemap's origin. The cell may be outside the tilemap
+    pub fn cell_at(&self, point: (f32, f32), tile_width: f32, tile_height: f32) -> (isize, isize) {
+        let (px, py) = self.swap(point);
+        let (width, height) = self.swap((tile_width, tile_height));
+        let line_gap = (height + self.side_length.unwrap_or(0.)) / 2.;
+        let (column, line) = ((px / width).floor() as isize, (py / line_gap).floor() as isize);
+
+        // The closest cell center is searched around the estimated cell. Hexagons are the cells closest to their
+        // center, diamonds the closest ones when the distance is measured along their diagonals
+        let distance = |(cx, cy): (f32, f32)| {
+            let (dx, dy) = ((px - cx) / width, (py - cy) / height);
+            if self.is_hexagonal() { dx * dx + dy * dy } else { dx.abs() + dy.abs() }
+        };
+        let candidates = (line - 1..=line + 1).flat_map(|l| (column - 1..=column + 1).map(move |c| (c, l)));
+        let (column, line) = candidates
+            .map(|(c, l)| {
+                let shift = if self.shifted(l) { width / 2. } else { 0. };
+                ((c, l), distance((c as f32 * width + shift + width / 2., l as f32 * line_gap + height / 2.)))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(cell, _)| cell)
+            .expect("Candidates are never empty");
+        self.swap((column, line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointy_hex() -> StaggeredGrid {
+        StaggeredGrid { axis: StaggerAxis::Y, index: StaggerIndex::Odd, side_length: Some(16.) }
+    }
+
+    #[test]
+    fn hexagonal_neighbours_and_distance() {
+        let grid = pointy_hex();
+        // Odd rows are shifted to the right
+        let mut neighbours = grid.neighbour_offsets(2, 1);
+        neighbours.sort();
+        assert_eq!(vec![(-1, 0), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)], neighbours);
+        let mut neighbours = grid.neighbour_offsets(2, 2);
+        neighbours.sort();
+        assert_eq!(vec![(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0)], neighbours);
+
+        assert_eq!(1, grid.distance((2, 1), (3, 2)));
+        assert_eq!(3, grid.distance((0, 0), (3, 0)));
+        assert_eq!(3, grid.distance((0, 0), (1, 3)));
+
+        let flat = StaggeredGrid { axis: StaggerAxis::X, ..grid };
+        assert_eq!(3, flat.distance((0, 0), (0, 3)));
+        assert_eq!(3, flat.distance((0, 0), (3, 1)));
+    }
+
+    #[test]
+    fn cells_are_found_from_their_pixels() {
+        let grid = pointy_hex();
+        // Rows are 24 pixels apart with 32x32 tiles
+        assert_eq!((48., 24.), grid.tile_position(1, 1, 32., 32.));
+        assert_eq!((1, 1), grid.cell_at((64., 40.), 32., 32.));
+        assert_eq!((0, 0), grid.cell_at((20., 10.), 32., 32.));
+
+        let staggered = StaggeredGrid { axis: StaggerAxis::Y, index: StaggerIndex::Odd, side_length: None };
+        assert_eq!((16., 8.), staggered.tile_position(0, 1, 32., 16.));
+        // The top left corner of the first diamond's image belongs to the diamond shifted on the previous row
+        assert_eq!((-1, -1), staggered.cell_at((2., 1.), 32., 16.));
+        assert_eq!((0, 1), staggered.cell_at((32., 16.), 32., 16.));
+    }
+}
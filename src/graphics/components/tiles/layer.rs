@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use log::warn;
+
+use crate::graphics::components::color::Color;
+
+/// Layer property hiding the layer when set to `false`
+pub const LAYER_VISIBLE: &str = "visible";
+/// Layer property holding the opacity of the layer, from 0.0 to 1.0
+pub const LAYER_OPACITY: &str = "opacity";
+/// Layer property holding the tint color of the layer, as `#rrggbb` or `#rrggbbaa`
+pub const LAYER_TINT: &str = "tint";
+/// Layer properties holding the parallax factors of the layer
+pub const LAYER_PARALLAX_X: &str = "parallax_x";
+pub const LAYER_PARALLAX_Y: &str = "parallax_y";
+/// Layer property holding the render offset of the layer, in layers
+pub const LAYER_RENDER_OFFSET: &str = "render_offset";
+
+/// Properties read by `LayerSettings`, the other properties of a layer being custom ones
+pub(crate) const LAYER_SETTINGS_PROPERTIES: [&str; 6] =
+    [LAYER_VISIBLE, LAYER_OPACITY, LAYER_TINT, LAYER_PARALLAX_X, LAYER_PARALLAX_Y, LAYER_RENDER_OFFSET];
+
+/// Rendering settings of a layer of a tilemap, controllable at runtime with `Tilemap::modify_layer`
+#[derive(Debug, Clone)]
+pub struct LayerSettings {
+    visible: bool,
+    opacity: f32,
+    tint: Option<Color>,
+    parallax: (f32, f32),
+    render_offset: isize,
+}
+
+impl Default for LayerSettings {
+    fn default() -> Self {
+        Self { visible: true, opacity: 1., tint: None, parallax: (1., 1.), render_offset: 0 }
+    }
+}
+
+impl LayerSettings {
+    /// Hides the layer, its tiles are kept but not drawn
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+
+    /// Opacity of the layer, from 0.0 (transparent) to 1.0 (opaque)
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// Multiplies the color of the layer's tiles by `tint`
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = Some(tint);
+        self
+    }
+
+    /// Parallax factors of the layer : 1.0 moves the layer with the tilemap, 0.0 keeps it fixed on the screen
+    /// and other values make it move slower or faster than the tilemap when the camera moves
+    pub fn with_parallax(mut self, parallax_x: f32, parallax_y: f32) -> Self {
+        self.parallax = (parallax_x, parallax_y);
+        self
+    }
+
+    /// Number of layers the layer is drawn above its position in the tilemap, negative values drawing it below
+    pub fn with_render_offset(mut self, render_offset: isize) -> Self {
+        self.render_offset = render_offset;
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0., 1.);
+    }
+
+    pub fn tint(&self) -> Option<&Color> {
+        self.tint.as_ref()
+    }
+
+    pub fn set_tint(&mut self, tint: Option<Color>) {
+        self.tint = tint;
+    }
+
+    pub fn parallax(&self) -> (f32, f32) {
+        self.parallax
+    }
+
+    pub fn set_parallax(&mut self, parallax_x: f32, parallax_y: f32) {
+        self.parallax = (parallax_x, parallax_y);
+    }
+
+    pub fn render_offset(&self) -> isize {
+        self.render_offset
+    }
+
+    pub fn set_render_offset(&mut self, render_offset: isize) {
+        self.render_offset = render_offset;
+    }
+
+    /// Color multiplied with the layer's tiles when rendering them. Hidden layers are fully transparent
+    pub(crate) fn color(&self) -> [f32; 4] {
+        if !self.visible {
+            return [1., 1., 1., 0.];
+        }
+        match &self.tint {
+            Some(tint) => {
+                let [r, g, b, a] = tint.as_f32_array();
+                [r, g, b, a * self.opacity]
+            }
+            None => [1., 1., 1., self.opacity],
+        }
+    }
+
+    /// Settings read from the properties of a layer of a tilemap file, invalid values being ignored
+    pub(crate) fn from_properties(properties: &HashMap<String, String>) -> Self {
+        fn parse<T: std::str::FromStr>(properties: &HashMap<String, String>, key: &str) -> Option<T> {
+            let value = properties.get(key)?;
+            let parsed = value.parse().ok();
+            if parsed.is_none() {
+                warn!("Invalid value '{}' for the layer property '{}'", value, key);
+            }
+            parsed
+        }
+
+        let mut settings = LayerSettings::default();
+        if let Some(visible) = parse(properties, LAYER_VISIBLE) {
+            settings.visible = visible;
+        }
+        if let Some(opacity) = parse(properties, LAYER_OPACITY) {
+            settings.set_opacity(opacity);
+        }
+        if let Some(tint) = properties.get(LAYER_TINT) {
+            if is_hex_color(tint) {
+                settings.tint = Some(Color::new_hex(tint));
+            } else {
+                warn!("Invalid value '{}' for the layer property '{}'", tint, LAYER_TINT);
+            }
+        }
+        settings.parallax = (
+            parse(properties, LAYER_PARALLAX_X).unwrap_or(1.),
+            parse(properties, LAYER_PARALLAX_Y).unwrap_or(1.),
+        );
+        if let Some(render_offset) = parse(properties, LAYER_RENDER_OFFSET) {
+            settings.render_offset = render_offset;
+        }
+        settings
+    }
+
+    /// Properties of a layer of a tilemap file holding these settings, default values being omitted
+    pub(crate) fn to_properties(&self) -> HashMap<String, String> {
+        let mut properties = HashMap::new();
+        if !self.visible {
+            properties.insert(LAYER_VISIBLE.to_string(), false.to_string());
+        }
+        if self.opacity < 1. {
+            properties.insert(LAYER_OPACITY.to_string(), self.opacity.to_string());
+        }
+        if let Some(tint) = &self.tint {
+            let alpha = (tint.alpha() * 255.).round() as u8;
+            let hex = format!("#{:02x}{:02x}{:02x}{:02x}", tint.red(), tint.green(), tint.blue(), alpha);
+            properties.insert(LAYER_TINT.to_string(), hex);
+        }
+        if self.parallax.0 != 1. {
+            properties.insert(LAYER_PARALLAX_X.to_string(), self.parallax.0.to_string());
+        }
+        if self.parallax.1 != 1. {
+            properties.insert(LAYER_PARALLAX_Y.to_string(), self.parallax.1.to_string());
+        }
+        if self.render_offset != 0 {
+            properties.insert(LAYER_RENDER_OFFSET.to_string(), self.render_offset.to_string());
+        }
+        properties
+    }
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.starts_with('#') && (value.len() == 7 || value.len() == 9) && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_read_back_from_their_properties() {
+        let settings = LayerSettings::default()
+            .hidden()
+            .with_opacity(0.5)
+            .with_tint(Color::new_rgb(255, 0, 0))
+            .with_parallax(0.5, 1.)
+            .with_render_offset(2);
+        let properties = settings.to_properties();
+        assert_eq!("#ff0000ff", properties[LAYER_TINT]);
+        assert!(!properties.contains_key(LAYER_PARALLAX_Y));
+
+        let read = LayerSettings::from_properties(&properties);
+        assert!(!read.is_visible());
+        assert_eq!(0.5, read.opacity());
+        assert_eq!(255, read.tint().unwrap().red());
+        assert_eq!((0.5, 1.), read.parallax());
+        assert_eq!(2, read.render_offset());
+
+        let invalid = HashMap::from([(LAYER_OPACITY.to_string(), "half".to_string()), (LAYER_TINT.to_string(), "red".to_string())]);
+        let read = LayerSettings::from_properties(&invalid);
+        assert_eq!(1., read.opacity());
+        assert!(read.tint().is_none());
+        assert_eq!([1., 1., 1., 1.], read.color());
+    }
+}
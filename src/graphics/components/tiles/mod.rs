@@ -1,5 +1,14 @@
 pub const SPRITE_ANIMATION_PRELOAD: &str = "sprite-anim";
 pub mod sprite;
 pub mod tilemap;
+pub mod chunk;
+pub mod autotile;
+pub mod hex;
+pub mod layer;
+pub mod generation;
 pub mod tileset;
 pub mod atlas;
+pub mod tiled;
+pub mod visibility;
+#[cfg(test)]
+pub(crate) mod test_utils;
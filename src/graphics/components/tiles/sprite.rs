@@ -11,6 +11,7 @@ use crate::{
     graphics::rendering::Renderable2D,
 };
 use crate::graphics::rendering::Highlight;
+use serde::{Deserialize, Serialize};
 
 const INDICES: &[u16] = &[0, 1, 3, 3, 1, 2];
 
@@ -27,17 +28,50 @@ pub struct Sprite {
     pivot: Pivot,
     /// Highlight type
     highlight: Option<Highlight>,
+    /// Whether `highlight` is the tint of a fog of war, rather than a highlight set by the game
+    fog_tinted: bool,
+    /// Flips applied to the tile's texture
+    flip: TileFlip,
+}
+
+/// Flips of the texture of a sprite. Like in Tiled, the diagonal flip (swapping x and y) is applied first,
+/// then the horizontal and the vertical ones.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TileFlip {
+    pub horizontal: bool,
+    pub vertical: bool,
+    pub diagonal: bool,
+}
+
+impl TileFlip {
+    pub fn is_flipped(&self) -> bool {
+        self.horizontal || self.vertical || self.diagonal
+    }
 }
 
 impl Sprite {
     /// Creates a new sprite that will use the `tile_number` from the tileset associated in the same
     /// entity
     pub fn new(tile_number: usize) -> Self {
-        Self { tile_number, contents: None, dirty: false, pivot: Pivot::TopLeft, highlight: None }
+        Self { tile_number, contents: None, dirty: false, pivot: Pivot::TopLeft, highlight: None, fog_tinted: false, flip: TileFlip::default() }
     }
 
     pub fn pivot(self, pivot: Pivot) -> Self {
-        Self { tile_number: self.tile_number, contents: None, dirty: false, pivot, highlight: None }
+        Self { tile_number: self.tile_number, contents: None, dirty: false, pivot, highlight: None, fog_tinted: false, flip: self.flip }
+    }
+
+    pub fn with_flip(mut self, flip: TileFlip) -> Self {
+        self.flip = flip;
+        self
+    }
+
+    pub fn flip(&self) -> TileFlip {
+        self.flip
+    }
+
+    pub fn set_flip(&mut self, flip: TileFlip) {
+        self.flip = flip;
+        self.dirty = true;
     }
 
     /// Modify the current sprite tile number
@@ -63,7 +97,11 @@ impl Sprite {
         let b = Coordinates::new(0., 1.);
         let c = Coordinates::new(1., 1.);
         let d = Coordinates::new(1., 0.);
-        [a, b, c, d]
+        [a, b, c, d].map(|corner| {
+            let x = if self.flip.horizontal { 1. - corner.x } else { corner.x };
+            let y = if self.flip.vertical { 1. - corner.y } else { corner.y };
+            if self.flip.diagonal { Coordinates::new(y, x) } else { Coordinates::new(x, y) }
+        })
     }
 
     pub(crate) fn compute_content(&self, material: Option<&Material>) -> [TexturedGlVertexWithLayer; 4] {
@@ -96,9 +134,21 @@ impl Sprite {
 
     pub fn set_highlight(&mut self, highlight: Option<Highlight>){
         self.highlight = highlight;
+        self.fog_tinted = false;
+        self.dirty = true;
+    }
+
+    /// Tints the sprite with the color of a fog of war, replacing its highlight until the fog removes it
+    pub(crate) fn set_fog_tint(&mut self, tint: Highlight) {
+        self.highlight = Some(tint);
+        self.fog_tinted = true;
         self.dirty = true;
     }
 
+    pub(crate) fn is_fog_tinted(&self) -> bool {
+        self.fog_tinted
+    }
+
     pub(crate) fn set_content(&mut self, content: [TexturedGlVertexWithLayer; 4]) {
         self.contents = Some(content);
     }
@@ -0,0 +1,48 @@
+//! Tilesets and tilemaps shared by the tests of the tiles and of the systems using them
+use std::collections::{HashMap, HashSet};
+
+use hecs::Entity;
+
+use crate::core::components::maths::transform::Transform;
+use crate::core::resources::asset_manager::{AssetManager, AssetRef};
+use crate::core::world::GameData;
+use crate::graphics::components::material::Material;
+use crate::graphics::components::tiles::tilemap::{TileInfos, Tilemap, TilemapInfo, TilemapType};
+use crate::graphics::components::tiles::tileset::Tileset;
+use crate::utils::maths::{Dimensions, Position};
+
+/// Registers a tileset named `name` of `tile_count` tiles of 16x16 pixels, `pathing` giving the tiles of each pathing value.
+/// The `AssetManager` is added if needed
+pub(crate) fn pathing_tileset(data: &mut GameData, name: &str, tile_count: usize, pathing: &[(&str, &[usize])]) -> AssetRef<Material> {
+    if data.get_resource::<AssetManager>().is_none() {
+        data.insert_resource(AssetManager::default());
+    }
+    let pathing: HashMap<String, HashSet<usize>> =
+        pathing.iter().map(|(value, tiles)| (value.to_string(), tiles.iter().copied().collect())).collect();
+    data.assets_mut().register_tileset(Tileset::new(name.to_string(), "".to_string(), tile_count, 1, 16, 16).with_pathing(pathing))
+}
+
+/// Creates a standard tilemap of `dimensions` placed at `transform`, on a `pathing_tileset` ending with the last tile of `pathing`
+pub(crate) fn pathing_tilemap(
+    data: &mut GameData,
+    dimensions: Dimensions,
+    transform: Transform,
+    pathing: &[(&str, &[usize])],
+    tile_resolver: impl FnMut(&Position) -> TileInfos,
+) -> Entity {
+    typed_pathing_tilemap(data, dimensions, transform, TilemapType::Standard, pathing, tile_resolver)
+}
+
+/// Same as `pathing_tilemap`, for a tilemap of type `tilemap_type`
+pub(crate) fn typed_pathing_tilemap(
+    data: &mut GameData,
+    dimensions: Dimensions,
+    transform: Transform,
+    tilemap_type: TilemapType,
+    pathing: &[(&str, &[usize])],
+    tile_resolver: impl FnMut(&Position) -> TileInfos,
+) -> Entity {
+    let tile_count = pathing.iter().flat_map(|(_, tiles)| tiles.iter()).max().map_or(1, |last| last + 1);
+    let tileset = pathing_tileset(data, "test", tile_count, pathing);
+    Tilemap::create(TilemapInfo::new(dimensions, transform, tileset, tilemap_type), data, tile_resolver)
+}
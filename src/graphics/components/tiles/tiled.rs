@@ -0,0 +1,908 @@
+//! Importer of the maps (`.tmx`, `.tmj`) and tilesets (`.tsx`, `.tsj`) of the Tiled editor, in XML or JSON.
+//!
+//! Maps are converted into `TilemapAtlas` and tilesets into `TilesetAtlas`, so they are loaded like `.scion` files,
+//! for example by `atlas::importer::load_tilemap`. Layer data can be encoded in CSV or base64, optionally compressed
+//! with zlib or gzip. Group layers are flattened, tile layers become the layers of the tilemap and the objects of all
+//! the object layers are gathered in the tilemap's objects. The custom `pathing` property of a tile adds it to the
+//! pathing of its tileset.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use log::warn;
+use roxmltree::Node;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::core::components::maths::coordinates::Coordinates;
+use crate::graphics::components::tiles::atlas::data::{
+    FlippedTile, TileAnimationFrame, TileConfig, TileObject, TileObjectClass, TileObjectShapeType, TileRectangle,
+    TiledTilemapTileset, TilemapAtlas, TilemapLayer, TilesetAtlas,
+};
+use crate::graphics::components::tiles::sprite::TileFlip;
+use crate::graphics::components::tiles::hex::{StaggerAxis, StaggerIndex};
+use crate::graphics::components::tiles::layer::{LAYER_OPACITY, LAYER_PARALLAX_X, LAYER_PARALLAX_Y, LAYER_TINT, LAYER_VISIBLE};
+use crate::graphics::components::tiles::tilemap::{OffsetMultiplier, TilemapType};
+use crate::graphics::components::tiles::tileset::Tileset;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x80000000;
+const FLIPPED_VERTICALLY: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY: u32 = 0x20000000;
+/// Removes the flip flags, including the hexagonal rotation one, from a global tile id
+const TILE_ID_MASK: u32 = 0x0FFFFFFF;
+
+/// Custom property of a tile adding it to the pathing of its tileset
+const PATHING_PROPERTY: &str = "pathing";
+
+#[derive(Debug)]
+pub enum TiledError {
+    /// A file could not be read
+    Io(String),
+    /// A file is not a valid Tiled file
+    Parsing(String),
+    /// A file uses a Tiled feature that Scion does not support
+    Unsupported(String),
+}
+
+/// A tileset imported from Tiled, with the path of its texture
+pub struct TiledTileset {
+    pub(crate) atlas: TilesetAtlas,
+    pub(crate) texture: String,
+}
+
+impl TiledTileset {
+    pub fn name(&self) -> &str {
+        &self.atlas.name
+    }
+
+    pub fn atlas(&self) -> &TilesetAtlas {
+        &self.atlas
+    }
+
+    /// Path of the image of this tileset, relative to the tileset file
+    pub fn texture(&self) -> &str {
+        &self.texture
+    }
+
+    pub fn into_tileset(self) -> Tileset {
+        self.atlas.into_tileset(self.texture)
+    }
+}
+
+/// Whether `path` is a Tiled map, which can be imported with `import_tiled_map`
+pub fn is_tiled_map(path: &str) -> bool {
+    has_extension(path, &["tmx", "tmj"])
+}
+
+/// Whether `path` is a Tiled tileset, which can be imported with `import_tiled_tileset`
+pub fn is_tiled_tileset(path: &str) -> bool {
+    has_extension(path, &["tsx", "tsj"])
+}
+
+/// Imports the Tiled map located at `path`, with all its tilesets, embedded or external
+pub fn import_tiled_map(path: &str) -> Result<(TilemapAtlas, Vec<TiledTileset>), TiledError> {
+    let content = read_to_string(path)?;
+    let directory = parent_directory(path);
+    if has_extension(path, &["tmx"]) {
+        parse_tmx(&content, &directory)
+    } else {
+        parse_tmj(&content, &directory)
+    }
+}
+
+/// Imports the Tiled tileset located at `path`
+pub fn import_tiled_tileset(path: &str) -> Result<TiledTileset, TiledError> {
+    let content = read_to_string(path)?;
+    let directory = parent_directory(path);
+    if has_extension(path, &["tsx"]) {
+        let document = roxmltree::Document::parse(&content).map_err(|e| TiledError::Parsing(e.to_string()))?;
+        xml_tileset(document.root_element(), &directory)
+    } else {
+        let data: JsonTileset = serde_json::from_str(&content).map_err(|e| TiledError::Parsing(e.to_string()))?;
+        json_tileset(data, &directory)
+    }
+}
+
+/// Map read from a Tiled file, whatever its format
+struct MapData {
+    orientation: String,
+    width: usize,
+    height: usize,
+    tile_width: usize,
+    tile_height: usize,
+    /// Stagger axis (`x` or `y`) and index (`odd` or `even`) of the hexagonal and staggered maps
+    stagger_axis: Option<String>,
+    stagger_index: Option<String>,
+    hex_side_length: usize,
+    infinite: bool,
+    properties: HashMap<String, String>,
+    /// Tilesets with their first global tile id in the Tiled map
+    tilesets: Vec<(u32, TiledTileset)>,
+    layers: Vec<LayerData>,
+}
+
+enum LayerData {
+    Tiles { name: String, properties: HashMap<String, String>, tiles: Vec<u32> },
+    Objects(Vec<TileObject>),
+}
+
+enum ShapeData {
+    Rectangle,
+    Point,
+    Polygon(Vec<Coordinates>),
+}
+
+impl MapData {
+    fn into_atlas(mut self) -> Result<(TilemapAtlas, Vec<TiledTileset>), TiledError> {
+        if self.infinite {
+            return Err(TiledError::Unsupported("infinite maps".to_string()));
+        }
+        let stagger_axis = if self.stagger_axis.as_deref() == Some("x") { StaggerAxis::X } else { StaggerAxis::Y };
+        let stagger_index = if self.stagger_index.as_deref() == Some("even") { StaggerIndex::Even } else { StaggerIndex::Odd };
+        let tilemap_type = match self.orientation.as_str() {
+            "orthogonal" => TilemapType::Standard,
+            "isometric" => isometric_type(self.tile_width as f32, self.tile_height as f32),
+            "hexagonal" => TilemapType::Hexagonal { stagger_axis, stagger_index, side_length: self.hex_side_length },
+            "staggered" => TilemapType::Staggered { stagger_axis, stagger_index },
+            orientation => return Err(TiledError::Unsupported(format!("{} orientation", orientation))),
+        };
+
+        self.tilesets.sort_by_key(|(first_gid, _)| *first_gid);
+        let mut first_tiles = Vec::new();
+        let mut next_first_tile = 0;
+        for (_, tileset) in self.tilesets.iter() {
+            first_tiles.push(next_first_tile);
+            next_first_tile += tileset.atlas.total_tiles;
+        }
+
+        let mut layers = Vec::new();
+        let mut objects = Vec::new();
+        for layer in self.layers {
+            match layer {
+                LayerData::Tiles { name, properties, tiles } => {
+                    if tiles.len() != self.width * self.height {
+                        return Err(TiledError::Parsing(format!("Layer {} does not have the size of the map", name)));
+                    }
+                    let mut rows = vec![vec![-1; self.width]; self.height];
+                    let mut flipped_tiles = Vec::new();
+                    for (i, raw_tile) in tiles.into_iter().enumerate() {
+                        let (x, y) = (i % self.width, i / self.width);
+                        let tile_id = raw_tile & TILE_ID_MASK;
+                        if tile_id == 0 {
+                            continue;
+                        }
+                        let tileset = self
+                            .tilesets
+                            .iter()
+                            .rposition(|(first_gid, _)| *first_gid <= tile_id)
+                            .ok_or_else(|| TiledError::Parsing(format!("Tile {} has no tileset", tile_id)))?;
+                        rows[y][x] = (first_tiles[tileset] + (tile_id - self.tilesets[tileset].0) as usize) as isize;
+                        let flip = TileFlip {
+                            horizontal: raw_tile & FLIPPED_HORIZONTALLY != 0,
+                            vertical: raw_tile & FLIPPED_VERTICALLY != 0,
+                            diagonal: raw_tile & FLIPPED_DIAGONALLY != 0,
+                        };
+                        if flip.is_flipped() {
+                            flipped_tiles.push(FlippedTile { x, y, flip });
+                        }
+                    }
+                    layers.push(TilemapLayer { name, tiles_encoded: None, tiles: rows, properties, flipped_tiles });
+                }
+                LayerData::Objects(mut layer_objects) => objects.append(&mut layer_objects),
+            }
+        }
+
+        let tilemap = TilemapAtlas {
+            width: self.width,
+            height: self.height,
+            tile_width: self.tile_width,
+            tile_height: self.tile_height,
+            properties: self.properties,
+            layers,
+            objects,
+            tilesets: self
+                .tilesets
+                .iter()
+                .enumerate()
+                .map(|(index, (_, t))| TiledTilemapTileset { index, total_tiles: t.atlas.total_tiles, name: t.atlas.name.clone() })
+                .collect(),
+            tilemap_type: Some(tilemap_type),
+            pathing: Vec::new(),
+            events: Vec::new(),
+        };
+        Ok((tilemap, self.tilesets.into_iter().map(|(_, t)| t).collect()))
+    }
+}
+
+/// Offsets placing the tiles of `tile_width` x `tile_height` pixels in a diamond, like Tiled does
+fn isometric_type(tile_width: f32, tile_height: f32) -> TilemapType {
+    TilemapType::Isometric {
+        offset_x: OffsetMultiplier { x_multiplier: tile_width / 2., y_multiplier: -tile_width / 2., z_multiplier: 0. },
+        offset_y: OffsetMultiplier { x_multiplier: -tile_height / 2., y_multiplier: tile_width - tile_height / 2., z_multiplier: 0. },
+        offset_z: OffsetMultiplier { x_multiplier: 0., y_multiplier: 0., z_multiplier: 0. },
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tileset_atlas(
+    name: String,
+    tile_width: usize,
+    tile_height: usize,
+    tile_count: usize,
+    columns: usize,
+    spacing: usize,
+    margin: usize,
+    tiles: HashMap<usize, TileConfig>,
+) -> TilesetAtlas {
+    if spacing != 0 || margin != 0 {
+        warn!("The spacing and the margin of the tileset '{}' are not supported and will be ignored", name);
+    }
+    let mut pathing: HashMap<String, _> = HashMap::new();
+    for (id, tile) in tiles.iter() {
+        if let Some(value) = tile.property(PATHING_PROPERTY) {
+            pathing.entry(value.to_string()).or_insert_with(std::collections::HashSet::new).insert(*id);
+        }
+    }
+    TilesetAtlas {
+        name,
+        total_tiles: tile_count,
+        width: columns,
+        height: tile_count.div_ceil(columns.max(1)),
+        tile_width,
+        tile_height,
+        pathing: Some(pathing),
+        tiles,
+        terrain_sets: Vec::new(),
+        adjacency_rules: None,
+    }
+}
+
+fn tile_object(class: &str, name: &str, position: Coordinates, size: (f32, f32), shape: ShapeData, properties: HashMap<String, String>) -> TileObject {
+    let class = match class.to_lowercase().as_str() {
+        "collider" | "collision" | "collisionarea" => TileObjectClass::CollisionArea,
+        "item" => TileObjectClass::Item,
+        "door" => TileObjectClass::Door,
+        "trigger" => TileObjectClass::Trigger,
+        _ => TileObjectClass::Custom(class.to_string()),
+    };
+    let (shape_type, polygon, rectangle) = match shape {
+        ShapeData::Rectangle => (TileObjectShapeType::Rectangle, None, Some(TileRectangle { width: size.0, height: size.1 })),
+        ShapeData::Point => (TileObjectShapeType::Point, None, None),
+        ShapeData::Polygon(points) => (TileObjectShapeType::Polygon, Some(points), None),
+    };
+    TileObject {
+        name: if name.is_empty() { None } else { Some(name.to_string()) },
+        class,
+        shape_type,
+        position,
+        polygon,
+        rectangle,
+        properties: if properties.is_empty() { None } else { Some(properties) },
+    }
+}
+
+/// Stores the visibility, opacity, tint and parallax of a Tiled layer as the layer properties read by `LayerSettings`,
+/// unless the layer already defines these properties
+fn layer_attributes(properties: &mut HashMap<String, String>, visible: Option<bool>, opacity: Option<f32>, tint: Option<&str>, parallax: (Option<f32>, Option<f32>)) {
+    let mut insert = |key: &str, value: String| {
+        properties.entry(key.to_string()).or_insert(value);
+    };
+    if visible == Some(false) {
+        insert(LAYER_VISIBLE, false.to_string());
+    }
+    if let Some(opacity) = opacity.filter(|o| *o < 1.) {
+        insert(LAYER_OPACITY, opacity.to_string());
+    }
+    if let Some(tint) = tint {
+        // Tiled writes the alpha of the colors first
+        let tint = match tint.len() {
+            9 => format!("#{}{}", &tint[3..], &tint[1..3]),
+            _ => tint.to_string(),
+        };
+        insert(LAYER_TINT, tint);
+    }
+    if let Some(parallax_x) = parallax.0 {
+        insert(LAYER_PARALLAX_X, parallax_x.to_string());
+    }
+    if let Some(parallax_y) = parallax.1 {
+        insert(LAYER_PARALLAX_Y, parallax_y.to_string());
+    }
+}
+
+/// Decodes base64 layer data, compressed with `compression`, into global tile ids
+fn decode_base64(data: &str, compression: Option<&str>) -> Result<Vec<u32>, TiledError> {
+    let bytes = BASE64_STANDARD.decode(data.trim()).map_err(|e| TiledError::Parsing(e.to_string()))?;
+    let mut decompressed = Vec::new();
+    let result = match compression {
+        None | Some("") => {
+            decompressed = bytes;
+            Ok(0)
+        }
+        Some("zlib") => ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed),
+        Some("gzip") => GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed),
+        Some(compression) => return Err(TiledError::Unsupported(format!("{} compression", compression))),
+    };
+    result.map_err(|e| TiledError::Parsing(e.to_string()))?;
+    Ok(decompressed.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+}
+
+fn decode_csv(data: &str) -> Result<Vec<u32>, TiledError> {
+    data.split(',')
+        .map(str::trim)
+        .filter(|tile| !tile.is_empty())
+        .map(|tile| tile.parse::<u32>().map_err(|e| TiledError::Parsing(e.to_string())))
+        .collect()
+}
+
+fn has_extension(path: &str, extensions: &[&str]) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|extension| e.eq_ignore_ascii_case(extension)))
+}
+
+fn parent_directory(path: &str) -> PathBuf {
+    Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+fn read_to_string(path: &str) -> Result<String, TiledError> {
+    let bytes = crate::utils::file::read_file(Path::new(path)).map_err(|e| TiledError::Io(format!("{:?}", e)))?;
+    String::from_utf8(bytes).map_err(|e| TiledError::Parsing(e.to_string()))
+}
+
+fn resolve_path(directory: &Path, source: &str) -> String {
+    directory.join(source).to_string_lossy().to_string()
+}
+
+// ================= XML formats =================
+
+pub(crate) fn parse_tmx(content: &str, directory: &Path) -> Result<(TilemapAtlas, Vec<TiledTileset>), TiledError> {
+    let document = roxmltree::Document::parse(content).map_err(|e| TiledError::Parsing(e.to_string()))?;
+    let map = document.root_element();
+    let mut tilesets = Vec::new();
+    for tileset in map.children().filter(|c| c.has_tag_name("tileset")) {
+        let first_gid = attribute(tileset, "firstgid")?;
+        let tileset = match tileset.attribute("source") {
+            Some(source) => import_tiled_tileset(&resolve_path(directory, source))?,
+            None => xml_tileset(tileset, directory)?,
+        };
+        tilesets.push((first_gid, tileset));
+    }
+    let mut layers = Vec::new();
+    xml_layers(map, &mut layers)?;
+    MapData {
+        orientation: attribute(map, "orientation")?,
+        width: attribute(map, "width")?,
+        height: attribute(map, "height")?,
+        tile_width: attribute(map, "tilewidth")?,
+        tile_height: attribute(map, "tileheight")?,
+        stagger_axis: optional_attribute(map, "staggeraxis")?,
+        stagger_index: optional_attribute(map, "staggerindex")?,
+        hex_side_length: optional_attribute(map, "hexsidelength")?.unwrap_or(0),
+        infinite: map.attribute("infinite") == Some("1"),
+        properties: xml_properties(map),
+        tilesets,
+        layers,
+    }
+    .into_atlas()
+}
+
+fn xml_layers(parent: Node, layers: &mut Vec<LayerData>) -> Result<(), TiledError> {
+    for layer in parent.children().filter(|c| c.is_element()) {
+        match layer.tag_name().name() {
+            "layer" => {
+                let data = layer.children().find(|c| c.has_tag_name("data")).ok_or_else(|| TiledError::Parsing("Missing layer data".to_string()))?;
+                let text = data.text().unwrap_or("");
+                let tiles = match data.attribute("encoding") {
+                    Some("csv") => decode_csv(text)?,
+                    Some("base64") => decode_base64(text, data.attribute("compression"))?,
+                    Some(encoding) => return Err(TiledError::Unsupported(format!("{} encoding", encoding))),
+                    None => data
+                        .children()
+                        .filter(|c| c.has_tag_name("tile"))
+                        .map(|t| t.attribute("gid").map_or(Ok(0), |gid| gid.parse().map_err(|_| TiledError::Parsing(gid.to_string()))))
+                        .collect::<Result<_, _>>()?,
+                };
+                let mut properties = xml_properties(layer);
+                layer_attributes(
+                    &mut properties,
+                    optional_attribute::<u8>(layer, "visible")?.map(|visible| visible != 0),
+                    optional_attribute(layer, "opacity")?,
+                    layer.attribute("tintcolor"),
+                    (optional_attribute(layer, "parallaxx")?, optional_attribute(layer, "parallaxy")?),
+                );
+                layers.push(LayerData::Tiles { name: layer.attribute("name").unwrap_or_default().to_string(), properties, tiles });
+            }
+            "objectgroup" => layers.push(LayerData::Objects(xml_objects(layer)?)),
+            "group" => xml_layers(layer, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn xml_tileset(tileset: Node, directory: &Path) -> Result<TiledTileset, TiledError> {
+    let image = tileset
+        .children()
+        .find(|c| c.has_tag_name("image"))
+        .ok_or_else(|| TiledError::Unsupported("tilesets without a single image".to_string()))?;
+    let mut tiles = HashMap::new();
+    for tile in tileset.children().filter(|c| c.has_tag_name("tile")) {
+        let animation: Vec<TileAnimationFrame> = tile
+            .children()
+            .filter(|c| c.has_tag_name("animation"))
+            .flat_map(|a| a.children().filter(|c| c.has_tag_name("frame")))
+            .map(|frame| Ok(TileAnimationFrame { tile_id: attribute(frame, "tileid")?, duration: attribute(frame, "duration")? }))
+            .collect::<Result<_, TiledError>>()?;
+        let objects = match tile.children().find(|c| c.has_tag_name("objectgroup")) {
+            Some(group) => xml_objects(group)?.into_iter().map(collision_area).collect(),
+            None => Vec::new(),
+        };
+        let properties = xml_properties(tile);
+        tiles.insert(
+            attribute(tile, "id")?,
+            TileConfig {
+                animation: if animation.is_empty() { None } else { Some(animation) },
+                objects,
+                properties: if properties.is_empty() { None } else { Some(properties) },
+            },
+        );
+    }
+    Ok(TiledTileset {
+        atlas: tileset_atlas(
+            attribute(tileset, "name")?,
+            attribute(tileset, "tilewidth")?,
+            attribute(tileset, "tileheight")?,
+            attribute(tileset, "tilecount")?,
+            attribute(tileset, "columns")?,
+            optional_attribute(tileset, "spacing")?.unwrap_or(0),
+            optional_attribute(tileset, "margin")?.unwrap_or(0),
+            tiles,
+        ),
+        texture: resolve_path(directory, &attribute::<String>(image, "source")?),
+    })
+}
+
+fn xml_objects(group: Node) -> Result<Vec<TileObject>, TiledError> {
+    group
+        .children()
+        .filter(|c| c.has_tag_name("object"))
+        .map(|object| {
+            let shape = if object.children().any(|c| c.has_tag_name("point")) {
+                ShapeData::Point
+            } else if let Some(points) = object.children().find(|c| c.has_tag_name("polygon") || c.has_tag_name("polyline")) {
+                ShapeData::Polygon(xml_points(attribute::<String>(points, "points")?.as_str())?)
+            } else {
+                ShapeData::Rectangle
+            };
+            let class = object.attribute("class").or(object.attribute("type")).unwrap_or_default();
+            Ok(tile_object(
+                class,
+                object.attribute("name").unwrap_or_default(),
+                Coordinates::new(attribute(object, "x")?, attribute(object, "y")?),
+                (optional_attribute(object, "width")?.unwrap_or(0.), optional_attribute(object, "height")?.unwrap_or(0.)),
+                shape,
+                xml_properties(object),
+            ))
+        })
+        .collect()
+}
+
+/// Parses the `x,y x,y ...` points of a polygon
+fn xml_points(points: &str) -> Result<Vec<Coordinates>, TiledError> {
+    points
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point.split_once(',').ok_or_else(|| TiledError::Parsing(point.to_string()))?;
+            let parse = |v: &str| v.parse::<f32>().map_err(|_| TiledError::Parsing(point.to_string()));
+            Ok(Coordinates::new(parse(x)?, parse(y)?))
+        })
+        .collect()
+}
+
+fn xml_properties(node: Node) -> HashMap<String, String> {
+    node.children()
+        .filter(|c| c.has_tag_name("properties"))
+        .flat_map(|p| p.children().filter(|c| c.has_tag_name("property")))
+        .filter_map(|property| {
+            let value = property.attribute("value").or(property.text()).unwrap_or_default();
+            property.attribute("name").map(|name| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn attribute<T: std::str::FromStr>(node: Node, name: &str) -> Result<T, TiledError> {
+    optional_attribute(node, name)?
+        .ok_or_else(|| TiledError::Parsing(format!("Missing attribute {} on {}", name, node.tag_name().name())))
+}
+
+fn optional_attribute<T: std::str::FromStr>(node: Node, name: &str) -> Result<Option<T>, TiledError> {
+    node.attribute(name)
+        .map(|value| value.parse::<T>().map_err(|_| TiledError::Parsing(format!("Invalid attribute {} : {}", name, value))))
+        .transpose()
+}
+
+fn collision_area(mut object: TileObject) -> TileObject {
+    object.class = TileObjectClass::CollisionArea;
+    object
+}
+
+// ================= JSON formats =================
+
+#[derive(Deserialize)]
+struct JsonMap {
+    orientation: String,
+    width: usize,
+    height: usize,
+    tilewidth: usize,
+    tileheight: usize,
+    staggeraxis: Option<String>,
+    staggerindex: Option<String>,
+    #[serde(default)]
+    hexsidelength: usize,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonLayer {
+    Tilelayer {
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+        data: Option<Value>,
+        encoding: Option<String>,
+        compression: Option<String>,
+        visible: Option<bool>,
+        opacity: Option<f32>,
+        tintcolor: Option<String>,
+        parallaxx: Option<f32>,
+        parallaxy: Option<f32>,
+    },
+    Objectgroup {
+        #[serde(default)]
+        objects: Vec<JsonObject>,
+    },
+    Group {
+        #[serde(default)]
+        layers: Vec<JsonLayer>,
+    },
+    Imagelayer {},
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    #[serde(default)]
+    firstgid: u32,
+    source: Option<String>,
+    name: Option<String>,
+    tilewidth: Option<usize>,
+    tileheight: Option<usize>,
+    tilecount: Option<usize>,
+    columns: Option<usize>,
+    #[serde(default)]
+    spacing: usize,
+    #[serde(default)]
+    margin: usize,
+    image: Option<String>,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: usize,
+    #[serde(default)]
+    animation: Vec<JsonFrame>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    objectgroup: Option<JsonObjectGroup>,
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+    tileid: usize,
+    duration: usize,
+}
+
+#[derive(Deserialize)]
+struct JsonObjectGroup {
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type")]
+    object_type: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    point: bool,
+    polygon: Option<Vec<JsonPoint>>,
+    polyline: Option<Vec<JsonPoint>>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    value: Value,
+}
+
+pub(crate) fn parse_tmj(content: &str, directory: &Path) -> Result<(TilemapAtlas, Vec<TiledTileset>), TiledError> {
+    let map: JsonMap = serde_json::from_str(content).map_err(|e| TiledError::Parsing(e.to_string()))?;
+    let mut tilesets = Vec::new();
+    for tileset in map.tilesets {
+        let first_gid = tileset.firstgid;
+        let tileset = match &tileset.source {
+            Some(source) => import_tiled_tileset(&resolve_path(directory, source))?,
+            None => json_tileset(tileset, directory)?,
+        };
+        tilesets.push((first_gid, tileset));
+    }
+    let mut layers = Vec::new();
+    json_layers(map.layers, &mut layers)?;
+    MapData {
+        orientation: map.orientation,
+        width: map.width,
+        height: map.height,
+        tile_width: map.tilewidth,
+        tile_height: map.tileheight,
+        stagger_axis: map.staggeraxis,
+        stagger_index: map.staggerindex,
+        hex_side_length: map.hexsidelength,
+        infinite: map.infinite,
+        properties: json_properties(map.properties),
+        tilesets,
+        layers,
+    }
+    .into_atlas()
+}
+
+fn json_layers(data: Vec<JsonLayer>, layers: &mut Vec<LayerData>) -> Result<(), TiledError> {
+    for layer in data {
+        match layer {
+            JsonLayer::Tilelayer { name, properties, data, encoding, compression, visible, opacity, tintcolor, parallaxx, parallaxy } => {
+                let tiles = match (data, encoding.as_deref()) {
+                    (Some(Value::Array(tiles)), _) => tiles
+                        .iter()
+                        .map(|t| t.as_u64().map(|t| t as u32).ok_or_else(|| TiledError::Parsing(t.to_string())))
+                        .collect::<Result<_, _>>()?,
+                    (Some(Value::String(data)), Some("base64")) => decode_base64(&data, compression.as_deref())?,
+                    _ => return Err(TiledError::Unsupported(format!("data of the layer {}", name))),
+                };
+                let mut properties = json_properties(properties);
+                layer_attributes(&mut properties, visible, opacity, tintcolor.as_deref(), (parallaxx, parallaxy));
+                layers.push(LayerData::Tiles { name, properties, tiles });
+            }
+            JsonLayer::Objectgroup { objects } => layers.push(LayerData::Objects(objects.into_iter().map(json_object).collect())),
+            JsonLayer::Group { layers: children } => json_layers(children, layers)?,
+            JsonLayer::Imagelayer {} => {}
+        }
+    }
+    Ok(())
+}
+
+fn json_tileset(tileset: JsonTileset, directory: &Path) -> Result<TiledTileset, TiledError> {
+    let missing = |field: &str| TiledError::Parsing(format!("Missing tileset field {}", field));
+    let image = tileset.image.ok_or_else(|| TiledError::Unsupported("tilesets without a single image".to_string()))?;
+    let tiles = tileset
+        .tiles
+        .into_iter()
+        .map(|tile| {
+            let animation: Vec<TileAnimationFrame> =
+                tile.animation.iter().map(|f| TileAnimationFrame { tile_id: f.tileid, duration: f.duration }).collect();
+            let objects = tile.objectgroup.map_or_else(Vec::new, |g| g.objects.into_iter().map(json_object).map(collision_area).collect());
+            let properties = json_properties(tile.properties);
+            let config = TileConfig {
+                animation: if animation.is_empty() { None } else { Some(animation) },
+                objects,
+                properties: if properties.is_empty() { None } else { Some(properties) },
+            };
+            (tile.id, config)
+        })
+        .collect();
+    Ok(TiledTileset {
+        atlas: tileset_atlas(
+            tileset.name.ok_or_else(|| missing("name"))?,
+            tileset.tilewidth.ok_or_else(|| missing("tilewidth"))?,
+            tileset.tileheight.ok_or_else(|| missing("tileheight"))?,
+            tileset.tilecount.ok_or_else(|| missing("tilecount"))?,
+            tileset.columns.ok_or_else(|| missing("columns"))?,
+            tileset.spacing,
+            tileset.margin,
+            tiles,
+        ),
+        texture: resolve_path(directory, &image),
+    })
+}
+
+fn json_object(object: JsonObject) -> TileObject {
+    let shape = match (object.point, object.polygon.or(object.polyline)) {
+        (true, _) => ShapeData::Point,
+        (_, Some(points)) => ShapeData::Polygon(points.iter().map(|p| Coordinates::new(p.x, p.y)).collect()),
+        _ => ShapeData::Rectangle,
+    };
+    let class = if object.class.is_empty() { &object.object_type } else { &object.class };
+    tile_object(class, &object.name, Coordinates::new(object.x, object.y), (object.width, object.height), shape, json_properties(object.properties))
+}
+
+fn json_properties(properties: Vec<JsonProperty>) -> HashMap<String, String> {
+    properties
+        .into_iter()
+        .map(|p| {
+            let value = match p.value {
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+            (p.name, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use super::*;
+    use crate::utils::maths::Position;
+
+    fn encode(tiles: &[u32], compression: &str) -> String {
+        let bytes: Vec<u8> = tiles.iter().flat_map(|t| t.to_le_bytes()).collect();
+        let compressed = match compression {
+            "zlib" => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            _ => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+        };
+        BASE64_STANDARD.encode(compressed)
+    }
+
+    #[test]
+    fn tmx_map_with_embedded_and_external_tilesets() {
+        let directory = std::env::temp_dir().join("scion_tiled_test");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("walls.tsx"),
+            r#"<tileset name="walls" tilewidth="16" tileheight="16" tilecount="2" columns="2">
+                 <image source="walls.png" width="32" height="16"/>
+               </tileset>"#,
+        )
+        .unwrap();
+        let tmx = format!(
+            r#"<map orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16">
+                 <properties><property name="music" value="forest"/></properties>
+                 <tileset firstgid="1" name="ground" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+                   <image source="images/ground.png" width="32" height="32"/>
+                   <tile id="1">
+                     <properties><property name="pathing" value="water"/></properties>
+                     <animation><frame tileid="1" duration="100"/><frame tileid="2" duration="150"/></animation>
+                     <objectgroup><object id="1" x="0" y="0" width="16" height="8"/></objectgroup>
+                   </tile>
+                 </tileset>
+                 <tileset firstgid="5" source="walls.tsx"/>
+                 <layer name="ground" width="2" height="2"><data encoding="csv">1,2,
+{},0</data></layer>
+                 <group>
+                   <layer name="walls" width="2" height="2"><data encoding="base64" compression="zlib">{}</data></layer>
+                   <objectgroup>
+                     <object name="spawn" class="Trigger" x="4" y="8"><point/></object>
+                     <object name="lake" type="water" x="0" y="16"><polygon points="0,0 16,0 8,8"/>
+                       <properties><property name="depth" type="int" value="3"/></properties>
+                     </object>
+                   </objectgroup>
+                 </group>
+               </map>"#,
+            2 | FLIPPED_HORIZONTALLY,
+            encode(&[0, 6, 5, 0], "zlib")
+        );
+        std::fs::write(directory.join("map.tmx"), tmx).unwrap();
+
+        let (map, tilesets) = import_tiled_map(directory.join("map.tmx").to_str().unwrap()).unwrap();
+
+        assert_eq!(vec!["ground", "walls"], tilesets.iter().map(|t| t.name()).collect::<Vec<_>>());
+        assert!(tilesets[0].texture().ends_with("ground.png"));
+        assert_eq!(2, tilesets[0].atlas().height);
+        assert!(tilesets[0].atlas().pathing.as_ref().unwrap()["water"].contains(&1));
+        let tile = tilesets[0].atlas().tile_config_for(1);
+        assert_eq!(2, tile.animation.as_ref().unwrap().len());
+        assert_eq!(&TileObjectClass::CollisionArea, tile.objects()[0].get_class());
+
+        assert_eq!("forest", map.properties["music"]);
+        assert_eq!(vec![vec![0, 1], vec![1, -1]], map.layers[0].tiles);
+        assert_eq!(TileFlip { horizontal: true, vertical: false, diagonal: false }, map.flip_at(&Position::new(0, 1, 0)));
+        assert!(!map.flip_at(&Position::new(1, 0, 0)).is_flipped());
+        // The tiles of the external tileset come after the 4 tiles of the first one
+        assert_eq!(vec![vec![-1, 5], vec![4, -1]], map.layers[1].tiles);
+        assert_eq!((1, 1), map.resolve_tile(5));
+
+        let objects = map.get_objects();
+        assert_eq!(&TileObjectClass::Trigger, objects[0].get_class());
+        assert_eq!("spawn", objects[0].get_name().unwrap());
+        assert!(objects[1].get_class().is_custom("water"));
+        assert_eq!(3, objects[1].get_polygon().len());
+        assert_eq!("3", objects[1].get_property("depth").unwrap());
+    }
+
+    #[test]
+    fn tmj_isometric_map() {
+        let tmj = format!(
+            r##"{{
+                "orientation": "isometric", "width": 2, "height": 1, "tilewidth": 32, "tileheight": 16,
+                "properties": [{{ "name": "dark", "type": "bool", "value": true }}],
+                "tilesets": [{{ "firstgid": 1, "name": "iso", "tilewidth": 32, "tileheight": 16, "tilecount": 3,
+                                "columns": 3, "image": "iso.png",
+                                "tiles": [{{ "id": 0, "animation": [{{ "tileid": 0, "duration": 50 }}] }}] }}],
+                "layers": [
+                    {{ "type": "tilelayer", "name": "floor", "data": [3, 1], "opacity": 0.5, "tintcolor": "#80ff0000", "parallaxx": 0.5 }},
+                    {{ "type": "group", "layers": [
+                        {{ "type": "tilelayer", "name": "top", "encoding": "base64", "compression": "gzip", "data": "{}" }}
+                    ]}},
+                    {{ "type": "objectgroup", "objects": [{{ "name": "chest", "type": "Item", "x": 1, "y": 2, "width": 3, "height": 4 }}] }}
+                ]
+            }}"##,
+            encode(&[0, 2 | FLIPPED_DIAGONALLY], "gzip")
+        );
+
+        let (map, tilesets) = parse_tmj(&tmj, Path::new("maps")).unwrap();
+
+        assert_eq!("true", map.properties["dark"]);
+        assert_eq!(Path::new("maps").join("iso.png").to_string_lossy(), tilesets[0].texture());
+        assert_eq!(vec![vec![2, 0]], map.layers[0].tiles);
+        assert_eq!("#ff000080", map.layers[0].properties["tint"]);
+        assert_eq!("0.5", map.layers[0].properties["opacity"]);
+        assert_eq!("0.5", map.layers[0].properties["parallax_x"]);
+        assert_eq!(vec![vec![-1, 1]], map.layers[1].tiles);
+        assert!(map.flip_at(&Position::new(1, 0, 1)).diagonal);
+        let Some(TilemapType::Isometric { offset_x, offset_y, .. }) = map.tilemap_type else { panic!("The map must be isometric") };
+        assert_eq!((16., -16.), (offset_x.x_multiplier, offset_x.y_multiplier));
+        assert_eq!((-8., 24.), (offset_y.x_multiplier, offset_y.y_multiplier));
+        assert_eq!(&TileObjectClass::Item, map.get_objects()[0].get_class());
+        assert_eq!(3., map.get_objects()[0].get_rect().width());
+        assert!(matches!(parse_tmj(&tmj.replace("isometric", "oblique"), Path::new("")), Err(TiledError::Unsupported(_))));
+
+        let hexagonal = tmj.replace(r#""orientation": "isometric""#, r#""orientation": "hexagonal", "staggeraxis": "x", "hexsidelength": 8"#);
+        let (map, _) = parse_tmj(&hexagonal, Path::new("")).unwrap();
+        assert!(matches!(
+            map.tilemap_type,
+            Some(TilemapType::Hexagonal { stagger_axis: StaggerAxis::X, stagger_index: StaggerIndex::Odd, side_length: 8 })
+        ));
+    }
+}
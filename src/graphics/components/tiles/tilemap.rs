@@ -1,22 +1,30 @@
-use std::{collections::HashMap, ops::Range};
+use std::{collections::HashMap, ops::Range, sync::Arc};
 
 use hecs::Entity;
+use log::warn;
 use serde::{Deserialize, Serialize};
 use wgpu::{util::BufferInitDescriptor, PrimitiveTopology};
 
+use crate::core::components::maths::camera::Camera;
+use crate::core::components::maths::collider::Collider;
 use crate::core::components::maths::hierarchy::Parent;
 use crate::core::components::maths::transform::Transform;
 use crate::core::resources::asset_manager::AssetManager;
-use crate::core::world::{ScionWorld, World};
+use crate::core::world::{GameData, ScionWorld, World};
 use crate::{
     core::resources::asset_manager::AssetRef,
     graphics::rendering::Renderable2D,
     graphics::components::{
         animations::{Animation, Animations},
         material::Material,
-        tiles::sprite::Sprite,
+        tiles::atlas::data::{FlippedTile, TileObject, TiledTilemapTileset, TilemapAtlas, TilemapEvent, TilemapLayer, TilePathing},
+        tiles::chunk::{ChunkSettings, TilemapChunks},
+        tiles::hex::{StaggerAxis, StaggerIndex, StaggeredGrid},
+        tiles::layer::{LayerSettings, LAYER_SETTINGS_PROPERTIES},
+        tiles::tileset::Tileset,
+        tiles::sprite::{Sprite, TileFlip},
     },
-    utils::maths::{Dimensions, Position},
+    utils::maths::{Dimensions, Position, Vector},
 };
 
 #[derive(Debug)]
@@ -24,7 +32,7 @@ pub struct Pathing {
     pathing_type: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileEvent {
     event_type: String,
     properties: HashMap<String, String>,
@@ -45,6 +53,10 @@ impl TileEvent {
     pub fn properties(&mut self) -> &mut HashMap<String, String> {
         &mut self.properties
     }
+
+    pub fn property(&self, key: &str) -> Option<&String> {
+        self.properties.get(key)
+    }
 }
 
 
@@ -56,6 +68,43 @@ pub enum TilemapType {
         offset_y : OffsetMultiplier,
         offset_z : OffsetMultiplier,
     },
+    /// Hexagons, every other line being shifted by half a tile. `side_length` is the length in pixels
+    /// of the flat side of the hexagons along the stagger axis
+    Hexagonal {
+        stagger_axis: StaggerAxis,
+        stagger_index: StaggerIndex,
+        side_length: usize,
+    },
+    /// Isometric diamonds placed in lines, every other line being shifted by half a tile
+    Staggered {
+        stagger_axis: StaggerAxis,
+        stagger_index: StaggerIndex,
+    },
+}
+
+impl TilemapType {
+    /// Pointy-topped hexagons, odd rows being shifted to the right
+    pub fn pointy_hexagonal(side_length: usize) -> Self {
+        TilemapType::Hexagonal { stagger_axis: StaggerAxis::Y, stagger_index: StaggerIndex::Odd, side_length }
+    }
+
+    /// Flat-topped hexagons, odd columns being shifted down
+    pub fn flat_hexagonal(side_length: usize) -> Self {
+        TilemapType::Hexagonal { stagger_axis: StaggerAxis::X, stagger_index: StaggerIndex::Odd, side_length }
+    }
+
+    /// Grid of the hexagonal and staggered tilemap types
+    pub fn staggered_grid(&self) -> Option<StaggeredGrid> {
+        match *self {
+            TilemapType::Hexagonal { stagger_axis, stagger_index, side_length } => {
+                Some(StaggeredGrid { axis: stagger_axis, index: stagger_index, side_length: Some(side_length as f32) })
+            }
+            TilemapType::Staggered { stagger_axis, stagger_index } => {
+                Some(StaggeredGrid { axis: stagger_axis, index: stagger_index, side_length: None })
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Copy, Clone)]
@@ -68,6 +117,10 @@ pub struct OffsetMultiplier{
 pub struct Tile {
     pub(crate) position: Position,
     pub(crate) tilemap: Entity,
+    /// Index of the tileset of this tile in its tilemap, the sprite's tile number is local to this tileset
+    pub(crate) tileset: usize,
+    /// Chunk of this tile, for the chunked tilemaps
+    pub(crate) chunk: Option<Entity>,
 }
 
 impl Tile{
@@ -75,6 +128,15 @@ impl Tile{
         &self.position
     }
 
+    pub fn get_tileset(&self) -> usize {
+        self.tileset
+    }
+
+    /// Entity rendering this tile : its chunk for the chunked tilemaps, its tilemap otherwise
+    pub(crate) fn mesh(&self) -> Entity {
+        self.chunk.unwrap_or(self.tilemap)
+    }
+
     pub fn get_tilemap_entity(&self) -> Entity {
         self.tilemap
     }
@@ -83,17 +145,19 @@ impl Tile{
 /// Struct representing a single tile in a tilemap. Needs to be returned in the
 /// tile resolver function when creating a tilemap.
 pub struct TileInfos {
-    tile_nb: Option<usize>,
+    pub(crate) tile_nb: Option<usize>,
     animations: Option<Animations>,
     event: Option<TileEvent>,
-    pathing_type: Option<String>,
+    pub(crate) pathing_type: Option<String>,
     custom_offset: Option<Transform>,
+    pub(crate) flip: TileFlip,
 }
 
 impl TileInfos {
-    /// Creates a new TileInfos struct
+    /// Creates a new TileInfos struct. `tile_nb` is a global tile id of the tilemap, resolved to
+    /// one of its tilesets. Animations of the tile use tile numbers local to this tileset.
     pub fn new(tile_nb: Option<usize>) -> Self {
-        Self { tile_nb, animations: None, event: None, pathing_type: None, custom_offset: None }
+        Self { tile_nb, animations: None, event: None, pathing_type: None, custom_offset: None, flip: TileFlip::default() }
     }
 
     /// Adds an event to the current tile.
@@ -128,6 +192,12 @@ impl TileInfos {
         self
     }
 
+    /// Flips the sprite of the current tile
+    pub fn with_flip(mut self, flip: TileFlip) -> Self {
+        self.flip = flip;
+        self
+    }
+
     /// Adds custom offset to the current tile
     pub fn with_custom_offset(mut self, offset: Option<Transform>) -> Self {
         self.custom_offset = offset;
@@ -139,137 +209,325 @@ impl TileInfos {
 pub struct TilemapInfo {
     dimensions: Dimensions,
     transform: Transform,
-    tileset_ref: AssetRef<Material>,
-    tilemap_type: TilemapType
+    tilesets: Vec<TilemapTileset>,
+    tilemap_type: TilemapType,
+    layers: HashMap<usize, LayerSettings>,
+    metadata: TilemapMetadata,
+}
+
+/// Data of the `TilemapAtlas` a tilemap has been loaded from that the tilemap doesn't use itself,
+/// kept to be written back by `Tilemap::export`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TilemapMetadata {
+    properties: HashMap<String, String>,
+    objects: Vec<TileObject>,
+    /// Name and custom properties of each layer
+    layers: Vec<(String, HashMap<String, String>)>,
+}
+
+impl TilemapMetadata {
+    pub(crate) fn from_atlas(atlas: &TilemapAtlas) -> Self {
+        let custom_properties = |properties: &HashMap<String, String>| {
+            properties.iter().filter(|(key, _)| !LAYER_SETTINGS_PROPERTIES.contains(&key.as_str())).map(|(k, v)| (k.clone(), v.clone())).collect()
+        };
+        Self {
+            properties: atlas.properties.clone(),
+            objects: atlas.objects.clone(),
+            layers: atlas.layers.iter().map(|layer| (layer.name.clone(), custom_properties(&layer.properties))).collect(),
+        }
+    }
 }
 
 impl TilemapInfo {
+    /// `tileset_ref` is the main tileset of the tilemap, starting at the global tile id 0
     pub fn new(
         dimensions: Dimensions,
         transform: Transform,
         tileset_ref: AssetRef<Material>,
         tilemap_type: TilemapType
     ) -> Self {
-        Self { dimensions, transform, tileset_ref, tilemap_type }
+        Self {
+            dimensions,
+            transform,
+            tilesets: vec![TilemapTileset { first_tile: 0, tileset_ref }],
+            tilemap_type,
+            layers: HashMap::new(),
+            metadata: TilemapMetadata::default(),
+        }
+    }
+
+    /// Adds a tileset to the tilemap. Global tile ids from `first_tile` up to the first tile of the
+    /// next tileset are resolved to this tileset, as `global id - first_tile`
+    pub fn with_tileset(mut self, tileset_ref: AssetRef<Material>, first_tile: usize) -> Self {
+        self.tilesets.push(TilemapTileset { first_tile, tileset_ref });
+        self
+    }
+
+    /// Rendering settings of the layer `z`, layers without settings using the default ones
+    pub fn with_layer_settings(mut self, z: usize, settings: LayerSettings) -> Self {
+        self.layers.insert(z, settings);
+        self
+    }
+
+    /// Keeps the data of the atlas the tilemap is loaded from, to export it back
+    pub(crate) fn with_metadata(mut self, metadata: TilemapMetadata) -> Self {
+        self.metadata = metadata;
+        self
     }
 }
 
+fn resolve_tile(tilesets: &[TilemapTileset], tile_nb: usize) -> (usize, usize) {
+    let (tileset, first_tile) = tilesets
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.first_tile <= tile_nb)
+        .max_by_key(|(_, t)| t.first_tile)
+        .map_or((0, 0), |(index, t)| (index, t.first_tile));
+    (tileset, tile_nb - first_tile)
+}
+
+/// A tileset used by a tilemap, with the first global tile id resolved to it
+#[derive(Clone)]
+struct TilemapTileset {
+    first_tile: usize,
+    tileset_ref: AssetRef<Material>,
+}
+
 /// `Tilemap` is `Scion` convenience component to create a full multi layered tilemap.
 pub struct Tilemap {
     tile_entities: HashMap<Position, Entity>,
     events: HashMap<Position, TileEvent>,
-    tileset_ref: AssetRef<Material>,
+    tilesets: Vec<TilemapTileset>,
     tilemap_type: TilemapType,
     width: usize,
     height: usize,
     depth: usize,
-
+    /// Incremented each time a tile of the tilemap is modified
+    revision: u64,
+    /// Chunks of a chunked tilemap, `None` when all the tiles are created with the tilemap
+    pub(crate) chunks: Option<TilemapChunks>,
+    /// Rendering settings of each layer
+    layers: Vec<LayerSettings>,
+    /// Whether the settings of a layer changed since the tilemap was last rendered
+    pub(crate) layers_dirty: bool,
+    /// Data of the loaded atlas written back on export
+    metadata: TilemapMetadata,
 }
 
 impl Tilemap {
-    pub(crate) fn new(tileset_ref: AssetRef<Material>, tilemap_type: TilemapType, dimensions: &Dimensions) -> Self {
-        Self { tile_entities: Default::default(), events: HashMap::default(), tileset_ref, tilemap_type, width: dimensions.width(), height: dimensions.height(), depth: dimensions.depth() }
+    fn new(infos: &mut TilemapInfo) -> Self {
+        let layers = (0..infos.dimensions.depth()).map(|z| infos.layers.remove(&z).unwrap_or_default()).collect();
+        Self {
+            tile_entities: Default::default(),
+            events: HashMap::default(),
+            tilesets: infos.tilesets.clone(),
+            tilemap_type: infos.tilemap_type,
+            width: infos.dimensions.width(),
+            height: infos.dimensions.height(),
+            depth: infos.dimensions.depth(),
+            revision: 0,
+            chunks: None,
+            layers,
+            layers_dirty: false,
+            metadata: std::mem::take(&mut infos.metadata),
+        }
     }
 
     /// Convenience fn to create a tilemap and add it to the world.
     /// tile_resolver is a function taking a 3D position as parameter and a `TileInfos`
     /// as a return. This way, the tilemap knows exactly what to add at which coordinates.
-    pub fn create<F>(infos: TilemapInfo, world: &mut impl World, mut tile_resolver: F) -> Entity
+    pub fn create<F>(mut infos: TilemapInfo, world: &mut impl World, mut tile_resolver: F) -> Entity
     where
         F: FnMut(&Position) -> TileInfos,
     {
-        let self_entity = Tilemap::create_tilemap(world, infos.tileset_ref, infos.transform, infos.tilemap_type, &infos.dimensions);
+        let self_entity = Tilemap::create_tilemap(world, Self::new(&mut infos), infos.transform);
 
         for x in 0..infos.dimensions.width() {
             for y in 0..infos.dimensions.height() {
                 for z in 0..infos.dimensions.depth() {
                     let position = Position::new(x, y, z);
                     let tile_infos = tile_resolver(&position);
+                    Tilemap::spawn_tile(world, self_entity, None, position, tile_infos);
+                }
+            }
+        }
 
-                    let entity = world.push((
-                        Tile { position: position.clone(), tilemap: self_entity },
-                        Parent::new(self_entity),
-                    ));
+        self_entity
+    }
 
-                    if let Some(tile_nb) = tile_infos.tile_nb {
-                        let _r = world.add_components(entity, (Sprite::new(tile_nb),));
-                    }
+    /// Creates a chunked tilemap and adds it to the world. Instead of creating all its tiles, the tilemap is split
+    /// into chunks, loaded when they come near a camera and unloaded when they go away. Each chunk is rendered as a
+    /// single mesh. `tile_resolver` is kept by the tilemap to create the tiles of the chunks when they are loaded,
+    /// tiles modified with `modify_sprite_tile` being remembered while their chunk is unloaded.
+    pub fn create_chunked<F>(mut infos: TilemapInfo, settings: ChunkSettings, world: &mut impl World, tile_resolver: F) -> Entity
+    where
+        F: Fn(&Position) -> TileInfos + Send + Sync + 'static,
+    {
+        if settings.chunk_width * settings.chunk_height * infos.dimensions.depth() * Sprite::indices().len() > u16::MAX as usize {
+            panic!("The chunks of a tilemap are too big to be rendered, use smaller chunks");
+        }
+        let mut tilemap = Self::new(&mut infos);
+        tilemap.chunks = Some(TilemapChunks::new(settings, Arc::new(tile_resolver)));
+        Tilemap::create_tilemap(world, tilemap, infos.transform)
+    }
 
-                    if let Some(offset) = tile_infos.custom_offset {
-                        let _r = world.add_components(entity, (offset,));
-                    } else {
-                        let _r = world.add_components(entity, (Transform::default(),));
-                    }
+    /// Creates the entity of the tile at `position`, in the chunk `chunk` of a chunked tilemap
+    pub(crate) fn spawn_tile(world: &mut impl World, tilemap_entity: Entity, chunk: Option<Entity>, position: Position, tile_infos: TileInfos) {
+        let resolved_tile = tile_infos.tile_nb.map(|tile_nb| world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap().resolve_tile(tile_nb));
 
-                    if let Some(animations) = tile_infos.animations {
-                        let _r = world.add_components(
-                            entity,
-                            (animations,),
-                        );
-                    }
+        let entity = world.push((
+            Tile { position: position.clone(), tilemap: tilemap_entity, tileset: resolved_tile.map_or(0, |(tileset, _)| tileset), chunk },
+            Parent::new(chunk.unwrap_or(tilemap_entity)),
+        ));
 
-                    if let Some(pathing) = tile_infos.pathing_type {
-                        let _r = world.add_components(entity, (Pathing { pathing_type: pathing },));
-                    }
+        if let Some((_, local_tile)) = resolved_tile {
+            let _r = world.add_components(entity, (Sprite::new(local_tile).with_flip(tile_infos.flip),));
+        }
 
-                    if let Some(event) = tile_infos.event {
-                        world
-                            .entry_mut::<&mut Tilemap>(self_entity)
-                            .unwrap()
-                            .events
-                            .insert(position.clone(), event);
-                    }
+        if let Some(offset) = tile_infos.custom_offset {
+            let _r = world.add_components(entity, (offset,));
+        } else {
+            let _r = world.add_components(entity, (Transform::default(),));
+        }
 
-                    world
-                        .entry_mut::<&mut Tilemap>(self_entity)
-                        .unwrap()
-                        .tile_entities
-                        .insert(position, entity);
-                }
-            }
+        if let Some(animations) = tile_infos.animations {
+            let _r = world.add_components(
+                entity,
+                (animations,),
+            );
         }
 
-        self_entity
+        if let Some(pathing) = tile_infos.pathing_type {
+            let _r = world.add_components(entity, (Pathing { pathing_type: pathing },));
+        }
+
+        let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+        if let Some(event) = tile_infos.event {
+            // Events of a reloaded chunk are kept as they were modified
+            tilemap.events.entry(position.clone()).or_insert(event);
+        }
+        tilemap.tile_entities.insert(position, entity);
     }
 
-    /// Try to modify the sprite's tile at a given position
+    /// Try to modify the sprite's tile at a given position, `new_tile_nb` being a global tile id
     pub fn modify_sprite_tile(
         world: &mut impl World,
         tilemap_entity: Entity,
         tile_position: Position,
         new_tile_nb: usize,
     ) {
-        let tile = world
-            .entry_mut::<&mut Tilemap>(tilemap_entity)
-            .unwrap()
-            .tile_entities
-            .get(&tile_position)
-            .as_ref()
-            .map(|e| **e);
+        let (tile, (tileset, local_tile)) = {
+            let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+            tilemap.revision += 1;
+            if let Some(chunks) = tilemap.chunks.as_mut() {
+                chunks.modified_tiles.insert(tile_position.clone(), new_tile_nb);
+            }
+            (tilemap.tile_entities.get(&tile_position).as_ref().map(|e| **e), tilemap.resolve_tile(new_tile_nb))
+        };
+        if let Ok(collider) = world.entry_mut::<&mut Collider>(tilemap_entity) {
+            collider.refresh_grid();
+        }
         if let Some(tile) = tile {
+            if let Ok(tile_component) = world.entry_mut::<&mut Tile>(tile) {
+                tile_component.tileset = tileset;
+            }
             let entry = world.entry_mut::<&mut Sprite>(tile);
             if let Ok(sprite) = entry {
-                sprite.set_tile_nb(new_tile_nb);
+                sprite.set_tile_nb(local_tile);
             } else {
-                let _r = world.add_components(tile, (Sprite::new(new_tile_nb),));
+                let _r = world.add_components(tile, (Sprite::new(local_tile),));
+            }
+        }
+    }
+
+    /// Forces the pathing value at a given position, overriding the pathing of the tile in its tileset.
+    /// `None` removes a previously forced pathing
+    pub fn modify_pathing(world: &mut impl World, tilemap_entity: Entity, tile_position: Position, pathing: Option<String>) {
+        let tile = {
+            let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).unwrap();
+            tilemap.revision += 1;
+            if let Some(chunks) = tilemap.chunks.as_mut() {
+                chunks.modified_pathing.insert(tile_position.clone(), pathing.clone());
+            }
+            tilemap.tile_entities.get(&tile_position).copied()
+        };
+        if let Ok(collider) = world.entry_mut::<&mut Collider>(tilemap_entity) {
+            collider.refresh_grid();
+        }
+        if let Some(tile) = tile {
+            match pathing {
+                Some(pathing_type) => {
+                    let _r = world.add_components(tile, (Pathing { pathing_type },));
+                }
+                None => {
+                    let _r = world.remove_component::<Pathing>(tile);
+                }
             }
         }
     }
 
+    /// Paints `terrain` at a given position, using the terrain sets of the tilemap's tilesets (see `Tileset::with_terrain_set`).
+    /// The tile at this position and its neighbours belonging to the same terrain set are replaced by the tiles
+    /// best matching the terrains around them
+    pub fn paint_terrain(data: &mut GameData, tilemap_entity: Entity, position: Position, terrain: &str) {
+        let (terrain_set, tileset, dimensions) = {
+            let (world, resources) = data.split();
+            let assets = resources.assets();
+            let mut entry = world.entry::<&Tilemap>(tilemap_entity).expect("Missing tilemap to paint");
+            let tilemap = entry.get().expect("Missing tilemap to paint");
+            let found = tilemap.tilesets.iter().enumerate().find_map(|(index, t)| {
+                assets.retrieve_tileset(&t.tileset_ref).and_then(|tileset| tileset.terrain_set_for(terrain)).map(|set| (set.clone(), index))
+            });
+            let Some((terrain_set, tileset)) = found else {
+                warn!("No terrain set of the tilemap defines the terrain '{}'", terrain);
+                return;
+            };
+            (terrain_set, tileset, (tilemap.width, tilemap.height))
+        };
+        let terrain_id = terrain_set.terrain(terrain).expect("Missing terrain in its terrain set");
+
+        for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+            let (x, y) = (position.x() as isize + dx, position.y() as isize + dy);
+            if x < 0 || y < 0 || x as usize >= dimensions.0 || y as usize >= dimensions.1 {
+                continue;
+            }
+            let neighbour = Position::new(x as usize, y as usize, position.z());
+            let current = Tilemap::retrieve_sprite_tile(data, tilemap_entity, &neighbour).and_then(|global_tile| {
+                let (tile_tileset, local_tile) = data.entry::<&Tilemap>(tilemap_entity).unwrap().get().unwrap().resolve_tile(global_tile);
+                (tile_tileset == tileset && terrain_set.terrain_id(local_tile).is_some()).then_some(local_tile)
+            });
+            // Only the painted tile and the tiles already part of the terrain set are modified
+            if current.is_none() && (dx, dy) != (0, 0) {
+                continue;
+            }
+            let Some(wanted) = terrain_set.wanted_around(current.and_then(|t| terrain_set.terrain_id(t)), (dx, dy), terrain_id) else {
+                continue;
+            };
+            if let Some(tile) = terrain_set.best_tile(&wanted).filter(|tile| Some(*tile) != current) {
+                let global_tile = data.entry::<&Tilemap>(tilemap_entity).unwrap().get().unwrap().global_tile(tileset, tile);
+                Tilemap::modify_sprite_tile(data, tilemap_entity, neighbour, global_tile);
+            }
+        }
+    }
+
+    /// Retrieves the global tile id of the sprite at this position in the tilemap
     pub fn retrieve_sprite_tile(
         world: &mut impl World,
         entity: Entity,
         tile_position: &Position,
     ) -> Option<usize> {
-        let tile = world
-            .entry_mut::<&mut Tilemap>(entity)
-            .unwrap()
-            .tile_entities
-            .get(tile_position)
-            .as_ref()
-            .map(|e| **e);
+        let tilemap = world.entry_mut::<&mut Tilemap>(entity).unwrap();
+        let tile = tilemap.tile_entities.get(tile_position).as_ref().map(|e| **e);
+        if tile.is_none() {
+            return tilemap.unloaded_tile(tile_position).and_then(|infos| infos.tile_nb);
+        }
+        let first_tiles: Vec<usize> = tilemap.tilesets.iter().map(|t| t.first_tile).collect();
         if let Some(tile) = tile {
-            return world.entry::<&Sprite>(tile).unwrap().get().map(|s| s.get_tile_nb());
+            return world
+                .entry::<(&Tile, &Sprite)>(tile)
+                .unwrap()
+                .get()
+                .map(|(tile, sprite)| first_tiles[tile.tileset] + sprite.get_tile_nb());
         }
         None
     }
@@ -281,37 +539,131 @@ impl Tilemap {
         tile_position: &Position,
         asset_manager: &AssetManager,
     ) -> Option<String> {
-        let (tile, tileset_ref) = {
+        let (tile, tilesets, unloaded_tile) = {
             let mut res = world.entry::<&Tilemap>(entity).unwrap();
-            let tilemap = res.get();
-            (
-                tilemap
-                    .as_ref()
-                    .unwrap()
-                    .tile_entities
-                    .get(tile_position)
-                    .as_ref()
-                    .map(|e| **e),
-                tilemap.as_ref().unwrap().tileset_ref.clone(),
-            )
+            let tilemap = res.get().unwrap();
+            let tile = tilemap.tile_entities.get(tile_position).copied();
+            let unloaded_tile = if tile.is_none() { tilemap.unloaded_tile(tile_position) } else { None };
+            (tile, tilemap.tilesets.clone(), unloaded_tile)
         };
-        if let Some(tile) = tile {
-            if let Ok(mut entry) = world.entry::<&Pathing>(tile) {
-                if let Some(path_value) = entry.get() {
-                    return Some(path_value.pathing_type.to_string());
-                }
+        if let Some(infos) = unloaded_tile {
+            if infos.pathing_type.is_some() {
+                return infos.pathing_type;
             }
+            let (tileset, local_tile) = resolve_tile(&tilesets, infos.tile_nb?);
+            let tileset = asset_manager.retrieve_tileset(&tilesets[tileset].tileset_ref)?;
+            return tileset.pathing.iter().find(|(_k, v)| v.contains(&local_tile)).map(|(pathing, _)| pathing.to_string());
         }
+        let tile = tile?;
+        if let Ok(mut entry) = world.entry::<&Pathing>(tile) {
+            if let Some(path_value) = entry.get() {
+                return Some(path_value.pathing_type.to_string());
+            }
+        }
+
+        let mut entry = world.entry::<(&Tile, &Sprite)>(tile).ok()?;
+        let (tile, sprite) = entry.get()?;
+        let tileset = asset_manager.retrieve_tileset(&tilesets.get(tile.tileset)?.tileset_ref)?;
+        tileset
+            .pathing
+            .iter()
+            .find(|(_k, v)| v.contains(&sprite.get_tile_nb()))
+            .map(|(pathing, _)| pathing.to_string())
+    }
 
-        if let Some(tileset) = asset_manager.retrieve_tileset(&tileset_ref) {
-            if let Some(sprite) = Tilemap::retrieve_sprite_tile(world, entity, tile_position) {
-                let val = tileset.pathing.iter().find(|(_k, v)| v.contains(&sprite));
-                if let Some(entry) = val {
-                    return Some(entry.0.to_string());
+    /// Exports the current state of the tilemap `entity` into a `TilemapAtlas`, ready to be written
+    /// in the .scion format with `exporter::export_tilemap`. Modified tiles, forced pathing and events
+    /// are exported, including those of the unloaded chunks of a chunked tilemap.
+    /// Global tile ids are renumbered to follow the .scion format, one tileset after the other.
+    /// The properties, objects, layer names and custom layer properties of the atlas the tilemap
+    /// has been loaded from are exported unchanged
+    pub fn export(data: &mut GameData, entity: Entity) -> TilemapAtlas {
+        let (world, resources) = data.split();
+        let assets = resources.assets();
+        let mut entry = world.entry::<&Tilemap>(entity).expect("Missing tilemap to export");
+        let tilemap = entry.get().expect("Missing tilemap to export");
+
+        let tilesets: Vec<&Tileset> = tilemap
+            .tilesets
+            .iter()
+            .map(|t| assets.retrieve_tileset(&t.tileset_ref).expect("Missing tileset of the exported tilemap"))
+            .collect();
+        let atlas_tilesets: Vec<TiledTilemapTileset> = tilesets
+            .iter()
+            .enumerate()
+            .map(|(index, t)| TiledTilemapTileset { index, total_tiles: t.width * t.height, name: t.name.clone() })
+            .collect();
+        let first_tiles: Vec<usize> =
+            (0..atlas_tilesets.len()).map(|index| atlas_tilesets.iter().take(index).map(|t| t.total_tiles).sum()).collect();
+
+        let mut layers = Vec::new();
+        let mut pathing = Vec::new();
+        let mut events = Vec::new();
+        for z in 0..tilemap.depth {
+            let mut tiles = Vec::with_capacity(tilemap.height);
+            let mut flipped_tiles = Vec::new();
+            for y in 0..tilemap.height {
+                let mut line = Vec::with_capacity(tilemap.width);
+                for x in 0..tilemap.width {
+                    let position = Position::new(x, y, z);
+                    let (tile, flip, forced_pathing, event) = match tilemap.tile_entities.get(&position) {
+                        Some(tile_entity) => {
+                            let mut tile_entry = world.entry::<(&Tile, Option<&Sprite>, Option<&Pathing>)>(*tile_entity).unwrap();
+                            let (tile, sprite, forced_pathing) = tile_entry.get().unwrap();
+                            (
+                                sprite.map(|s| first_tiles[tile.tileset] + s.get_tile_nb()),
+                                sprite.map_or_else(TileFlip::default, |s| s.flip()),
+                                forced_pathing.map(|p| p.pathing_type.to_string()),
+                                tilemap.events.get(&position).cloned(),
+                            )
+                        }
+                        None => match tilemap.unloaded_tile(&position) {
+                            Some(infos) => (
+                                infos.tile_nb.map(|t| {
+                                    let (tileset, local_tile) = tilemap.resolve_tile(t);
+                                    first_tiles[tileset] + local_tile
+                                }),
+                                infos.flip,
+                                infos.pathing_type,
+                                tilemap.events.get(&position).cloned().or(infos.event),
+                            ),
+                            None => (None, TileFlip::default(), None, tilemap.events.get(&position).cloned()),
+                        },
+                    };
+                    line.push(tile.map_or(-1, |t| t as isize));
+                    if flip.is_flipped() {
+                        flipped_tiles.push(FlippedTile { x, y, flip });
+                    }
+                    if let Some(pathing_type) = forced_pathing {
+                        pathing.push(TilePathing { position: position.clone(), pathing: pathing_type });
+                    }
+                    if let Some(event) = event {
+                        events.push(TilemapEvent { position, event });
+                    }
                 }
+                tiles.push(line);
             }
+            let (name, mut properties) =
+                tilemap.metadata.layers.get(z).cloned().unwrap_or_else(|| (format!("layer_{}", z), HashMap::new()));
+            properties.extend(tilemap.layers[z].to_properties());
+            let mut layer = TilemapLayer { name, tiles_encoded: None, tiles, properties, flipped_tiles };
+            layer.encode_tiles();
+            layers.push(layer);
+        }
+
+        TilemapAtlas {
+            width: tilemap.width,
+            height: tilemap.height,
+            tile_width: tilesets[0].tile_width,
+            tile_height: tilesets[0].tile_height,
+            properties: tilemap.metadata.properties.clone(),
+            layers,
+            objects: tilemap.metadata.objects.clone(),
+            tilesets: atlas_tilesets,
+            tilemap_type: Some(tilemap.tilemap_type),
+            pathing,
+            events,
         }
-        None
     }
 
     /// Retrieves the mutable tile event associated with this position in the tilemap
@@ -319,23 +671,112 @@ impl Tilemap {
         self.events.get_mut(tile_position)
     }
 
-    fn create_tilemap(
-        world: &mut impl World,
-        tileset_ref: AssetRef<Material>,
-        transform: Transform,
-        tilemap_type: TilemapType,
-        dimensions: &Dimensions,
-    ) -> Entity {
-        world.push((Self::new(tileset_ref.clone(), tilemap_type, dimensions), tileset_ref, transform))
+    /// Retrieves the tile event associated with this position in the tilemap
+    pub fn event(&self, tile_position: &Position) -> Option<&TileEvent> {
+        self.events.get(tile_position)
+    }
+
+    /// Retrieves the entity of the tile at this position in the tilemap
+    pub fn tile_entity(&self, tile_position: &Position) -> Option<Entity> {
+        self.tile_entities.get(tile_position).copied()
+    }
+
+    fn create_tilemap(world: &mut impl World, tilemap: Tilemap, transform: Transform) -> Entity {
+        let tileset_ref = tilemap.tilesets[0].tileset_ref;
+        world.push((tilemap, tileset_ref, transform))
     }
 
     pub fn is_isometric(&self)-> bool{
         match self.tilemap_type {
             TilemapType::Standard => { false }
             TilemapType::Isometric { .. } => { true }
+            TilemapType::Hexagonal { .. } | TilemapType::Staggered { .. } => { false }
+        }
+    }
+
+    pub fn tilemap_type(&self) -> &TilemapType {
+        &self.tilemap_type
+    }
+
+    /// Rendering settings of the layer `z`
+    pub fn layer_settings(&self, z: usize) -> Option<&LayerSettings> {
+        self.layers.get(z)
+    }
+
+    /// Modifies the rendering settings of the layer `z` of the tilemap `tilemap_entity`, for example to hide
+    /// the roofs of a building when the player enters it
+    pub fn modify_layer(world: &mut impl World, tilemap_entity: Entity, z: usize, modifier: impl FnOnce(&mut LayerSettings)) {
+        let tilemap = world.entry_mut::<&mut Tilemap>(tilemap_entity).expect("Missing tilemap to modify");
+        match tilemap.layers.get_mut(z) {
+            Some(settings) => {
+                modifier(settings);
+                tilemap.layers_dirty = true;
+            }
+            None => warn!("The tilemap has no layer {}", z),
+        }
+    }
+
+    /// Shows or hides the layer `z` of the tilemap `tilemap_entity`
+    pub fn set_layer_visible(world: &mut impl World, tilemap_entity: Entity, z: usize, visible: bool) {
+        Tilemap::modify_layer(world, tilemap_entity, z, |settings| settings.set_visible(visible));
+    }
+
+
+    /// Neighbours of a tile on its layer : the 6 surrounding hexagons for hexagonal tilemaps, the tiles
+    /// sharing an edge with it otherwise
+    pub fn neighbours(&self, position: &Position) -> Vec<Position> {
+        let offsets = match self.tilemap_type.staggered_grid() {
+            Some(grid) => grid.neighbour_offsets(position.x(), position.y()),
+            None => vec![(1, 0), (-1, 0), (0, 1), (0, -1)],
+        };
+        offsets
+            .into_iter()
+            .filter_map(|(dx, dy)| Some((position.x().checked_add_signed(dx)?, position.y().checked_add_signed(dy)?)))
+            .filter(|(x, y)| *x < self.width && *y < self.height)
+            .map(|(x, y)| Position::new(x, y, position.z()))
+            .collect()
+    }
+
+    /// Number of moves from a tile to one of its `neighbours` needed to go from `from` to `to`, layers being ignored
+    pub fn distance(&self, from: &Position, to: &Position) -> usize {
+        match self.tilemap_type.staggered_grid() {
+            Some(grid) => grid.distance((from.x(), from.y()), (to.x(), to.y())),
+            None => from.x().abs_diff(to.x()) + from.y().abs_diff(to.y()),
         }
     }
 
+    /// Tile of the first layer at `point`, in pixels relative to the tilemap's position, for tiles of `tile_width` x `tile_height` pixels
+    pub fn cell_at(&self, point: &Vector, tile_width: usize, tile_height: usize) -> Option<Position> {
+        let (tile_width, tile_height) = (tile_width as f32, tile_height as f32);
+        let (x, y) = match self.tilemap_type.staggered_grid() {
+            Some(grid) => grid.cell_at((point.x(), point.y()), tile_width, tile_height),
+            None if self.is_isometric() => {
+                // The diamond of a tile is centered in its image
+                let (axis_x, axis_y) = self.grid_axes(tile_width);
+                let (px, py) = (point.x() - tile_width / 2., point.y() - tile_height / 2.);
+                let determinant = axis_x.x() * axis_y.y() - axis_x.y() * axis_y.x();
+                let x = (px * axis_y.y() - py * axis_y.x()) / determinant;
+                let y = (py * axis_x.x() - px * axis_x.y()) / determinant;
+                (x.round() as isize, y.round() as isize)
+            }
+            None => ((point.x() / tile_width).floor() as isize, (point.y() / tile_height).floor() as isize),
+        };
+        (x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height).then(|| Position::new(x as usize, y as usize, 0))
+    }
+
+    /// Tile of the first layer of the tilemap `tilemap_entity` under a point of the screen, such as the cursor
+    /// position given by `InputsController::mouse_xy`, as seen by the camera
+    pub fn screen_to_cell(data: &mut GameData, tilemap_entity: Entity, screen_point: &Vector) -> Option<Position> {
+        let camera = data.query::<(&Camera, &Transform)>().iter().next().map(|(_, (_, t))| *t.global_translation())?;
+        let (world, resources) = data.split();
+        let mut entry = world.entry::<(&Tilemap, &Transform)>(tilemap_entity).ok()?;
+        let (tilemap, transform) = entry.get()?;
+        let (tile_width, tile_height) = resources.assets().retrieve_tileset(tilemap.tileset_ref()).map(|t| (t.tile_width, t.tile_height))?;
+        let origin = transform.global_translation();
+        let point = Vector::new(camera.x() + screen_point.x() - origin.x(), camera.y() + screen_point.y() - origin.y());
+        tilemap.cell_at(&point, tile_width, tile_height)
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -348,6 +789,83 @@ impl Tilemap {
         self.depth
     }
 
+    /// Main tileset of the tilemap, defining its grid's tile size
+    pub fn tileset_ref(&self) -> &AssetRef<Material> {
+        &self.tilesets[0].tileset_ref
+    }
+
+    /// Tileset at `index` in the tilesets of this tilemap, as referenced by `Tile::get_tileset`
+    pub fn tileset_ref_at(&self, index: usize) -> Option<&AssetRef<Material>> {
+        self.tilesets.get(index).map(|t| &t.tileset_ref)
+    }
+
+    pub fn tilesets_count(&self) -> usize {
+        self.tilesets.len()
+    }
+
+    /// Resolves a global tile id to the index of its tileset and the tile id local to this tileset
+    pub fn resolve_tile(&self, tile_nb: usize) -> (usize, usize) {
+        resolve_tile(&self.tilesets, tile_nb)
+    }
+
+    pub(crate) fn remove_tile_entity(&mut self, position: &Position) -> Option<Entity> {
+        self.tile_entities.remove(position)
+    }
+
+    /// Whether this tilemap is split into chunks, loaded around the cameras
+    pub fn is_chunked(&self) -> bool {
+        self.chunks.is_some()
+    }
+
+    /// Chunks of the tilemap currently loaded, with the entity of each of them
+    pub fn loaded_chunks(&self) -> Vec<((usize, usize), Entity)> {
+        self.chunks.as_ref().map_or_else(Vec::new, |c| c.loaded.iter().map(|(chunk, e)| (*chunk, *e)).collect())
+    }
+
+    /// Entities holding the meshes of this tilemap : its loaded chunks for the chunked tilemaps,
+    /// the tilemap itself otherwise
+    pub(crate) fn mesh_entities(&self, self_entity: Entity) -> Vec<Entity> {
+        match self.chunks.as_ref() {
+            Some(chunks) => chunks.loaded.values().copied().collect(),
+            None => vec![self_entity],
+        }
+    }
+
+    /// Infos of the tile at `position` of a chunked tilemap, when its chunk is not loaded
+    fn unloaded_tile(&self, position: &Position) -> Option<TileInfos> {
+        if position.x() >= self.width || position.y() >= self.height || position.z() >= self.depth {
+            return None;
+        }
+        self.chunks.as_ref().map(|chunks| chunks.tile_infos(position))
+    }
+
+    /// Global tile id of the tile `local_tile_nb` of the tileset at `tileset`
+    pub fn global_tile(&self, tileset: usize, local_tile_nb: usize) -> usize {
+        self.tilesets[tileset].first_tile + local_tile_nb
+    }
+
+    /// Translations, relative to the tilemap, between a tile and its next tile along x and along y,
+    /// for tiles of `tile_size` pixels. Hexagonal and staggered tilemaps get the average translations, their
+    /// lines being shifted every other line
+    pub fn grid_axes(&self, tile_size: f32) -> (Vector, Vector) {
+        if let Some(grid) = self.tilemap_type.staggered_grid() {
+            let line_gap = (tile_size + grid.side_length.unwrap_or(0.)) / 2.;
+            return match grid.axis {
+                StaggerAxis::X => (Vector::new(line_gap, 0.), Vector::new(0., tile_size)),
+                StaggerAxis::Y => (Vector::new(tile_size, 0.), Vector::new(0., line_gap)),
+            };
+        }
+        (
+            Vector::new(tile_size - self.offset_x_multiplier_x(), -self.offset_y_multiplier_x()),
+            Vector::new(self.offset_x_multiplier_y(), tile_size - self.offset_y_multiplier_y()),
+        )
+    }
+
+    /// Number of modifications done on the tiles of this tilemap, used to invalidate the data computed from them
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     pub fn offset_x_multiplier_y(&self) -> f32 {
         if let TilemapType::Isometric { offset_x, .. } = self.tilemap_type{
             return offset_x.y_multiplier;
@@ -416,4 +934,179 @@ impl Renderable2D for Tilemap {
     fn set_dirty(&mut self, _is_dirty: bool) {
         todo!()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::world::GameData;
+    use crate::graphics::components::tiles::atlas::{exporter, importer};
+    use crate::graphics::components::tiles::autotile::{TerrainSet, TerrainSetKind};
+    use crate::graphics::components::tiles::test_utils::pathing_tileset;
+
+    #[test]
+    fn tiles_are_resolved_to_their_tileset() {
+        let mut data = GameData::default();
+        data.insert_resource(AssetManager::default());
+        let ground = pathing_tileset(&mut data, "ground", 4, &[("floor", &[0, 1])]);
+        let walls = pathing_tileset(&mut data, "walls", 4, &[("wall", &[0])]);
+        let infos = TilemapInfo::new(Dimensions::new(3, 1, 1), Transform::default(), ground, TilemapType::Standard)
+            .with_tileset(walls, 10);
+        let tilemap = Tilemap::create(infos, &mut data, |p| TileInfos::new(Some([1, 11, 10][p.x()])));
+
+        let tile = data.entry::<&Tilemap>(tilemap).unwrap().get().unwrap().tile_entity(&Position::new(1, 0, 0)).unwrap();
+        assert_eq!(1, data.entry::<&Tile>(tile).unwrap().get().unwrap().get_tileset());
+        assert_eq!(1, data.entry::<&Sprite>(tile).unwrap().get().unwrap().get_tile_nb());
+        assert_eq!(Some(11), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(1, 0, 0)));
+
+        let (world, resources) = data.split();
+        let assets = resources.assets();
+        let pathing = |world: &mut ScionWorld, x| Tilemap::retrieve_pathing(world, tilemap, &Position::new(x, 0, 0), &assets);
+        assert_eq!(Some("floor".to_string()), pathing(world, 0));
+        assert_eq!(None, pathing(world, 1));
+        assert_eq!(Some("wall".to_string()), pathing(world, 2));
+
+        Tilemap::modify_sprite_tile(world, tilemap, Position::new(0, 0, 0), 10);
+        assert_eq!(Some("wall".to_string()), pathing(world, 0));
+        assert_eq!(Some(10), Tilemap::retrieve_sprite_tile(world, tilemap, &Position::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn screen_points_are_converted_to_hexagonal_cells() {
+        let mut data = GameData::default();
+        data.insert_resource(AssetManager::default());
+        let hex = pathing_tileset(&mut data, "hex", 4, &[("floor", &[0])]);
+        data.push((Camera::new(320., 160.), Transform::from_xy(100., 50.)));
+        let infos = TilemapInfo::new(Dimensions::new(3, 3, 1), Transform::from_xy(100., 50.), hex, TilemapType::pointy_hexagonal(8));
+        let tilemap = Tilemap::create(infos, &mut data, |_| TileInfos::new(Some(0)));
+
+        // Rows are 12 pixels apart, the second one being shifted by half a tile
+        assert_eq!(Some(Position::new(0, 1, 0)), Tilemap::screen_to_cell(&mut data, tilemap, &Vector::new(20., 20.)));
+        assert_eq!(Some(Position::new(1, 1, 0)), Tilemap::screen_to_cell(&mut data, tilemap, &Vector::new(34., 18.)));
+        assert_eq!(None, Tilemap::screen_to_cell(&mut data, tilemap, &Vector::new(-20., 5.)));
+
+        let mut entry = data.entry::<&Tilemap>(tilemap).unwrap();
+        let tilemap = entry.get().unwrap();
+        assert_eq!(5, tilemap.neighbours(&Position::new(0, 1, 0)).len());
+        assert_eq!(2, tilemap.distance(&Position::new(0, 0, 0), &Position::new(1, 2, 0)));
+    }
+
+    #[test]
+    fn exported_tilemap_is_loaded_back_with_its_modifications() {
+        let mut data = GameData::default();
+        data.insert_resource(AssetManager::default());
+        let ground = pathing_tileset(&mut data, "ground", 4, &[("floor", &[0, 1])]);
+        let walls = pathing_tileset(&mut data, "walls", 4, &[("wall", &[0])]);
+        // Data of the atlas the tilemap would have been loaded from, that the tilemap doesn't use itself
+        let source: TilemapAtlas = serde_json::from_str(
+            r#"{"width": 3, "height": 2, "tile_width": 16, "tile_height": 16, "properties": {"weather": "rain"},
+               "layers": [{"name": "ground", "tiles_encoded": null, "properties": {"music": "cave", "opacity": "0.5"}}],
+               "objects": [{"name": "chest", "class": {"tag": "Item"}, "shape_type": "Point", "position": {"x": 8.0, "y": 24.0},
+                            "polygon": null, "rectangle": null, "properties": {"gold": "10"}}],
+               "tilesets": []}"#,
+        )
+        .unwrap();
+        let infos = TilemapInfo::new(Dimensions::new(3, 2, 1), Transform::default(), ground, TilemapType::Standard)
+            .with_tileset(walls, 10)
+            .with_metadata(TilemapMetadata::from_atlas(&source));
+        let tilemap = Tilemap::create(infos, &mut data, |p| {
+            let event = (p.x() == 2).then(|| TileEvent::new("door".to_string(), HashMap::new()));
+            TileInfos::new((p.y() == 0).then_some(p.x())).with_event(event)
+        });
+        Tilemap::modify_sprite_tile(&mut data, tilemap, Position::new(1, 1, 0), 11);
+        Tilemap::modify_pathing(&mut data, tilemap, Position::new(0, 1, 0), Some("water".to_string()));
+
+        let atlas = Tilemap::export(&mut data, tilemap);
+        // The walls tileset starts right after the 4 tiles of the ground tileset in the .scion format
+        assert_eq!(&vec![vec![0, 1, 2], vec![-1, 5, -1]], atlas.get_layers()[0].get_tiles());
+        assert_eq!(Some("water".to_string()), atlas.pathing_at(&Position::new(0, 1, 0)));
+        assert_eq!("door", atlas.event_at(&Position::new(2, 1, 0)).unwrap().event_type());
+
+        // Layer settings are exported from the tilemap, the opacity of the source being replaced
+        Tilemap::modify_layer(&mut data, tilemap, 0, |settings| settings.set_opacity(0.25));
+        let atlas = Tilemap::export(&mut data, tilemap);
+
+        let unique = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!("scion_exported_tilemap_{}_{}.scion", std::process::id(), unique));
+        exporter::export_tilemap(&atlas, path.to_str().unwrap()).unwrap();
+        let imported = importer::import_tilemap(path.to_str().unwrap());
+        let _r = std::fs::remove_file(&path);
+        assert_eq!(atlas.get_layers()[0].get_tiles(), imported.get_layers()[0].get_tiles());
+        assert_eq!(Some("water".to_string()), imported.pathing_at(&Position::new(0, 1, 0)));
+        assert_eq!(vec!["ground", "walls"], imported.tilesets.iter().map(|t| t.name.as_str()).collect::<Vec<_>>());
+        assert_eq!(Some(5), imported.tile_at(&Position::new(1, 1, 0)));
+        assert_eq!(Some(&"rain".to_string()), imported.properties.get("weather"));
+        assert_eq!("ground", imported.get_layers()[0].get_name());
+        assert_eq!(Some(&"cave".to_string()), imported.get_layers()[0].get_properties().get("music"));
+        assert_eq!(0.25, LayerSettings::from_properties(imported.get_layers()[0].get_properties()).opacity());
+        let chest = &imported.get_objects()[0];
+        assert_eq!((Some(&"chest".to_string()), Some(&"10".to_string())), (chest.get_name(), chest.get_property("gold")));
+    }
+
+    #[test]
+    fn layers_are_hidden_and_exported_with_their_settings() {
+        let mut data = GameData::default();
+        data.insert_resource(AssetManager::default());
+        let ground = pathing_tileset(&mut data, "ground", 4, &[("floor", &[0])]);
+        let infos = TilemapInfo::new(Dimensions::new(2, 2, 2), Transform::default(), ground, TilemapType::Standard)
+            .with_layer_settings(1, LayerSettings::default().with_opacity(0.5).with_parallax(0.5, 0.5));
+        let tilemap = Tilemap::create(infos, &mut data, |_| TileInfos::new(Some(0)));
+
+        Tilemap::set_layer_visible(&mut data, tilemap, 1, false);
+        {
+            let mut entry = data.entry::<&Tilemap>(tilemap).unwrap();
+            let tilemap = entry.get().unwrap();
+            assert!(tilemap.layers_dirty);
+            assert!(tilemap.layer_settings(0).unwrap().is_visible());
+            assert!(!tilemap.layer_settings(1).unwrap().is_visible());
+            assert_eq!([1., 1., 1., 0.], tilemap.layer_settings(1).unwrap().color());
+        }
+
+        let atlas = Tilemap::export(&mut data, tilemap);
+        assert!(atlas.get_layers()[0].get_properties().is_empty());
+        let settings = LayerSettings::from_properties(atlas.get_layers()[1].get_properties());
+        assert!(!settings.is_visible());
+        assert_eq!(0.5, settings.opacity());
+        assert_eq!((0.5, 0.5), settings.parallax());
+    }
+
+    #[test]
+    fn painted_terrain_updates_the_neighbouring_tiles() {
+        let mut data = GameData::default();
+        data.insert_resource(AssetManager::default());
+        // 2-corner set of grass and water, the 16 tiles being numbered by their water corners
+        let coast = (0..16).fold(TerrainSet::new("coast", TerrainSetKind::Corner, vec!["grass".to_string(), "water".to_string()]), |set, tile| {
+            let corner = |bit: usize| if tile & (1 << bit) != 0 { 2 } else { 1 };
+            set.with_tile(tile, [0, corner(0), 0, corner(1), 0, corner(2), 0, corner(3)])
+        });
+        let tileset = data.assets_mut().register_tileset(Tileset::new("coast".to_string(), "".to_string(), 4, 4, 16, 16).with_terrain_set(coast));
+        let infos = TilemapInfo::new(Dimensions::new(4, 3, 1), Transform::default(), tileset, TilemapType::Standard);
+        let tilemap = Tilemap::create(infos, &mut data, |p| TileInfos::new((p.x() < 3).then_some(0)));
+
+        Tilemap::paint_terrain(&mut data, tilemap, Position::new(1, 1, 0), "water");
+        let tiles: Vec<Vec<Option<usize>>> = (0..3)
+            .map(|y| (0..4).map(|x| Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(x, y, 0))).collect())
+            .collect();
+        assert_eq!(vec![Some(0b0010), Some(0b0110), Some(0b0100), None], tiles[0]);
+        assert_eq!(vec![Some(0b0011), Some(0b1111), Some(0b1100), None], tiles[1]);
+        assert_eq!(vec![Some(0b0001), Some(0b1001), Some(0b1000), None], tiles[2]);
+    }
+
+    #[test]
+    fn painted_terrain_keeps_the_tiles_outside_the_terrain_set() {
+        let mut data = GameData::default();
+        data.insert_resource(AssetManager::default());
+        // Tiles 0 to 15 are the coast terrain set, tile 16 is a rock from the same tileset
+        let coast = (0..16).fold(TerrainSet::new("coast", TerrainSetKind::Corner, vec!["grass".to_string(), "water".to_string()]), |set, tile| {
+            let corner = |bit: usize| if tile & (1 << bit) != 0 { 2 } else { 1 };
+            set.with_tile(tile, [0, corner(0), 0, corner(1), 0, corner(2), 0, corner(3)])
+        });
+        let tileset = data.assets_mut().register_tileset(Tileset::new("coast".to_string(), "".to_string(), 5, 4, 16, 16).with_terrain_set(coast));
+        let infos = TilemapInfo::new(Dimensions::new(3, 3, 1), Transform::default(), tileset, TilemapType::Standard);
+        let tilemap = Tilemap::create(infos, &mut data, |p| TileInfos::new(Some(if (p.x(), p.y()) == (2, 1) { 16 } else { 0 })));
+
+        Tilemap::paint_terrain(&mut data, tilemap, Position::new(1, 1, 0), "water");
+        assert_eq!(Some(16), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(2, 1, 0)));
+        assert_eq!(Some(0b1111), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(1, 1, 0)));
+        assert_eq!(Some(0b0011), Tilemap::retrieve_sprite_tile(&mut data, tilemap, &Position::new(0, 1, 0)));
+    }
+}
@@ -4,6 +4,8 @@ use std::path::Path;
 use log::error;
 
 use crate::graphics::components::tiles::atlas::data::{TileConfig, TilesetAtlas};
+use crate::graphics::components::tiles::autotile::TerrainSet;
+use crate::graphics::components::tiles::generation::wfc::AdjacencyRules;
 use crate::utils::file::read_file;
 
 #[derive(Clone, Debug)]
@@ -25,11 +27,15 @@ pub struct Tileset {
     pub(crate) pathing: HashMap<String, HashSet<usize>>,
     /// Configuration atteched to each tile
     pub(crate) tiles: HashMap<usize, TileConfig>,
+    /// Autotiling rules, used by `Tilemap::paint_terrain`
+    pub(crate) terrain_sets: Vec<TerrainSet>,
+    /// Wave Function Collapse rules, see `AdjacencyRules::from_tileset`
+    pub(crate) adjacency_rules: Option<Box<AdjacencyRules>>,
 }
 
 impl Tileset {
     pub fn new(name: String, texture: String, width: usize,height: usize, tile_width: usize, tile_height: usize) -> Self {
-        Self { name, width, height, tile_width,tile_height, texture, pathing: HashMap::default(), tiles: HashMap::default() }
+        Self { name, width, height, tile_width,tile_height, texture, pathing: HashMap::default(), tiles: HashMap::default(), terrain_sets: Vec::new(), adjacency_rules: None }
     }
 
     pub fn with_pathing(mut self, pathing: HashMap<String, HashSet<usize>>) -> Self {
@@ -37,6 +43,26 @@ impl Tileset {
         self
     }
 
+    pub fn with_terrain_set(mut self, terrain_set: TerrainSet) -> Self {
+        self.terrain_sets.push(terrain_set);
+        self
+    }
+
+    pub fn with_adjacency_rules(mut self, adjacency_rules: AdjacencyRules) -> Self {
+        self.adjacency_rules = Some(Box::new(adjacency_rules));
+        self
+    }
+
+    /// Adjacency rules declared for this tileset
+    pub fn adjacency_rules(&self) -> Option<&AdjacencyRules> {
+        self.adjacency_rules.as_deref()
+    }
+
+    /// Terrain set of this tileset defining the terrain named `terrain`
+    pub fn terrain_set_for(&self, terrain: &str) -> Option<&TerrainSet> {
+        self.terrain_sets.iter().find(|set| set.terrain(terrain).is_some())
+    }
+
     pub fn from_atlas(path_to_atlas: &str, path_to_texture: &str) -> Result<Self, ()> {
         let path = Path::new(path_to_atlas);
         if path.exists() {
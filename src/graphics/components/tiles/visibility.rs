@@ -0,0 +1,374 @@
+use std::collections::{HashMap, HashSet};
+
+use hecs::Entity;
+
+use crate::core::resources::asset_manager::AssetManager;
+use crate::core::world::{ScionWorld, World};
+use crate::graphics::components::color::Color;
+use crate::graphics::components::tiles::tilemap::Tilemap;
+use crate::graphics::rendering::Highlight;
+use crate::utils::maths::Position;
+
+/// `Opacity` tells which tiles of a tilemap block the sight.
+///
+/// A tile is opaque when its pathing value is one of the opaque pathings, or when its tile event has one of the
+/// opaque properties set to `"true"`.
+#[derive(Default, Debug, Clone)]
+pub struct Opacity {
+    opaque_pathings: HashSet<String>,
+    opaque_properties: HashSet<String>,
+}
+
+impl Opacity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_opaque_pathing(mut self, pathing: &str) -> Self {
+        self.opaque_pathings.insert(pathing.to_string());
+        self
+    }
+
+    pub fn with_opaque_property(mut self, property: &str) -> Self {
+        self.opaque_properties.insert(property.to_string());
+        self
+    }
+
+    fn is_opaque(&self, world: &mut ScionWorld, assets: &AssetManager, tilemap: Entity, position: &Position) -> bool {
+        let opaque_event = !self.opaque_properties.is_empty()
+            && world.entry::<&Tilemap>(tilemap).ok().is_some_and(|mut entry| {
+                entry.get().and_then(|t| t.event(position)).is_some_and(|event| {
+                    self.opaque_properties.iter().any(|p| event.property(p).is_some_and(|v| v == "true"))
+                })
+            });
+        opaque_event
+            || (!self.opaque_pathings.is_empty()
+                && Tilemap::retrieve_pathing(world, tilemap, position, assets).is_some_and(|p| self.opaque_pathings.contains(&p)))
+    }
+}
+
+/// Slope of a shadowcasting row, kept as a fraction to stay exact
+#[derive(Debug, Copy, Clone)]
+struct Slope {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Slope {
+    fn new(numerator: i64, denominator: i64) -> Self {
+        Self { numerator, denominator }
+    }
+
+    /// `depth * self`, rounded to the closest integer, ties rounded up
+    fn round_ties_up(&self, depth: i64) -> i64 {
+        (2 * depth * self.numerator + self.denominator).div_euclid(2 * self.denominator)
+    }
+
+    /// `depth * self`, rounded to the closest integer, ties rounded down
+    fn round_ties_down(&self, depth: i64) -> i64 {
+        -(self.denominator - 2 * depth * self.numerator).div_euclid(2 * self.denominator)
+    }
+
+    /// `value <= depth * self`
+    fn at_most(&self, depth: i64, value: i64) -> bool {
+        value * self.denominator <= depth * self.numerator
+    }
+
+    /// `value >= depth * self`
+    fn at_least(&self, depth: i64, value: i64) -> bool {
+        value * self.denominator >= depth * self.numerator
+    }
+}
+
+/// Transforms the (depth, column) of a quadrant, given the origin, into grid coordinates
+type QuadrantTransform = fn(i64, i64, i64, i64) -> (i64, i64);
+
+/// Computes the cells visible from `origin` on a grid of `width` x `height` cells, using symmetric shadowcasting :
+/// a cell is visible from another one if and only if the other is visible from it.
+/// Opaque cells are visible, but block the sight. When a `radius` is given, only the cells within this distance are visible.
+pub fn symmetric_shadowcasting(
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+    radius: Option<usize>,
+    mut is_opaque: impl FnMut(usize, usize) -> bool,
+) -> HashSet<(usize, usize)> {
+    let mut visible = HashSet::new();
+    if origin.0 >= width || origin.1 >= height {
+        return visible;
+    }
+    visible.insert(origin);
+    let (ox, oy) = (origin.0 as i64, origin.1 as i64);
+    let in_range = |depth: i64, col: i64| radius.is_none_or(|r| depth * depth + col * col <= (r * r) as i64);
+    let max_depth = radius.map_or(width.max(height) as i64, |r| r as i64);
+
+    // North, east, south and west quadrants
+    let quadrants: [QuadrantTransform; 4] = [
+        |ox, oy, depth, col| (ox + col, oy - depth),
+        |ox, oy, depth, col| (ox + depth, oy + col),
+        |ox, oy, depth, col| (ox + col, oy + depth),
+        |ox, oy, depth, col| (ox - depth, oy + col),
+    ];
+    for transform in quadrants {
+        // Cells outside of the grid block the sight
+        let mut opaque = |depth: i64, col: i64| {
+            let (x, y) = transform(ox, oy, depth, col);
+            x < 0 || y < 0 || x >= width as i64 || y >= height as i64 || is_opaque(x as usize, y as usize)
+        };
+        let mut rows = vec![(1, Slope::new(-1, 1), Slope::new(1, 1))];
+        while let Some((depth, mut start, end)) = rows.pop() {
+            if depth > max_depth {
+                continue;
+            }
+            let mut previous: Option<bool> = None;
+            for col in start.round_ties_up(depth)..=end.round_ties_down(depth) {
+                let wall = opaque(depth, col);
+                let symmetric = start.at_least(depth, col) && end.at_most(depth, col);
+                if (wall || symmetric) && in_range(depth, col) {
+                    let (x, y) = transform(ox, oy, depth, col);
+                    if x >= 0 && y >= 0 && x < width as i64 && y < height as i64 {
+                        visible.insert((x as usize, y as usize));
+                    }
+                }
+                if previous == Some(true) && !wall {
+                    start = Slope::new(2 * col - 1, 2 * depth);
+                }
+                if previous == Some(false) && wall {
+                    rows.push((depth + 1, start, Slope::new(2 * col - 1, 2 * depth)));
+                }
+                previous = Some(wall);
+            }
+            if previous == Some(false) {
+                rows.push((depth + 1, start, end));
+            }
+        }
+    }
+    visible
+}
+
+/// Computes the positions of the `layer` of `tilemap` visible from `origin`. See [`symmetric_shadowcasting`]
+pub fn tilemap_field_of_view(
+    world: &mut ScionWorld,
+    assets: &AssetManager,
+    tilemap: Entity,
+    layer: usize,
+    origin: (usize, usize),
+    radius: Option<usize>,
+    opacity: &Opacity,
+) -> HashSet<Position> {
+    let Some((width, height)) = world.entry::<&Tilemap>(tilemap).ok().and_then(|mut e| e.get().map(|t| (t.width(), t.height())))
+    else {
+        return HashSet::new();
+    };
+    let mut opaque_cells: HashMap<(usize, usize), bool> = HashMap::new();
+    symmetric_shadowcasting(origin, width, height, radius, |x, y| {
+        *opaque_cells
+            .entry((x, y))
+            .or_insert_with(|| opacity.is_opaque(world, assets, tilemap, &Position::new(x, y, layer)))
+    })
+    .into_iter()
+    .map(|(x, y)| Position::new(x, y, layer))
+    .collect()
+}
+
+/// Whether `to` can be seen from `from` on the `layer` of `tilemap`. Line of sight is symmetric, like the field of view.
+pub fn has_line_of_sight(
+    world: &mut ScionWorld,
+    assets: &AssetManager,
+    tilemap: Entity,
+    layer: usize,
+    from: (usize, usize),
+    to: (usize, usize),
+    opacity: &Opacity,
+) -> bool {
+    let (dx, dy) = (from.0.abs_diff(to.0), from.1.abs_diff(to.1));
+    let radius = ((dx * dx + dy * dy) as f32).sqrt().ceil() as usize;
+    tilemap_field_of_view(world, assets, tilemap, layer, from, Some(radius), opacity).contains(&Position::new(to.0, to.1, layer))
+}
+
+/// `FieldOfView` is the component of the entities seeing a tilemap. The visible positions are computed again by `Scion`
+/// each time the origin or the tilemap change.
+pub struct FieldOfView {
+    pub(crate) tilemap: Entity,
+    pub(crate) layer: usize,
+    pub(crate) radius: Option<usize>,
+    pub(crate) opacity: Opacity,
+    pub(crate) origin: (usize, usize),
+    pub(crate) visible: HashSet<Position>,
+    /// Origin and tilemap revision used for the last computation
+    pub(crate) computed: Option<((usize, usize), u64)>,
+    /// Incremented each time the visible positions are computed again
+    pub(crate) revision: u64,
+}
+
+impl FieldOfView {
+    pub fn new(tilemap: Entity, origin: (usize, usize), opacity: Opacity) -> Self {
+        Self { tilemap, layer: 0, radius: None, opacity, origin, visible: HashSet::new(), computed: None, revision: 0 }
+    }
+
+    pub fn with_layer(mut self, layer: usize) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: usize) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    pub fn tilemap(&self) -> Entity {
+        self.tilemap
+    }
+
+    pub fn origin(&self) -> (usize, usize) {
+        self.origin
+    }
+
+    /// Moves the viewer to another cell
+    pub fn set_origin(&mut self, x: usize, y: usize) {
+        self.origin = (x, y);
+    }
+
+    /// Positions visible at the last computation
+    pub fn visible(&self) -> &HashSet<Position> {
+        &self.visible
+    }
+
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        self.visible.contains(&Position::new(x, y, self.layer))
+    }
+}
+
+/// Visibility state of a cell under a `FogOfWar`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CellVisibility {
+    /// Never seen
+    Unexplored,
+    /// Seen before, but not visible anymore
+    Remembered,
+    Visible,
+}
+
+/// `FogOfWar` is the component of a tilemap whose tiles are darkened depending on what the `FieldOfView` entities
+/// looking at it see. Unexplored tiles and remembered tiles are tinted with their color, visible tiles get back
+/// the highlight they had before being fogged.
+///
+/// Tiles are only updated when a `FieldOfView` of the tilemap has been computed again, and only the cells whose
+/// visibility changed.
+pub struct FogOfWar {
+    pub(crate) explored: HashSet<(usize, usize)>,
+    pub(crate) visible: HashSet<(usize, usize)>,
+    pub(crate) unexplored_color: Color,
+    pub(crate) remembered_color: Color,
+    /// Visibility applied to the tiles, to only update the changing ones
+    pub(crate) applied: HashMap<(usize, usize), CellVisibility>,
+    /// Highlight of the fogged tiles before the fog tinted them
+    pub(crate) hidden_highlights: HashMap<Entity, Option<Highlight>>,
+    /// Revision of each `FieldOfView` of the tilemap at the last update
+    pub(crate) viewers: HashMap<Entity, u64>,
+    /// Whether every cell must be checked at the next update, as the explored cells or the colors changed
+    pub(crate) dirty: bool,
+}
+
+impl Default for FogOfWar {
+    fn default() -> Self {
+        Self {
+            explored: HashSet::new(),
+            visible: HashSet::new(),
+            unexplored_color: Color::new(0, 0, 0, 1.),
+            remembered_color: Color::new(80, 80, 110, 1.),
+            applied: HashMap::new(),
+            hidden_highlights: HashMap::new(),
+            viewers: HashMap::new(),
+            dirty: true,
+        }
+    }
+}
+
+impl FogOfWar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Color multiplied with the unexplored tiles, black by default to hide them
+    pub fn with_unexplored_color(mut self, color: Color) -> Self {
+        self.unexplored_color = color;
+        self
+    }
+
+    /// Color multiplied with the explored tiles that are not visible anymore
+    pub fn with_remembered_color(mut self, color: Color) -> Self {
+        self.remembered_color = color;
+        self
+    }
+
+    pub fn visibility(&self, x: usize, y: usize) -> CellVisibility {
+        if self.visible.contains(&(x, y)) {
+            CellVisibility::Visible
+        } else if self.explored.contains(&(x, y)) {
+            CellVisibility::Remembered
+        } else {
+            CellVisibility::Unexplored
+        }
+    }
+
+    /// Cells seen at least once
+    pub fn explored(&self) -> &HashSet<(usize, usize)> {
+        &self.explored
+    }
+
+    /// Marks cells as explored, for example when loading a saved game
+    pub fn explore(&mut self, cells: impl IntoIterator<Item = (usize, usize)>) {
+        self.explored.extend(cells);
+        self.dirty = true;
+    }
+
+    /// Forgets every explored cell
+    pub fn reset(&mut self) {
+        self.explored.clear();
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(map: &[&str], origin: (usize, usize), radius: Option<usize>) -> HashSet<(usize, usize)> {
+        let cells: Vec<Vec<char>> = map.iter().map(|line| line.chars().collect()).collect();
+        symmetric_shadowcasting(origin, cells[0].len(), cells.len(), radius, |x, y| cells[y][x] == '#')
+    }
+
+    #[test]
+    fn shadowcasting_walls_block_sight() {
+        let map = ["..........", "..........", "....#.....", "..........", ".........."];
+        let visible = field(&map, (4, 4), None);
+        assert!(visible.contains(&(4, 4)));
+        assert!(visible.contains(&(4, 2)));
+        assert!(!visible.contains(&(4, 1)));
+        assert!(!visible.contains(&(4, 0)));
+        assert!(visible.contains(&(0, 0)));
+        assert!(visible.contains(&(9, 4)));
+
+        let visible = field(&map, (4, 4), Some(2));
+        assert!(visible.contains(&(6, 4)));
+        assert!(!visible.contains(&(7, 4)));
+        assert!(!visible.contains(&(6, 2)));
+    }
+
+    #[test]
+    fn shadowcasting_is_symmetric() {
+        let map = ["..#.......", ".....#....", "...#...#..", "#.........", ".....##...", "........#."];
+        let (width, height) = (10, 6);
+        let fields: HashMap<(usize, usize), HashSet<(usize, usize)>> = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .filter(|(x, y)| map[*y].as_bytes()[*x] == b'.')
+            .map(|cell| (cell, field(&map, cell, None)))
+            .collect();
+        for (from, visible) in fields.iter() {
+            for to in visible.iter().filter(|to| fields.contains_key(to)) {
+                assert!(fields[to].contains(from), "{:?} sees {:?} but not the opposite", from, to);
+            }
+        }
+    }
+}
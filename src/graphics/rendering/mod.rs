@@ -26,6 +26,15 @@ pub(crate) trait Renderable2D {
     fn get_rendering_priority(&self) -> usize { 0 }
 }
 
+/// Offset of the pivot of a component, applied to its transform uniform
+pub(crate) trait PivotOffset {
+    fn get_pivot_offset(&self, _material: Option<&Material>) -> Vector { Vector::default() }
+}
+
+impl<T: Renderable2D> PivotOffset for T {
+    fn get_pivot_offset(&self, material: Option<&Material>) -> Vector { Renderable2D::get_pivot_offset(self, material) }
+}
+
 pub(crate) trait RenderableUi: Renderable2D {}
 
 
@@ -84,4 +93,6 @@ pub struct RenderingInfos {
 #[derive(Debug, Clone)]
 pub enum Highlight{
     ColorNonTransparent(Color),
+    /// Multiplies the colors of the sprite by this color, mixed using its alpha
+    Tint(Color),
 }
@@ -1,4 +1,6 @@
-use hecs::Component;
+use std::collections::HashMap;
+
+use hecs::{Component, Entity};
 
 use crate::core::components::maths::transform::Transform;
 use crate::core::world::{GameData, World};
@@ -42,31 +44,42 @@ pub(crate) fn pre_render_tilemaps(data: &mut GameData) -> Vec<RenderingInfos> {
     let type_name = std::any::type_name::<Tilemap>();
     let mut render_infos = Vec::new();
 
-    let tiles = data.query::<(&Tile, &Sprite)>().iter().map(|(e, _)| e).collect::<Vec<_>>();
+    // Number of tiles of each tileset, in each mesh
+    let mut tiles: HashMap<(Entity, usize), usize> = HashMap::new();
+    for (_, (tile, _)) in data.query::<(&Tile, &Sprite)>().iter() {
+        *tiles.entry((tile.mesh(), tile.tileset)).or_default() += 1;
+    }
+    let assets = data.assets();
 
-    for (entity, (_, material, transform)) in data
+    for (entity, (tilemap, _, transform)) in data
         .query::<(&mut Tilemap, &Material, &Transform)>()
         .without::<&Hide>()
         .without::<&HidePropagated>()
         .iter()
     {
-        let tiles_nb = tiles
-            .iter()
-            .filter(|t| data.entry::<&Tile>(**t).expect("").get().expect("").tilemap == entity)
-            .count();
-
-        let path = match material {
-            Material::Tileset(tileset) => Some(tileset.texture.clone()),
-            _ => None,
-        };
-        render_infos.push(RenderingInfos {
-            layer: transform.translation().z(),
-            range: 0..(tiles_nb * Sprite::indices().len()) as u32,
-            entity,
-            texture_path: path,
-            type_name: type_name.to_string(),
-            render_priority: 0
-        });
+        for mesh in tilemap.mesh_entities(entity) {
+            // Tiles are stored grouped by tileset in the mesh's buffers, each group is rendered with its tileset's texture
+            let mut first_index = 0;
+            for tileset in 0..tilemap.tilesets_count() {
+                let tiles_nb = tiles.get(&(mesh, tileset)).copied().unwrap_or(0);
+                if tiles_nb == 0 {
+                    continue;
+                }
+                let last_index = first_index + (tiles_nb * Sprite::indices().len()) as u32;
+                let path = tilemap.tileset_ref_at(tileset)
+                    .and_then(|tileset_ref| assets.retrieve_tileset(tileset_ref))
+                    .map(|tileset| tileset.texture.clone());
+                render_infos.push(RenderingInfos {
+                    layer: transform.translation().z(),
+                    range: first_index..last_index,
+                    entity: mesh,
+                    texture_path: path,
+                    type_name: type_name.to_string(),
+                    render_priority: 0
+                });
+                first_index = last_index;
+            }
+        }
     }
     render_infos
 }
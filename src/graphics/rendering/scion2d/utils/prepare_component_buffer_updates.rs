@@ -6,6 +6,7 @@ use crate::graphics::components::material::Material;
 use crate::graphics::components::shapes::line::Line;
 use crate::graphics::components::shapes::polygon::Polygon;
 use crate::graphics::components::shapes::rectangle::Rectangle;
+use crate::graphics::components::tiles::layer::LayerSettings;
 use crate::graphics::components::tiles::sprite::Sprite;
 use crate::graphics::components::tiles::tilemap::{Tile, Tilemap};
 use crate::graphics::components::ui::ui_image::UiImage;
@@ -15,6 +16,7 @@ use crate::graphics::rendering::scion2d::pre_renderer::Scion2DPreRenderer;
 use crate::graphics::rendering::shaders::gl_representations::TexturedGlVertexWithLayer;
 use crate::graphics::rendering::{Highlight, Renderable2D, RenderableUi, RenderingUpdate};
 use hecs::{Component, Entity};
+use std::collections::{HashMap, HashSet};
 use wgpu::BufferUsages;
 
 pub(crate) fn call(renderer: &mut Scion2DPreRenderer, data: &mut GameData) -> Vec<RenderingUpdate> {
@@ -93,44 +95,94 @@ fn prepare_buffer_update_for_tilemap(renderer: &mut Scion2DPreRenderer, data: &m
     let mut updates = vec![];
     {
         let mut to_modify: Vec<(Entity, [TexturedGlVertexWithLayer; 4])> = Vec::new();
+        // Tiles of each mesh, with the meshes having at least one modified tile
+        let mut mesh_tiles: HashMap<Entity, Vec<(Entity, usize)>> = HashMap::new();
+        let mut dirty_meshes: HashSet<Entity> = HashSet::new();
+        for (e, (tile, sprite, t)) in data.query::<(&Tile, &Sprite, &Transform)>().iter() {
+            mesh_tiles.entry(tile.mesh()).or_default().push((e, tile.tileset));
+            if sprite.dirty() || t.dirty_offset {
+                dirty_meshes.insert(tile.mesh());
+            }
+        }
+        let default_layer = LayerSettings::default();
         for (entity, (t, material, _)) in data.query::<(&mut Tilemap, &Material, &Transform)>().iter() {
+            let refresh_layers = t.layers_dirty;
+            t.layers_dirty = false;
             let tile_size = Material::tile_size(material).expect("");
-            let mut position = 0;
-            let mut vertexes = Vec::new();
-            let mut indexes = Vec::new();
             let isometric = t.is_isometric();
+            let staggered_grid = t.tilemap_type().staggered_grid();
+            let tile_height = if let Material::Tileset(tileset) = material { tileset.tile_height } else { tile_size };
             let max_x = t.width();
             let depth = t.depth();
 
-            let any_tile_modified = renderer.missing_vertex_buffer(&entity) || any_dirty_sprite(data, entity);
-            if any_tile_modified {
-                for (e, (tile, sprite, offset_transform)) in data.query::<(&Tile, &Sprite, &Transform)>().iter() {
-                    if tile.tilemap == entity {
+            for mesh in t.mesh_entities(entity) {
+                let any_tile_modified = renderer.missing_vertex_buffer(&mesh) || dirty_meshes.contains(&mesh) || refresh_layers;
+                if !any_tile_modified {
+                    continue;
+                }
+                let mut position = 0;
+                let mut vertexes = Vec::new();
+                let mut indexes = Vec::new();
+                // Tiles are grouped by tileset, so that each tileset is rendered with its own texture
+                let tileset_materials: Vec<Material> = (0..t.tilesets_count())
+                    .map(|tileset| data.assets().get_material_for_ref(t.tileset_ref_at(tileset).unwrap()))
+                    .collect();
+                let tiles = mesh_tiles.get(&mesh).map_or(&[][..], |tiles| tiles.as_slice());
+                for (tileset, tileset_material) in tileset_materials.iter().enumerate() {
+                    for (e, _) in tiles.iter().filter(|(_, tile_tileset)| *tile_tileset == tileset) {
+                        let e = *e;
+                        let mut entry = data.entry::<(&Tile, &Sprite, &Transform)>(e).expect("");
+                        let (tile, sprite, offset_transform) = entry.get().expect("");
                         let color_picking = renderer.color_picking_storage.create_picking(e);
-                        let current_vertex = sprite.compute_content(Some(material));
+                        let current_vertex = sprite.compute_content(Some(tileset_material));
                         to_modify.push((e, current_vertex));
                         let mut vec = current_vertex.to_vec();
+                        let layer = t.layer_settings(tile.position.z()).unwrap_or(&default_layer);
+                        let (parallax_x, parallax_y) = layer.parallax();
                         let mut offset_x = 0.;
                         let mut offset_y = 0.;
                         let offset_z: usize;
+                        // Depth between two layers, used to move the layers by their render offset
+                        let layer_step: usize;
 
                         if isometric {
                             offset_x = offset_transform.local_translation.x + -1. * tile.position.x() as f32 * t.offset_x_multiplier_x() + tile.position.y() as f32 * t.offset_x_multiplier_y() - (tile.position.z() as f32 * t.offset_x_multiplier_z());
                             offset_y = offset_transform.local_translation.y + -1. * (tile.position.y() as f32 * t.offset_y_multiplier_y()  + tile.position.x() as f32 * t.offset_y_multiplier_x()) - (tile.position.z() as f32 * t.offset_y_multiplier_z());
-                            offset_z = offset_transform.local_translation.z + (max_x - tile.position.z()) * (max_x + 1) + tile.position.x() * (max_x + 1) + (max_x - tile.position.y())
+                            offset_z = offset_transform.local_translation.z + (max_x - tile.position.z()) * (max_x + 1) + tile.position.x() * (max_x + 1) + (max_x - tile.position.y());
+                            layer_step = max_x + 1;
+                        } else if let Some(grid) = staggered_grid {
+                            // Tiles are placed by the grid, the lines lower on the screen being drawn in front
+                            let (x, y) = grid.tile_position(tile.position.x(), tile.position.y(), tile_size as f32, tile_height as f32);
+                            offset_x = offset_transform.local_translation.x + x - tile_size as f32 * tile.position.x() as f32;
+                            offset_y = offset_transform.local_translation.y + y - tile_size as f32 * tile.position.y() as f32;
+                            let lines = 2 * t.height() + 2;
+                            offset_z = (depth - tile.position.z()) * lines + (lines - 1 - grid.draw_rank(tile.position.x(), tile.position.y()));
+                            layer_step = lines;
                         } else {
                             offset_z = depth * 100 - tile.position.z() * 10;
+                            layer_step = 10;
                         }
+                        let layer_depth = offset_z as f32 - (layer.render_offset() * layer_step as isize) as f32;
+                        let layer_color = layer.color();
                         vec.iter_mut().for_each(|gl_vertex| {
                             gl_vertex.position[0] = gl_vertex.position[0] + tile_size as f32 * tile.position.x() as f32 + offset_x;
                             gl_vertex.position[1] = gl_vertex.position[1] + tile_size as f32 * tile.position.y() as f32 + offset_y;
                             gl_vertex.position[2] = gl_vertex.position[2] + tile.position.z() as f32 / 100.;
-                            gl_vertex.depth = gl_vertex.depth + offset_z as f32 * 0.00001;
+                            gl_vertex.depth += layer_depth * 0.00001;
+                            gl_vertex.layer_color = layer_color;
+                            gl_vertex.parallax = [parallax_x, parallax_y];
                             gl_vertex.enable_color_picking_override = 1;
                             gl_vertex.color_picking_override = color_picking.as_f32_array();
-                            if let Some(Highlight::ColorNonTransparent(c)) = sprite.highlight(){
-                                gl_vertex.enable_highlight = 1;
-                                gl_vertex.highlight_color = c.as_f32_array();
+                            match sprite.highlight() {
+                                Some(Highlight::ColorNonTransparent(c)) => {
+                                    gl_vertex.enable_highlight = 1;
+                                    gl_vertex.highlight_color = c.as_f32_array();
+                                }
+                                Some(Highlight::Tint(c)) => {
+                                    gl_vertex.enable_highlight = 2;
+                                    gl_vertex.highlight_color = c.as_f32_array();
+                                }
+                                None => {}
                             }
                         });
                         let sprite_indexes = Sprite::indices();
@@ -146,19 +198,19 @@ fn prepare_buffer_update_for_tilemap(renderer: &mut Scion2DPreRenderer, data: &m
 
                 let bytes_vertexes: &[u8] = bytemuck::cast_slice(vertexes.as_slice());
                 updates.push(RenderingUpdate::VertexBuffer {
-                    entity,
+                    entity: mesh,
                     contents: bytes_vertexes.to_vec(),
                     usage: BufferUsages::VERTEX,
                 });
-                renderer.upsert_vertex_buffer(entity);
+                renderer.upsert_vertex_buffer(mesh);
 
                 let bytes_indexes: &[u8] = bytemuck::cast_slice(indexes.as_slice());
                 updates.push(RenderingUpdate::IndexBuffer {
-                    entity,
+                    entity: mesh,
                     contents: bytes_indexes.to_vec(),
                     usage: BufferUsages::INDEX,
                 });
-                renderer.upsert_indexes_buffer(entity);
+                renderer.upsert_indexes_buffer(mesh);
             }
         }
 
@@ -171,15 +223,6 @@ fn prepare_buffer_update_for_tilemap(renderer: &mut Scion2DPreRenderer, data: &m
     updates
 }
 
-fn any_dirty_sprite(data: &GameData, entity: Entity) -> bool {
-    data
-        .query::<(&Tile, &Sprite, &Transform)>()
-        .iter()
-        .filter(|(_, (tile, sprite, t))| tile.tilemap == entity && (sprite.dirty() || t.dirty_offset))
-        .count()
-        > 0
-}
-
 fn prepare_buffer_update_for_ui_text(renderer: &mut Scion2DPreRenderer, data: &mut GameData) -> Vec<RenderingUpdate> {
     let mut updates = vec![];
     let (world, resources) = data.split();
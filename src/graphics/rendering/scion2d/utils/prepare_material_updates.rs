@@ -5,6 +5,7 @@ use std::time::SystemTime;
 use crate::core::world::{GameData, World};
 use crate::graphics::components::color::Color;
 use crate::graphics::components::material::{Material, Texture, TextureArray};
+use crate::graphics::components::tiles::tilemap::Tilemap;
 use crate::graphics::components::tiles::tileset::Tileset;
 use crate::graphics::rendering::scion2d::pre_renderer::Scion2DPreRenderer;
 use crate::graphics::rendering::{DiffuseBindGroupUpdate, RenderingUpdate};
@@ -37,6 +38,18 @@ pub(crate) fn call(renderer: &mut Scion2DPreRenderer, data: &mut GameData) -> Ve
             }
         }
     }
+
+    // Only the main tileset of a tilemap is a component, the other ones are loaded from the asset manager
+    let assets = data.assets();
+    for (_entity, tilemap) in data.query::<&Tilemap>().iter() {
+        for tileset in 1..tilemap.tilesets_count() {
+            if let Some(tileset) = tilemap.tileset_ref_at(tileset).and_then(|r| assets.retrieve_tileset(r)) {
+                if let Some(update) = try_tileset_update(renderer, hot_timer_cycle, tileset) {
+                    updates.push(update);
+                }
+            }
+        }
+    }
     updates
 }
 
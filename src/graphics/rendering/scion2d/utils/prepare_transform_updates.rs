@@ -6,6 +6,7 @@ use crate::graphics::components::material::Material;
 use crate::graphics::components::shapes::line::Line;
 use crate::graphics::components::shapes::polygon::Polygon;
 use crate::graphics::components::shapes::rectangle::Rectangle;
+use crate::graphics::components::tiles::chunk::TilemapChunk;
 use crate::graphics::components::tiles::sprite::Sprite;
 use crate::graphics::components::tiles::tilemap::{Tile, Tilemap};
 use crate::graphics::components::ui::ui_image::UiImage;
@@ -14,7 +15,7 @@ use crate::graphics::components::ui::UiComponent;
 use crate::graphics::components::{Square, Triangle};
 use crate::graphics::rendering::scion2d::pre_renderer::Scion2DPreRenderer;
 use crate::graphics::rendering::shaders::gl_representations::{GlUniform, UniformData};
-use crate::graphics::rendering::{Renderable2D, RenderingUpdate};
+use crate::graphics::rendering::{PivotOffset, RenderingUpdate};
 use hecs::Component;
 
 pub(crate) fn call(renderer: &mut Scion2DPreRenderer, data: &mut GameData) -> (Vec<RenderingUpdate>, (Camera, Transform)) {
@@ -37,6 +38,7 @@ pub(crate) fn call(renderer: &mut Scion2DPreRenderer, data: &mut GameData) -> (V
         updates.append(&mut update_transforms_for_type_no_dirty_check::<UiImage>(renderer, data, &camera));
         updates.append(&mut update_transforms_for_type_no_dirty_check::<UiText>(renderer, data, &camera));
         updates.append(&mut update_transforms_for_type_no_dirty_check::<Tilemap>(renderer, data, &camera));
+        updates.append(&mut update_transforms_for_type_no_dirty_check::<TilemapChunk>(renderer, data, &camera));
     } else{
         updates.append(&mut update_transforms_for_type::<Triangle>(renderer, data, &camera));
         updates.append(&mut update_transforms_for_type::<Square>(renderer, data, &camera));
@@ -47,12 +49,13 @@ pub(crate) fn call(renderer: &mut Scion2DPreRenderer, data: &mut GameData) -> (V
         updates.append(&mut update_transforms_for_type::<UiImage>(renderer, data, &camera));
         updates.append(&mut update_transforms_for_type::<UiText>(renderer, data, &camera));
         updates.append(&mut update_transforms_for_type::<Tilemap>(renderer, data, &camera));
+        updates.append(&mut update_transforms_for_type::<TilemapChunk>(renderer, data, &camera));
     }
 
     (updates, camera)
 }
 
-fn update_transforms_for_type<T: Component + Renderable2D>(
+fn update_transforms_for_type<T: Component + PivotOffset>(
     _renderer: &mut Scion2DPreRenderer,
     data: &mut GameData,
     camera: &(Camera, Transform),
@@ -110,7 +113,7 @@ fn update_transforms_for_sprites(
 }
 
 
-fn update_transforms_for_type_no_dirty_check<T: Component + Renderable2D>(
+fn update_transforms_for_type_no_dirty_check<T: Component + PivotOffset>(
     _renderer: &mut Scion2DPreRenderer,
     data: &mut GameData,
     camera: &(Camera, Transform),
@@ -65,7 +65,10 @@ pub(crate) struct TexturedGlVertexWithLayer {
     pub enable_color_picking_override: u32,
     pub enable_highlight: u32,
     pub highlight_color: [f32;4],
-
+    /// Color multiplied with the sampled texture, used by the tilemap layers' opacity and tint
+    pub layer_color: [f32;4],
+    /// Parallax factors of the tilemap layer, the shader moving the vertex with the camera when they are not 1
+    pub parallax: [f32;2],
 }
 
 impl TexturedGlVertexWithLayer {
@@ -115,6 +118,16 @@ impl TexturedGlVertexWithLayer {
                     shader_location: 7,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 2]>() + size_of::<[f32; 3]>() + size_of::<u32>() + size_of::<f32>() + size_of::<[f32; 4]>() + size_of::<u32>() + size_of::<u32>() + size_of::<[f32; 4]>()) as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 2]>() + size_of::<[f32; 3]>() + size_of::<u32>() + size_of::<f32>() + size_of::<[f32; 4]>() + size_of::<u32>() + size_of::<u32>() + size_of::<[f32; 4]>() + size_of::<[f32; 4]>()) as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
@@ -192,6 +205,8 @@ impl From<(&Coordinates, &Coordinates, usize, f32)> for TexturedGlVertexWithLaye
             enable_color_picking_override: 0,
             enable_highlight: 0,
             highlight_color: [0.,0.,0.,0.],
+            layer_color: [1.,1.,1.,1.],
+            parallax: [1.,1.],
         }
     }
 }
@@ -207,6 +222,8 @@ impl From<(&Coordinates, &Coordinates, usize, f32, Color, bool)> for TexturedGlV
             enable_color_picking_override: if vertex_infos.5 { 1 } else { 0 },
             enable_highlight: 0,
             highlight_color: [0.,0.,0.,0.],
+            layer_color: [1.,1.,1.,1.],
+            parallax: [1.,1.],
         }
     }
 }
@@ -222,12 +239,15 @@ pub(crate) struct PickingData {
 pub(crate) struct GlUniform {
     pub model_trans: [[f32; 4]; 4],
     pub camera_view: [[f32; 4]; 4],
+    /// Position of the camera, used by the parallax layers of the tilemaps
+    pub camera_position: [f32; 4],
 }
 
 impl GlUniform {
     pub(crate) fn replace_with(&mut self, other: GlUniform) {
         self.model_trans = other.model_trans;
         self.camera_view = other.camera_view;
+        self.camera_position = other.camera_position;
     }
 }
 
@@ -260,7 +280,9 @@ impl From<UniformData<'_>> for GlUniform {
             y: uniform_data.transform.global_translation.y() + uniform_data.pivot_offset.y * uniform_data.transform.scale,
             z: uniform_data.transform.global_translation.z() as f32,
         });
+        let mut camera_position = [0.; 4];
         if !uniform_data.is_ui_component && !uniform_data.transform.use_screen_as_origin {
+            camera_position = [uniform_data.camera.1.global_translation().x(), uniform_data.camera.1.global_translation().y(), 0., 0.];
             model_trans.append_translation(Vec3 {
                 x: -1. * uniform_data.camera.1.global_translation().x(),
                 y: -1. * uniform_data.camera.1.global_translation().y(),
@@ -285,6 +307,7 @@ impl From<UniformData<'_>> for GlUniform {
         GlUniform {
             model_trans: create_glmat4(&mut model_trans),
             camera_view: create_glmat4(&mut camera_view),
+            camera_position,
         }
     }
 }
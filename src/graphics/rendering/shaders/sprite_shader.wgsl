@@ -5,12 +5,14 @@ struct VertexOutput {
     @location(2) color_picking_override: vec4<f32>,
     @location(3) enable_color_picking_override: u32,
     @location(4) enable_highlight: u32,
-    @location(5) highlight_color: vec4<f32>
+    @location(5) highlight_color: vec4<f32>,
+    @location(6) layer_color: vec4<f32>
  };
 
 struct Uniforms {
     model_trans: mat4x4<f32>,
-    camera_view: mat4x4<f32>
+    camera_view: mat4x4<f32>,
+    camera_position: vec4<f32>
 }
 
 struct PickingData {
@@ -32,10 +34,14 @@ fn vs_main(
     @location(4) color_picking_override: vec4<f32>,
     @location(5) enable_color_picking_override: u32,
     @location(6) enable_highlight: u32,
-    @location(7) highlight_color: vec4<f32>
+    @location(7) highlight_color: vec4<f32>,
+    @location(8) layer_color: vec4<f32>,
+    @location(9) parallax: vec2<f32>
 ) ->  VertexOutput {
     var result: VertexOutput;
-    let world_position = r_data.model_trans * vec4<f32>(a_position, 1.0);
+    // Parallax layers follow the camera by the part of its movement they don't take
+    let parallax_offset = r_data.camera_position.xy * (vec2<f32>(1.0, 1.0) - parallax);
+    let world_position = r_data.model_trans * vec4<f32>(a_position, 1.0) + vec4<f32>(parallax_offset, 0.0, 0.0);
     var clip_position = r_data.camera_view * world_position;
     clip_position.z += depth;
     result.position = clip_position;
@@ -45,6 +51,7 @@ fn vs_main(
     result.enable_color_picking_override = u32(enable_color_picking_override);
     result.enable_highlight = u32(enable_highlight);
     result.highlight_color = highlight_color;
+    result.layer_color = layer_color;
     return result;
 }
 
@@ -63,7 +70,7 @@ var<uniform> picking_data: PickingData;
 @fragment
 fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
    let depth = vertex.position.z / vertex.position.w;
-   let color = textureSample(t_diffuse, s_diffuse, vertex.v_tex_translation, vertex.layer);
+   let color = textureSample(t_diffuse, s_diffuse, vertex.v_tex_translation, vertex.layer) * vertex.layer_color;
 
    if (color.a < 0.0001) {
        discard;
@@ -73,6 +80,10 @@ fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
        return vertex.color_picking_override;
    }
 
+   if(vertex.enable_highlight == 2u){
+   return vec4<f32>(mix(color.rgb, color.rgb * vertex.highlight_color.rgb, vertex.highlight_color.a), color.a);
+   }
+
    if(vertex.enable_highlight > 0){
    let final_color = vec4<f32>(
        mix(color.rgb, color.rgb + vertex.highlight_color.rgb * vertex.highlight_color.a, vertex.highlight_color.a),
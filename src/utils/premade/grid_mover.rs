@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::core::package::Package;
+use crate::core::resources::events::topic::TopicConfiguration;
+use crate::core::resources::events::Events;
+use crate::core::resources::inputs::types::{Input, KeyCode};
+use crate::core::resources::time::Time;
+use crate::core::world::{GameData, World};
+use crate::graphics::components::tiles::tilemap::Tilemap;
+use crate::utils::maths::{Position, Vector};
+use crate::ScionBuilder;
+
+/// Topic on which a [`TileEntered`] event is published each time a `GridMover` enters a tile having a `TileEvent`
+pub const TILE_EVENTS_TOPIC: &str = "TileEvents";
+
+/// Direction of a move on the grid
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GridDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl GridDirection {
+    /// Offset, in cells, of a move in this direction
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            GridDirection::Up => (0, -1),
+            GridDirection::Down => (0, 1),
+            GridDirection::Left => (-1, 0),
+            GridDirection::Right => (1, 0),
+        }
+    }
+}
+
+/// Event published on the [`TILE_EVENTS_TOPIC`] topic when a `GridMover` enters a tile with a `TileEvent`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TileEntered {
+    entity: u64,
+    x: usize,
+    y: usize,
+    event_type: String,
+    properties: HashMap<String, String>,
+}
+
+impl TileEntered {
+    /// Entity of the `GridMover` entering the tile
+    pub fn entity(&self) -> Entity {
+        Entity::from_bits(self.entity).expect("Invalid entity bits in a tile event")
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    pub fn properties(&self) -> &HashMap<String, String> {
+        &self.properties
+    }
+}
+
+struct GridMove {
+    target: (usize, usize),
+    step: Vector,
+    progress: f32,
+}
+
+/// `GridMover` is a premade component moving its entity on a tilemap, cell by cell, like in classic top-down RPGs.
+///
+/// A move toward a direction is only done if the target cell is walkable, meaning that its pathing
+/// (the `Pathing` of the tile or the pathing of its tileset) is one of the walkable pathings, and that no other
+/// `GridMover` uses it. Otherwise, the mover only faces the direction.
+/// The entity translation is tweened during `move_duration` seconds, and entering a tile having a `TileEvent`
+/// publishes a [`TileEntered`] event on the [`TILE_EVENTS_TOPIC`] topic.
+///
+/// Moves are driven by the keyboard arrows, or from code using `request_move` and `set_held_direction`.
+/// A move requested during another one is buffered and starts as soon as the current one ends.
+pub struct GridMover {
+    tilemap: Entity,
+    cell_width: f32,
+    cell_height: f32,
+    position: (usize, usize),
+    /// Layer of the tilemap used to read pathings and events
+    layer: usize,
+    /// Duration of a move to the next cell, in seconds
+    move_duration: f32,
+    /// Pathing values the mover can walk on. `None` accepts any tile having a pathing
+    walkable_pathings: Option<Vec<String>>,
+    keyboard_controls: bool,
+    facing: GridDirection,
+    buffered: Option<GridDirection>,
+    held: Option<GridDirection>,
+    current_move: Option<GridMove>,
+    arrived: bool,
+}
+
+impl GridMover {
+    /// Creates a mover standing at the cell `(x, y)` of `tilemap`, whose cells are `cell_width` x `cell_height`
+    /// in world units. The entity is expected to be already placed on this cell.
+    pub fn new(tilemap: Entity, cell_width: f32, cell_height: f32, x: usize, y: usize) -> Self {
+        Self {
+            tilemap,
+            cell_width,
+            cell_height,
+            position: (x, y),
+            layer: 0,
+            move_duration: 0.25,
+            walkable_pathings: None,
+            keyboard_controls: true,
+            facing: GridDirection::Down,
+            buffered: None,
+            held: None,
+            current_move: None,
+            arrived: false,
+        }
+    }
+
+    pub fn with_move_duration(mut self, move_duration: f32) -> Self {
+        self.move_duration = move_duration;
+        self
+    }
+
+    pub fn with_walkable_pathings(mut self, walkable_pathings: Vec<String>) -> Self {
+        self.walkable_pathings = Some(walkable_pathings);
+        self
+    }
+
+    pub fn with_layer(mut self, layer: usize) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_facing(mut self, facing: GridDirection) -> Self {
+        self.facing = facing;
+        self
+    }
+
+    /// Enables or disables the keyboard controls using the arrows. Enabled by default
+    pub fn with_keyboard_controls(mut self, keyboard_controls: bool) -> Self {
+        self.keyboard_controls = keyboard_controls;
+        self
+    }
+
+    /// Requests a single move toward `direction`, buffered if the mover is currently moving
+    pub fn request_move(&mut self, direction: GridDirection) {
+        self.buffered = Some(direction);
+    }
+
+    /// Sets the direction the mover keeps walking toward, move after move. `None` stops it after the current move
+    pub fn set_held_direction(&mut self, direction: Option<GridDirection>) {
+        self.held = direction;
+    }
+
+    /// Cell on which the mover stands, or the cell it is leaving while moving
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Cell the mover is moving to, if it is moving
+    pub fn target(&self) -> Option<(usize, usize)> {
+        self.current_move.as_ref().map(|m| m.target)
+    }
+
+    pub fn facing(&self) -> GridDirection {
+        self.facing
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.current_move.is_some()
+    }
+
+    /// Whether the mover reached a new cell during the last update
+    pub fn has_arrived(&self) -> bool {
+        self.arrived
+    }
+
+    fn next_cell(&self, direction: GridDirection, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (dx, dy) = direction.offset();
+        let x = self.position.0.checked_add_signed(dx).filter(|x| *x < width)?;
+        let y = self.position.1.checked_add_signed(dy).filter(|y| *y < height)?;
+        Some((x, y))
+    }
+}
+
+/// This package adds the `GridMover` system, and the [`TILE_EVENTS_TOPIC`] topic.
+pub struct GridMovement;
+
+impl Package for GridMovement {
+    fn prepare(&self, data: &mut GameData) {
+        if let Some(mut events) = data.get_resource_mut::<Events>() {
+            let _r = events.create_topic(TILE_EVENTS_TOPIC, TopicConfiguration::default());
+        }
+    }
+
+    fn load(&self, builder: ScionBuilder) -> ScionBuilder {
+        builder.with_system(grid_mover_system)
+    }
+}
+
+/// Reads the keyboard inputs of the movers using keyboard controls, then moves every `GridMover`
+pub fn grid_mover_system(data: &mut GameData) {
+    {
+        let (world, resources) = data.split();
+        let inputs = resources.inputs();
+        let keys = [
+            (KeyCode::Up, GridDirection::Up),
+            (KeyCode::Down, GridDirection::Down),
+            (KeyCode::Left, GridDirection::Left),
+            (KeyCode::Right, GridDirection::Right),
+        ];
+        let held = keys.iter().find(|(key, _)| inputs.input_pressed(&Input::Key(*key))).map(|(_, d)| *d);
+        let pressed = keys.iter().find(|(key, _)| inputs.input_pressed_event(&Input::Key(*key))).map(|(_, d)| *d);
+        for (_, mover) in world.query_mut::<&mut GridMover>() {
+            if mover.keyboard_controls {
+                mover.held = held;
+                if let Some(direction) = pressed {
+                    mover.buffered = Some(direction);
+                }
+            }
+        }
+    }
+    let dt = data.get_resource::<Time>().map_or(0., |t| t.delta_duration().as_secs_f32());
+    advance_grid_movers(data, dt);
+}
+
+/// Starts the moves of the idle movers, then advances every move by `dt` seconds
+fn advance_grid_movers(data: &mut GameData, dt: f32) {
+    let (world, resources, commands) = data.split_with_command();
+
+    let mut occupied: Vec<(Entity, (usize, usize))> = Vec::new();
+    let mut requests: Vec<(Entity, Entity, GridDirection, Option<Position>)> = Vec::new();
+    for (entity, mover) in world.query::<&GridMover>().iter() {
+        occupied.push((mover.tilemap, mover.position));
+        occupied.extend(mover.target().map(|target| (mover.tilemap, target)));
+        if mover.is_moving() {
+            continue;
+        }
+        if let Some(direction) = mover.buffered.or(mover.held) {
+            let target = world
+                .entry::<&Tilemap>(mover.tilemap)
+                .ok()
+                .and_then(|mut entry| entry.get().map(|t| (t.width(), t.height())))
+                .and_then(|(width, height)| mover.next_cell(direction, width, height))
+                .map(|(x, y)| Position::new(x, y, mover.layer));
+            requests.push((entity, mover.tilemap, direction, target));
+        }
+    }
+
+    for (entity, tilemap, direction, target) in requests {
+        let pathing = target
+            .as_ref()
+            .and_then(|position| Tilemap::retrieve_pathing(world, tilemap, position, &resources.assets()));
+        let mover = world.entry_mut::<&mut GridMover>(entity).expect("Missing grid mover");
+        let walkable = pathing.is_some_and(|pathing| mover.walkable_pathings.as_ref().is_none_or(|w| w.contains(&pathing)));
+        mover.facing = direction;
+        mover.buffered = None;
+        let target = target.map(|position| (position.x(), position.y())).filter(|t| !occupied.contains(&(tilemap, *t)));
+        if let (true, Some(target)) = (walkable, target) {
+            let (dx, dy) = direction.offset();
+            let step = Vector::new(dx as f32 * mover.cell_width, dy as f32 * mover.cell_height);
+            mover.current_move = Some(GridMove { target, step, progress: 0. });
+            occupied.push((tilemap, target));
+        }
+    }
+
+    let mut arrivals: Vec<(Entity, Entity, usize, (usize, usize))> = Vec::new();
+    for (entity, mover) in world.query_mut::<&mut GridMover>() {
+        mover.arrived = false;
+        let duration = mover.move_duration;
+        let Some(current) = mover.current_move.as_mut() else { continue };
+        let progress = if duration <= 0. { 1. } else { (current.progress + dt / duration).min(1.) };
+        let advance = progress - current.progress;
+        current.progress = progress;
+        commands.transform_commands.append_vector(entity, Vector::new(current.step.x * advance, current.step.y * advance));
+        if progress >= 1. {
+            mover.position = current.target;
+            mover.current_move = None;
+            mover.arrived = true;
+            arrivals.push((entity, mover.tilemap, mover.layer, mover.position));
+        }
+    }
+
+    for (entity, tilemap, layer, (x, y)) in arrivals {
+        let Ok(tilemap) = world.entry_mut::<&mut Tilemap>(tilemap) else { continue };
+        if let Some(event) = tilemap.retrieve_event(&Position::new(x, y, layer)) {
+            let tile_entered = TileEntered {
+                entity: entity.to_bits().get(),
+                x,
+                y,
+                event_type: event.event_type(),
+                properties: event.properties().clone(),
+            };
+            if let Some(mut events) = resources.get_resource_mut::<Events>() {
+                let _r = events.publish(TILE_EVENTS_TOPIC, tile_entered);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use super::*;
+    use crate::core::components::maths::transform::Transform;
+    use crate::core::resources::asset_manager::{AssetManager, AssetRef};
+    use crate::core::resources::events::PollConfiguration;
+    use crate::graphics::components::tiles::tilemap::{TileEvent, TileInfos, TilemapInfo, TilemapType};
+    use crate::utils::maths::Dimensions;
+
+    fn grid(data: &mut GameData) -> Entity {
+        data.insert_resource(AssetManager::default());
+        // 4x1 corridor : floor, floor, floor with an event, wall
+        Tilemap::create(
+            TilemapInfo::new(Dimensions::new(4, 1, 1), Transform::default(), AssetRef(0, PhantomData), TilemapType::Standard),
+            data,
+            |p| match p.x() {
+                3 => TileInfos::new(None).with_pathing("wall".to_string()),
+                2 => TileInfos::new(None)
+                    .with_pathing("floor".to_string())
+                    .with_event(Some(TileEvent::new("DOOR".to_string(), HashMap::new()))),
+                _ => TileInfos::new(None).with_pathing("floor".to_string()),
+            },
+        )
+    }
+
+    fn x_of(data: &mut GameData, entity: Entity) -> f32 {
+        data.entry::<&Transform>(entity).unwrap().get().unwrap().translation().x()
+    }
+
+    #[test]
+    fn grid_mover_moves_cell_by_cell() {
+        let mut data = GameData::default();
+        let tilemap = grid(&mut data);
+        let mut events = Events::default();
+        events.create_topic(TILE_EVENTS_TOPIC, TopicConfiguration::default()).unwrap();
+        let subscriber = events.subscribe(TILE_EVENTS_TOPIC, PollConfiguration::default()).unwrap();
+        data.insert_resource(events);
+        let mover = data.push((
+            Transform::from_xy(0., 0.),
+            GridMover::new(tilemap, 16., 16., 0, 0)
+                .with_walkable_pathings(vec!["floor".to_string()])
+                .with_keyboard_controls(false),
+        ));
+
+        data.entry_mut::<&mut GridMover>(mover).unwrap().request_move(GridDirection::Right);
+        advance_grid_movers(&mut data, 0.125);
+        data.apply_commands();
+        assert!((x_of(&mut data, mover) - 8.).abs() < 0.001);
+        assert_eq!(Some((1, 0)), data.entry::<&GridMover>(mover).unwrap().get().unwrap().target());
+
+        // Buffered during the move
+        data.entry_mut::<&mut GridMover>(mover).unwrap().request_move(GridDirection::Right);
+        advance_grid_movers(&mut data, 0.2);
+        data.apply_commands();
+        assert!((x_of(&mut data, mover) - 16.).abs() < 0.001);
+        assert!(data.entry::<&GridMover>(mover).unwrap().get().unwrap().has_arrived());
+
+        for _ in 0..3 {
+            advance_grid_movers(&mut data, 0.1);
+            data.apply_commands();
+        }
+        assert!((x_of(&mut data, mover) - 32.).abs() < 0.001);
+        let received: Vec<TileEntered> = data.events().poll(&subscriber).unwrap().into_iter().collect();
+        assert_eq!(1, received.len());
+        assert_eq!("DOOR", received[0].event_type());
+        assert_eq!((2, 0), received[0].position());
+        assert_eq!(mover, received[0].entity());
+
+        // The edge of the tilemap and the wall only make the mover face them
+        {
+            let grid_mover = data.entry_mut::<&mut GridMover>(mover).unwrap();
+            grid_mover.request_move(GridDirection::Up);
+            grid_mover.set_held_direction(None);
+        }
+        advance_grid_movers(&mut data, 0.1);
+        {
+            let mut entry = data.entry::<&GridMover>(mover).unwrap();
+            let grid_mover = entry.get().unwrap();
+            assert!(!grid_mover.is_moving());
+            assert_eq!(GridDirection::Up, grid_mover.facing());
+        }
+        data.entry_mut::<&mut GridMover>(mover).unwrap().request_move(GridDirection::Right);
+        advance_grid_movers(&mut data, 0.1);
+        let mut entry = data.entry::<&GridMover>(mover).unwrap();
+        let grid_mover = entry.get().unwrap();
+        assert!(!grid_mover.is_moving());
+        assert_eq!(GridDirection::Right, grid_mover.facing());
+        assert_eq!((2, 0), grid_mover.position());
+    }
+
+    #[test]
+    fn grid_movers_do_not_share_cells() {
+        let mut data = GameData::default();
+        let tilemap = grid(&mut data);
+        let first = data.push((
+            Transform::from_xy(0., 0.),
+            GridMover::new(tilemap, 16., 16., 0, 0).with_keyboard_controls(false),
+        ));
+        let _second = data.push((
+            Transform::from_xy(16., 0.),
+            GridMover::new(tilemap, 16., 16., 1, 0).with_keyboard_controls(false),
+        ));
+
+        data.entry_mut::<&mut GridMover>(first).unwrap().set_held_direction(Some(GridDirection::Right));
+        advance_grid_movers(&mut data, 0.1);
+        assert!(!data.entry::<&GridMover>(first).unwrap().get().unwrap().is_moving());
+        assert_eq!(0., x_of(&mut data, first));
+    }
+}
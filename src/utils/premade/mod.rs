@@ -1,2 +1,4 @@
 pub mod dummy_camera_controller;
-pub mod default_developper_console;
\ No newline at end of file
+pub mod default_developper_console;
+pub mod platformer_controller;
+pub mod grid_mover;
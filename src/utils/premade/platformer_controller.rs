@@ -0,0 +1,538 @@
+use hecs::Entity;
+
+use crate::core::components::maths::collider::{Collider, ColliderMask, ColliderShape, ColliderType};
+use crate::core::components::maths::coordinates::Coordinates;
+use crate::core::components::maths::transform::Transform;
+use crate::core::package::Package;
+use crate::core::resources::inputs::types::{Input, KeyCode};
+use crate::core::resources::physics::Physics;
+use crate::core::resources::physics_query::{PhysicsQuery, SweepHit};
+use crate::core::world::{GameData, ScionWorld, World};
+use crate::graphics::components::tiles::atlas::data::{TileObject, TilemapAtlas};
+use crate::graphics::components::tiles::tileset::Tileset;
+use crate::utils::maths::Vector;
+use crate::ScionBuilder;
+
+/// Class of the tilemap objects that should be spawned as one-way platforms
+pub const ONE_WAY_PLATFORM_CLASS: &str = "OneWayPlatform";
+
+/// Distance kept between the character and the colliders it touches
+const SKIN: f32 = 0.01;
+/// Maximum number of surfaces the character can slide along in one step
+const MAX_SLIDES: usize = 4;
+
+/// Shape of a character being moved during a step
+struct CastShape {
+    shape: ColliderShape,
+    moved: Vector,
+}
+
+impl CastShape {
+    fn translate(&mut self, dx: f32, dy: f32) {
+        self.moved.x += dx;
+        self.moved.y += dy;
+        self.shape.translate(dx, dy);
+    }
+}
+
+/// Marker component for colliders that only block characters falling on them from above
+pub struct OneWayPlatform;
+
+/// `PlatformerController` is a premade character controller for platformers, moving its entity at each fixed update.
+///
+/// The entity needs a `Transform` and a `Collider`. Its movement is computed with shape casts against the colliders
+/// whose mask is in `solid_masks`, so the character stays on the ground, walks on slopes, slides along walls and
+/// goes through `OneWayPlatform` colliders from below. It is controlled with the keyboard arrows and space,
+/// or from code using `set_horizontal_input` and `request_jump`.
+pub struct PlatformerController {
+    /// Gravity applied to the character, in pixels per second squared
+    gravity: f32,
+    /// Maximum horizontal speed, in pixels per second
+    max_speed: f32,
+    /// Horizontal acceleration while moving, in pixels per second squared
+    acceleration: f32,
+    /// Horizontal deceleration without input, in pixels per second squared
+    deceleration: f32,
+    /// Height of a jump, in pixels
+    jump_height: f32,
+    max_fall_speed: f32,
+    /// Duration, in seconds, during which the character can still jump after leaving the ground
+    coyote_time: f32,
+    /// Duration, in seconds, during which a jump requested before landing is kept
+    jump_buffer: f32,
+    /// Steepest slope, in degrees, the character can stand on
+    max_slope: f32,
+    /// Distance of the shape cast used to detect the ground
+    ground_check_distance: f32,
+    solid_masks: Vec<ColliderMask>,
+    keyboard_controls: bool,
+    horizontal_input: f32,
+    jump_request: Option<f32>,
+    velocity: Vector,
+    grounded: bool,
+    ground_normal: Vector,
+    time_since_grounded: f32,
+}
+
+impl Default for PlatformerController {
+    fn default() -> Self {
+        Self {
+            gravity: 980.,
+            max_speed: 200.,
+            acceleration: 1500.,
+            deceleration: 2000.,
+            jump_height: 64.,
+            max_fall_speed: 800.,
+            coyote_time: 0.1,
+            jump_buffer: 0.1,
+            max_slope: 50.,
+            ground_check_distance: 2.,
+            solid_masks: vec![ColliderMask::Landscape],
+            keyboard_controls: true,
+            horizontal_input: 0.,
+            jump_request: None,
+            velocity: Vector::default(),
+            grounded: false,
+            ground_normal: Vector::new(0., -1.),
+            time_since_grounded: f32::MAX,
+        }
+    }
+}
+
+impl PlatformerController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_gravity(mut self, gravity: f32) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    pub fn with_acceleration(mut self, acceleration: f32, deceleration: f32) -> Self {
+        self.acceleration = acceleration;
+        self.deceleration = deceleration;
+        self
+    }
+
+    pub fn with_jump_height(mut self, jump_height: f32) -> Self {
+        self.jump_height = jump_height;
+        self
+    }
+
+    pub fn with_max_fall_speed(mut self, max_fall_speed: f32) -> Self {
+        self.max_fall_speed = max_fall_speed;
+        self
+    }
+
+    pub fn with_coyote_time(mut self, coyote_time: f32) -> Self {
+        self.coyote_time = coyote_time;
+        self
+    }
+
+    pub fn with_jump_buffer(mut self, jump_buffer: f32) -> Self {
+        self.jump_buffer = jump_buffer;
+        self
+    }
+
+    /// Sets the steepest slope, in degrees, the character can stand and walk on. Steeper ones are handled as walls.
+    pub fn with_max_slope(mut self, max_slope: f32) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    pub fn with_ground_check_distance(mut self, ground_check_distance: f32) -> Self {
+        self.ground_check_distance = ground_check_distance;
+        self
+    }
+
+    /// Sets the masks of the colliders blocking the character. Default is `ColliderMask::Landscape`
+    pub fn with_solid_masks(mut self, solid_masks: Vec<ColliderMask>) -> Self {
+        self.solid_masks = solid_masks;
+        self
+    }
+
+    /// Enables or disables the keyboard controls (arrows to move, space or up to jump). Enabled by default
+    pub fn with_keyboard_controls(mut self, keyboard_controls: bool) -> Self {
+        self.keyboard_controls = keyboard_controls;
+        self
+    }
+
+    /// Sets the horizontal input, from -1 (left) to 1 (right)
+    pub fn set_horizontal_input(&mut self, input: f32) {
+        self.horizontal_input = input.clamp(-1., 1.);
+    }
+
+    /// Asks the character to jump. The request is kept during the jump buffer duration
+    pub fn request_jump(&mut self) {
+        self.jump_request = Some(0.);
+    }
+
+    pub fn velocity(&self) -> &Vector {
+        &self.velocity
+    }
+
+    pub fn set_velocity(&mut self, velocity: Vector) {
+        self.velocity = velocity;
+    }
+
+    /// Whether the character stood on the ground during the last step
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Normal of the ground under the character, pointing up
+    pub fn ground_normal(&self) -> &Vector {
+        &self.ground_normal
+    }
+
+    fn jump_velocity(&self) -> f32 {
+        (2. * self.gravity * self.jump_height).sqrt()
+    }
+
+    fn is_ground(&self, normal: &Vector) -> bool {
+        -normal.y >= self.max_slope.to_radians().cos()
+    }
+}
+
+/// This package adds the `PlatformerController` systems : keyboard inputs at each frame, and movement at each fixed update.
+pub struct Platformer;
+
+impl Package for Platformer {
+    fn load(&self, builder: ScionBuilder) -> ScionBuilder {
+        builder.with_system(platformer_input_system).with_fixed_system(platformer_controller_system)
+    }
+}
+
+/// Reads the keyboard inputs of the controllers using keyboard controls
+pub fn platformer_input_system(data: &mut GameData) {
+    let (world, resources) = data.split();
+    let inputs = resources.inputs();
+    let left = inputs.input_pressed(&Input::Key(KeyCode::Left));
+    let right = inputs.input_pressed(&Input::Key(KeyCode::Right));
+    let jump = inputs.input_pressed_event(&Input::Key(KeyCode::Space)) || inputs.input_pressed_event(&Input::Key(KeyCode::Up));
+
+    for (_, controller) in world.query_mut::<&mut PlatformerController>() {
+        if !controller.keyboard_controls {
+            continue;
+        }
+        controller.set_horizontal_input(if left { -1. } else { 0. } + if right { 1. } else { 0. });
+        if jump {
+            controller.request_jump();
+        }
+    }
+}
+
+/// Moves the entities having a `PlatformerController`, using the colliders of the `PhysicsQuery`
+pub fn platformer_controller_system(data: &mut GameData) {
+    let (world, resources, commands) = data.split_with_command();
+    let Some(query) = resources.get_resource::<PhysicsQuery>() else {
+        return;
+    };
+    let dt = resources.get_resource::<Physics>().map_or(1. / 60., |p| p.timestep());
+
+    for (entity, (transform, collider, controller)) in
+        world.query::<(&Transform, &Collider, &mut PlatformerController)>().iter()
+    {
+        let mut cast = CastShape { shape: collider.collider_shape(transform), moved: Vector::default() };
+        update_ground(&query, world, entity, &cast.shape, controller);
+        if controller.grounded {
+            controller.time_since_grounded = 0.;
+        } else {
+            controller.time_since_grounded += dt;
+        }
+
+        let target = controller.horizontal_input * controller.max_speed;
+        let rate = if controller.horizontal_input != 0. { controller.acceleration } else { controller.deceleration } * dt;
+        controller.velocity.x += (target - controller.velocity.x).clamp(-rate, rate);
+
+        if let Some(age) = controller.jump_request {
+            if controller.time_since_grounded <= controller.coyote_time {
+                controller.velocity.y = -controller.jump_velocity();
+                controller.jump_request = None;
+                controller.grounded = false;
+                controller.time_since_grounded = f32::MAX;
+            } else if age + dt > controller.jump_buffer {
+                controller.jump_request = None;
+            } else {
+                controller.jump_request = Some(age + dt);
+            }
+        }
+
+        let motion = if controller.grounded {
+            controller.velocity.y = 0.;
+            // Walks along the ground, so that slopes are climbed and descended at the same speed
+            let normal = controller.ground_normal;
+            let tangent = Vector::new(-normal.y, normal.x);
+            Vector::new(tangent.x * controller.velocity.x * dt, tangent.y * controller.velocity.x * dt)
+        } else {
+            let previous_y = controller.velocity.y;
+            controller.velocity.y = (controller.velocity.y + controller.gravity * dt).min(controller.max_fall_speed);
+            // The average speed of the step keeps the jump height exact whatever the timestep
+            Vector::new(controller.velocity.x * dt, (previous_y + controller.velocity.y) / 2. * dt)
+        };
+
+        for normal in move_and_slide(&query, world, entity, &mut cast, motion, &controller.solid_masks) {
+            if (controller.is_ground(&normal) && controller.velocity.y > 0.) || (normal.y > 0.5 && controller.velocity.y < 0.) {
+                controller.velocity.y = 0.;
+            } else if normal.x.abs() > 0.5 && controller.velocity.x * normal.x < 0. {
+                controller.velocity.x = 0.;
+            }
+        }
+
+        if controller.grounded {
+            // Keeps the character on the ground when walking down a slope
+            let snap = controller.ground_check_distance + motion.x.abs() * controller.max_slope.to_radians().tan();
+            let hit = first_blocking_hit(&query, world, entity, &cast.shape, Vector::new(0., 1.), snap, &controller.solid_masks);
+            if let Some(hit) = hit.filter(|h| controller.is_ground(h.normal())) {
+                cast.translate(0., (hit.distance() - SKIN).max(0.));
+            }
+        }
+
+        if cast.moved.x != 0. || cast.moved.y != 0. {
+            commands.transform_commands.append_vector(entity, cast.moved);
+        }
+    }
+}
+
+fn update_ground(
+    query: &PhysicsQuery,
+    world: &ScionWorld,
+    entity: Entity,
+    shape: &ColliderShape,
+    controller: &mut PlatformerController,
+) {
+    controller.grounded = false;
+    if controller.velocity.y < 0. {
+        return;
+    }
+    let hit = first_blocking_hit(
+        query,
+        world,
+        entity,
+        shape,
+        Vector::new(0., 1.),
+        controller.ground_check_distance,
+        &controller.solid_masks,
+    );
+    if let Some(hit) = hit.filter(|h| controller.is_ground(h.normal())) {
+        controller.grounded = true;
+        controller.ground_normal = *hit.normal();
+    }
+}
+
+/// Moves the shape along `motion`, sliding along the surfaces it touches, and returns their normals
+fn move_and_slide(
+    query: &PhysicsQuery,
+    world: &ScionWorld,
+    entity: Entity,
+    cast: &mut CastShape,
+    motion: Vector,
+    solid_masks: &[ColliderMask],
+) -> Vec<Vector> {
+    let mut normals = Vec::new();
+    let mut remaining = motion;
+    for _ in 0..MAX_SLIDES {
+        let length = (remaining.x * remaining.x + remaining.y * remaining.y).sqrt();
+        if length <= f32::EPSILON {
+            break;
+        }
+        let direction = Vector::new(remaining.x / length, remaining.y / length);
+        match first_blocking_hit(query, world, entity, &cast.shape, direction, length, solid_masks) {
+            None => {
+                cast.translate(remaining.x, remaining.y);
+                break;
+            }
+            Some(hit) => {
+                let travel = (hit.distance() - SKIN).max(0.);
+                cast.translate(direction.x * travel, direction.y * travel);
+                let normal = *hit.normal();
+                let left = length - travel;
+                let (rx, ry) = (direction.x * left, direction.y * left);
+                let dot = rx * normal.x + ry * normal.y;
+                remaining = Vector::new(rx - dot * normal.x, ry - dot * normal.y);
+                normals.push(normal);
+            }
+        }
+    }
+    normals
+}
+
+/// First collider blocking a shape cast, one-way platforms only blocking the shapes falling on them
+fn first_blocking_hit(
+    query: &PhysicsQuery,
+    world: &ScionWorld,
+    entity: Entity,
+    shape: &ColliderShape,
+    direction: Vector,
+    distance: f32,
+    solid_masks: &[ColliderMask],
+) -> Option<SweepHit> {
+    if solid_masks.is_empty() {
+        return None;
+    }
+    query.sweep_shape_all(shape, direction, distance, solid_masks, Some(entity)).into_iter().find(|hit| {
+        let one_way = world.entry::<&OneWayPlatform>(hit.entity()).is_ok_and(|mut e| e.get().is_some());
+        !one_way || (direction.y > 0. && hit.normal().y < -0.5)
+    })
+}
+
+/// Spawns a static `OneWayPlatform` collider for each object of the `ONE_WAY_PLATFORM_CLASS` class : the objects
+/// of the tilemap, and, when the tileset is given, the objects attached to the tiles of the tilemap.
+/// Rectangle and polygon objects are supported.
+pub fn spawn_one_way_platforms(
+    world: &mut impl World,
+    tilemap: &TilemapAtlas,
+    tileset: Option<&Tileset>,
+    tilemap_transform: &Transform,
+    mask: ColliderMask,
+) -> Vec<Entity> {
+    let origin = *tilemap_transform.global_translation();
+    let mut platforms: Vec<(Coordinates, &TileObject)> =
+        tilemap.get_objects().iter().map(|object| (origin, object)).collect();
+    if let Some(tileset) = tileset {
+        for layer in tilemap.layers.iter() {
+            for (y, row) in layer.tiles.iter().enumerate() {
+                for (x, tile) in row.iter().enumerate().filter(|(_, tile)| **tile >= 0) {
+                    let Some(config) = tileset.tiles.get(&(*tile as usize)) else { continue };
+                    let tile_origin = Coordinates::new(
+                        origin.x + (x * tilemap.tile_width) as f32,
+                        origin.y + (y * tilemap.tile_height) as f32,
+                    );
+                    platforms.extend(config.objects().iter().map(|object| (tile_origin, object)));
+                }
+            }
+        }
+    }
+
+    platforms
+        .into_iter()
+        .filter(|(_, object)| object.get_class().is_custom(ONE_WAY_PLATFORM_CLASS))
+        .filter_map(|(tile_origin, object)| {
+            let collider_type = if object.is_rect() {
+                ColliderType::RectangleCollider(object.get_rect().width() as usize, object.get_rect().height() as usize)
+            } else if object.has_polygon() {
+                ColliderType::PolygonCollider(object.get_polygon().clone())
+            } else {
+                return None;
+            };
+            let position = object.get_position();
+            Some(world.push((
+                Transform::from_xy(tile_origin.x + position.x, tile_origin.y + position.y),
+                Collider::new(mask.clone(), vec![], collider_type).with_static(),
+                OneWayPlatform,
+            )))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::systems::collider_systems::{collision_test_data, compute_collisions_system};
+
+    fn step(data: &mut GameData) {
+        compute_collisions_system(data);
+        platformer_controller_system(data);
+        data.apply_commands();
+    }
+
+    fn character(data: &mut GameData, x: f32, y: f32) -> Entity {
+        data.push((
+            Transform::from_xy(x, y),
+            Collider::new(ColliderMask::Character, vec![], ColliderType::RectangleCollider(10, 20)),
+            PlatformerController::new().with_keyboard_controls(false),
+        ))
+    }
+
+    fn y_of(data: &mut GameData, entity: Entity) -> f32 {
+        data.entry::<&Transform>(entity).unwrap().get().unwrap().translation().y()
+    }
+
+    #[test]
+    fn character_lands_and_jumps() {
+        let mut data = collision_test_data();
+        data.insert_resource(PhysicsQuery::default());
+        let player = character(&mut data, 0., 0.);
+        data.push((
+            Transform::from_xy(-100., 50.),
+            Collider::new(ColliderMask::Landscape, vec![], ColliderType::RectangleCollider(300, 10)),
+        ));
+
+        for _ in 0..60 {
+            step(&mut data);
+        }
+        assert!((y_of(&mut data, player) - 30.).abs() < 0.1);
+        assert!(data.entry::<&PlatformerController>(player).unwrap().get().unwrap().is_grounded());
+
+        data.entry_mut::<&mut PlatformerController>(player).unwrap().request_jump();
+        let mut highest = f32::MAX;
+        for _ in 0..20 {
+            step(&mut data);
+            highest = highest.min(y_of(&mut data, player));
+        }
+        assert!((30. - highest - 64.).abs() < 3., "jump height = {}", 30. - highest);
+    }
+
+    #[test]
+    fn character_walks_up_slope() {
+        let mut data = collision_test_data();
+        data.insert_resource(PhysicsQuery::default());
+        let player = character(&mut data, 0., 0.);
+        data.push((
+            Transform::from_xy(0., 0.),
+            Collider::new(
+                ColliderMask::Landscape,
+                vec![],
+                ColliderType::PolygonCollider(vec![
+                    Coordinates::new(-100., 50.),
+                    Coordinates::new(100., 50.),
+                    Coordinates::new(300., -50.),
+                    Coordinates::new(300., 100.),
+                    Coordinates::new(-100., 100.),
+                ]),
+            ),
+        ));
+        for _ in 0..60 {
+            step(&mut data);
+        }
+        data.entry_mut::<&mut PlatformerController>(player).unwrap().set_horizontal_input(1.);
+        for _ in 0..60 {
+            step(&mut data);
+        }
+        let x = data.entry::<&Transform>(player).unwrap().get().unwrap().translation().x();
+        assert!(x > 120., "character should go up the slope, x = {}", x);
+        assert!(y_of(&mut data, player) < 0.);
+        assert!(data.entry::<&PlatformerController>(player).unwrap().get().unwrap().is_grounded());
+    }
+
+    #[test]
+    fn one_way_platform_from_tilemap_objects() {
+        let tilemap: TilemapAtlas = serde_json::from_str(
+            r#"{"width": 1, "height": 1, "tile_width": 16, "tile_height": 16, "properties": {}, "layers": [],
+            "tilesets": [], "tilemap_type": null, "objects": [
+                {"class": {"tag": "Custom", "content": "OneWayPlatform"}, "shape_type": "Rectangle",
+                 "position": {"x": -50, "y": 0, "z": 0}, "polygon": null, "rectangle": {"width": 100, "height": 5}},
+                {"class": {"tag": "Door"}, "shape_type": "Point", "position": {"x": 0, "y": 0, "z": 0}, "polygon": null, "rectangle": null}
+            ]}"#,
+        )
+        .unwrap();
+        let mut data = collision_test_data();
+        data.insert_resource(PhysicsQuery::default());
+        let platforms = spawn_one_way_platforms(&mut data, &tilemap, None, &Transform::from_xy(0., 0.), ColliderMask::Landscape);
+        assert_eq!(1, platforms.len());
+
+        // Jumping from below goes through the platform, and the character then lands on it
+        let player = character(&mut data, 0., 10.);
+        data.entry_mut::<&mut PlatformerController>(player).unwrap().set_velocity(Vector::new(0., -400.));
+        for _ in 0..90 {
+            step(&mut data);
+        }
+        assert!((y_of(&mut data, player) + 20.).abs() < 0.1, "y = {}", y_of(&mut data, player));
+    }
+}